@@ -52,6 +52,8 @@ impl Algorithm {
 }
 
 /// 128-bit AES key.
+///
+/// The key is redacted when formatted with defmt.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Key128 {
     key: [u32; 4],
@@ -99,6 +101,13 @@ impl Key128 {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Key128 {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Key128 {{ .. }}")
+    }
+}
+
 impl From<[u32; 4]> for Key128 {
     fn from(key: [u32; 4]) -> Self {
         Key128::from_u32(key)
@@ -112,6 +121,8 @@ impl From<u128> for Key128 {
 }
 
 /// 256-bit AES key.
+///
+/// The key is redacted when formatted with defmt.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Key256 {
     key: [u32; 8],
@@ -126,7 +137,17 @@ impl Key256 {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Key256 {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Key256 {{ .. }}")
+    }
+}
+
 /// AES key sizes.
+///
+/// The key is redacted when formatted with defmt.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Key {
     /// 128-bit key
     K128(Key128),
@@ -197,6 +218,7 @@ impl From<Mode> for u8 {
 
 /// AES errors.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive] // add timeout at some point
 pub enum Error {
     /// Unexpected read operation from the AES_DOUTR register
@@ -212,6 +234,13 @@ pub struct Aes {
     aes: pac::AES,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Aes {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Aes {{ .. }}")
+    }
+}
+
 impl Aes {
     /// Create a new AES driver from an AES peripheral.
     ///
@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU8, Ordering::SeqCst};
+use cortex_m::peripheral::SCB;
+use cortex_m_rt::exception;
+use defmt_rtt as _; // global logger
+use panic_probe as _;
+use stm32wl_hal::{
+    hsem::{CoreId, Hsem, Semaphore},
+    pac,
+};
+
+const SEM: Semaphore = Semaphore::SUBGHZ;
+
+const PENDSV_NONE: u8 = 0;
+const PENDSV_LOCKED: u8 = 1;
+const PENDSV_BUSY: u8 = 2;
+
+/// PendSV exception number, the process ID of locks taken in the handler.
+const PENDSV_PROCID: u8 = 14;
+
+/// Result of the lock attempt in the PendSV handler.
+static PENDSV_RESULT: AtomicU8 = AtomicU8::new(PENDSV_NONE);
+
+/// Lock the semaphore from the PendSV handler, and keep it locked.
+///
+/// This is a lock from another context on the same core.
+#[exception]
+fn PendSV() {
+    let hsem: Hsem = unsafe { Hsem::steal() };
+    let result: u8 = match hsem.try_lock(SEM) {
+        Some(guard) => {
+            defmt::assert_eq!(guard.procid(), PENDSV_PROCID);
+            core::mem::forget(guard);
+            PENDSV_LOCKED
+        }
+        None => PENDSV_BUSY,
+    };
+    PENDSV_RESULT.store(result, SeqCst);
+}
+
+fn pendsv() -> u8 {
+    PENDSV_RESULT.store(PENDSV_NONE, SeqCst);
+    SCB::set_pendsv();
+    cortex_m::asm::isb();
+    PENDSV_RESULT.load(SeqCst)
+}
+
+#[defmt_test::tests]
+mod tests {
+    use super::*;
+
+    #[init]
+    fn init() -> Hsem {
+        let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+        let rcc = dp.RCC;
+
+        rcc.ahb3enr.modify(|_, w| w.hsemen().set_bit());
+        rcc.ahb3enr.read(); // Delay after an RCC peripheral clock enabling
+
+        let mut hsem: Hsem = Hsem::new(dp.HSEM);
+        hsem.release_all();
+        hsem
+    }
+
+    #[test]
+    fn lock_unlock(hsem: &mut Hsem) {
+        defmt::assert_eq!(hsem.owner(SEM), None);
+        let guard = hsem.lock(SEM);
+        defmt::assert_eq!(guard.procid(), 0);
+        defmt::assert_eq!(hsem.owner(SEM), Some(CoreId::CURRENT));
+        drop(guard);
+        defmt::assert!(!hsem.is_locked(SEM));
+    }
+
+    #[test]
+    fn no_second_guard_same_context(hsem: &mut Hsem) {
+        let guard = hsem.lock(SEM);
+        defmt::assert!(hsem.try_lock(SEM).is_none());
+        drop(guard);
+    }
+
+    #[test]
+    fn interrupt_cannot_take_thread_lock(hsem: &mut Hsem) {
+        let guard = hsem.lock(SEM);
+        defmt::assert_eq!(pendsv(), PENDSV_BUSY);
+        drop(guard);
+        defmt::assert!(!hsem.is_locked(SEM));
+    }
+
+    #[test]
+    fn thread_cannot_take_interrupt_lock(hsem: &mut Hsem) {
+        defmt::assert_eq!(pendsv(), PENDSV_LOCKED);
+        defmt::assert_eq!(hsem.owner(SEM), Some(CoreId::CURRENT));
+        defmt::assert!(hsem.try_lock(SEM).is_none());
+
+        // unlocking requires the process ID of the interrupt
+        hsem.release_all();
+        defmt::assert!(!hsem.is_locked(SEM));
+        defmt::assert!(hsem.try_lock(SEM).is_some());
+    }
+}
@@ -0,0 +1,417 @@
+//! Hardware semaphore.
+//!
+//! The hardware semaphore (HSEM) arbitrates access to resources shared
+//! between the CPU1 (Cortex-M4) and CPU2 (Cortex-M0+) cores of the STM32WL5x.
+//!
+//! Each core uses its own [`Hsem`] driver, a semaphore is acquired with
+//! [`Hsem::lock`] or [`Hsem::try_lock`] which return a [`Guard`] that
+//! releases the semaphore when it is dropped.
+//!
+//! # Example
+//!
+//! Sharing the radio between cores, the same code runs on both cores.
+//!
+//! ```no_run
+//! use stm32wl_hal_hsem::{pac, Hsem, Semaphore};
+//!
+//! let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+//! let mut rcc = dp.RCC;
+//!
+//! rcc.ahb3enr.modify(|_, w| w.hsemen().set_bit());
+//! rcc.ahb3enr.read(); // Delay after an RCC peripheral clock enabling
+//!
+//! let hsem: Hsem = Hsem::new(dp.HSEM);
+//!
+//! {
+//!     let _guard = hsem.lock(Semaphore::SUBGHZ);
+//!     // ... exclusive access to the radio until _guard is dropped
+//! }
+//! ```
+#![cfg_attr(not(test), no_std)]
+
+use core::sync::atomic::{compiler_fence, Ordering::SeqCst};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "stm32wl5x_cm0p")] {
+        /// Peripheral access crate.
+        pub use stm32wl::stm32wl5x_cm0p as pac;
+    } else if #[cfg(feature = "stm32wl5x_cm4")] {
+        /// Peripheral access crate.
+        pub use stm32wl::stm32wl5x_cm4 as pac;
+    } else if #[cfg(feature = "stm32wle5")] {
+        /// Peripheral access crate.
+        pub use stm32wl::stm32wle5 as pac;
+    } else {
+        core::compile_error!("You must select your hardware with a feature flag");
+    }
+}
+
+// HSEM_Rx bits
+const R_LOCK: u32 = 1 << 31;
+const R_COREID_MASK: u32 = 0xF << 8;
+const R_COREID_POS: u32 = 8;
+
+// HSEM_KEYR bits
+const KEYR_KEY_MASK: u32 = 0xFFFF_0000;
+
+/// Process ID of the current execution context.
+///
+/// This is the active exception number from `SCB_ICSR.VECTACTIVE`, `0` in
+/// thread mode.
+/// An exception cannot preempt itself, so every context that can run
+/// concurrently on a core has a different process ID.
+/// The STM32WL has fewer than 240 interrupts, the exception number fits in
+/// the 8-bit `PROCID` field.
+fn current_procid() -> u8 {
+    (unsafe { (*pac::SCB::PTR).icsr.read() } & 0x1FF) as u8
+}
+
+/// Core identifiers.
+///
+/// See table 5 "Bus master IDs" in the reference manual.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CoreId {
+    /// CPU1, the Cortex-M4.
+    Cpu1 = 0x4,
+    /// CPU2, the Cortex-M0+.
+    Cpu2 = 0x8,
+}
+
+impl CoreId {
+    /// Identifier of the core this code was compiled for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_hsem::CoreId;
+    ///
+    /// #[cfg(not(feature = "stm32wl5x_cm0p"))]
+    /// assert_eq!(CoreId::CURRENT, CoreId::Cpu1);
+    /// #[cfg(feature = "stm32wl5x_cm0p")]
+    /// assert_eq!(CoreId::CURRENT, CoreId::Cpu2);
+    /// ```
+    pub const CURRENT: CoreId = if cfg!(feature = "stm32wl5x_cm0p") {
+        CoreId::Cpu2
+    } else {
+        CoreId::Cpu1
+    };
+
+    /// Create a core ID from the `COREID` field of a semaphore register.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_hsem::CoreId;
+    ///
+    /// assert_eq!(CoreId::from_raw(0x4), Ok(CoreId::Cpu1));
+    /// assert_eq!(CoreId::from_raw(0x8), Ok(CoreId::Cpu2));
+    /// assert_eq!(CoreId::from_raw(0x0), Err(0x0));
+    /// ```
+    pub const fn from_raw(bits: u8) -> Result<CoreId, u8> {
+        match bits {
+            0x4 => Ok(CoreId::Cpu1),
+            0x8 => Ok(CoreId::Cpu2),
+            _ => Err(bits),
+        }
+    }
+}
+
+/// Hardware semaphores.
+///
+/// The associated constants assign semaphores to the peripherals shared
+/// between cores.
+/// These assignments are a convention of this HAL, firmware on both cores
+/// must use the same assignments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum Semaphore {
+    Sem0 = 0,
+    Sem1 = 1,
+    Sem2 = 2,
+    Sem3 = 3,
+    Sem4 = 4,
+    Sem5 = 5,
+    Sem6 = 6,
+    Sem7 = 7,
+    Sem8 = 8,
+    Sem9 = 9,
+    Sem10 = 10,
+    Sem11 = 11,
+    Sem12 = 12,
+    Sem13 = 13,
+    Sem14 = 14,
+    Sem15 = 15,
+}
+
+impl Semaphore {
+    /// Semaphore for the RNG peripheral.
+    pub const RNG: Semaphore = Semaphore::Sem0;
+    /// Semaphore for the PKA peripheral.
+    pub const PKA: Semaphore = Semaphore::Sem1;
+    /// Semaphore for the AES peripheral.
+    pub const AES: Semaphore = Semaphore::Sem2;
+    /// Semaphore for the sub-GHz radio.
+    pub const SUBGHZ: Semaphore = Semaphore::Sem3;
+
+    const fn idx(self) -> usize {
+        self as usize
+    }
+}
+
+/// Hardware semaphore driver.
+pub struct Hsem {
+    hsem: pac::HSEM,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hsem {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Hsem {{ .. }}")
+    }
+}
+
+impl Hsem {
+    /// Create a new `Hsem` driver from a HSEM peripheral.
+    ///
+    /// This will not reset the HSEM, semaphores held by the other core are
+    /// left as-is.
+    /// This will not enable clocks for the HSEM, CPU1 enables the clock in
+    /// `RCC_AHB3ENR`, CPU2 enables the clock in `RCC_C2AHB3ENR`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{pac, Hsem};
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let mut rcc = dp.RCC;
+    ///
+    /// rcc.ahb3enr.modify(|_, w| w.hsemen().set_bit());
+    /// rcc.ahb3enr.read(); // Delay after an RCC peripheral clock enabling
+    ///
+    /// let hsem: Hsem = Hsem::new(dp.HSEM);
+    /// ```
+    pub fn new(hsem: pac::HSEM) -> Hsem {
+        Hsem { hsem }
+    }
+
+    /// Free the HSEM peripheral from the driver.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{pac, Hsem};
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let hsem: Hsem = Hsem::new(dp.HSEM);
+    /// // ... use hsem
+    /// let hsem_dp: pac::HSEM = hsem.free();
+    /// ```
+    pub fn free(self) -> pac::HSEM {
+        self.hsem
+    }
+
+    /// Steal the HSEM peripheral from whatever is currently using it.
+    ///
+    /// # Safety
+    ///
+    /// This will create a new HSEM peripheral, bypassing the singleton checks
+    /// that normally occur.
+    /// You are responsible for ensuring that semaphores acquired through the
+    /// stolen driver are not released by another driver on the same core.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_hsem::Hsem;
+    ///
+    /// // ... setup happens here
+    ///
+    /// let hsem = unsafe { Hsem::steal() };
+    /// ```
+    pub unsafe fn steal() -> Hsem {
+        let dp: pac::Peripherals = pac::Peripherals::steal();
+        Hsem { hsem: dp.HSEM }
+    }
+
+    /// Try to acquire a semaphore.
+    ///
+    /// This uses the 2-step lock procedure, the semaphore is locked with the
+    /// [`CoreId::CURRENT`] and the active exception number as the process ID,
+    /// `0` in thread mode.
+    /// The process ID distinguishes thread mode and interrupt handlers on the
+    /// same core, an interrupt that takes the semaphore while this function
+    /// runs makes it return `None`.
+    ///
+    /// Returns `None` if the semaphore is already locked, including when it is
+    /// locked by the current core.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{Hsem, Semaphore};
+    ///
+    /// let hsem = unsafe { Hsem::steal() };
+    ///
+    /// if let Some(_guard) = hsem.try_lock(Semaphore::AES) {
+    ///     // ... use AES
+    /// };
+    /// ```
+    pub fn try_lock(&self, sem: Semaphore) -> Option<Guard<'_>> {
+        // the read back after the write also matches when this context
+        // already holds the semaphore, this would create a second guard
+        if self.is_locked(sem) {
+            return None;
+        }
+
+        let procid: u8 = current_procid();
+        let val: u32 = R_LOCK | ((CoreId::CURRENT as u32) << R_COREID_POS) | u32::from(procid);
+        // the write is ignored if another core or context locked the
+        // semaphore after the check above
+        self.hsem.r[sem.idx()].write(|w| unsafe { w.bits(val) });
+        let r: u32 = self.hsem.r[sem.idx()].read().bits();
+        compiler_fence(SeqCst);
+        if r == val {
+            Some(Guard {
+                hsem: self,
+                sem,
+                procid,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Acquire a semaphore, spinning until it is available.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{Hsem, Semaphore};
+    ///
+    /// let hsem = unsafe { Hsem::steal() };
+    ///
+    /// let guard = hsem.lock(Semaphore::PKA);
+    /// // ... use PKA
+    /// drop(guard);
+    /// ```
+    pub fn lock(&self, sem: Semaphore) -> Guard<'_> {
+        loop {
+            if let Some(guard) = self.try_lock(sem) {
+                return guard;
+            }
+        }
+    }
+
+    /// Returns `true` if the semaphore is locked by either core.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{Hsem, Semaphore};
+    ///
+    /// let hsem = unsafe { Hsem::steal() };
+    ///
+    /// let guard = hsem.lock(Semaphore::RNG);
+    /// assert!(hsem.is_locked(Semaphore::RNG));
+    /// drop(guard);
+    /// ```
+    pub fn is_locked(&self, sem: Semaphore) -> bool {
+        self.hsem.r[sem.idx()].read().bits() & R_LOCK != 0
+    }
+
+    /// Returns the core holding the semaphore, or `None` if the semaphore
+    /// is free.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::{CoreId, Hsem, Semaphore};
+    ///
+    /// let hsem = unsafe { Hsem::steal() };
+    ///
+    /// let guard = hsem.lock(Semaphore::SUBGHZ);
+    /// assert_eq!(hsem.owner(Semaphore::SUBGHZ), Some(CoreId::CURRENT));
+    /// drop(guard);
+    /// ```
+    pub fn owner(&self, sem: Semaphore) -> Option<CoreId> {
+        let r: u32 = self.hsem.r[sem.idx()].read().bits();
+        if r & R_LOCK == 0 {
+            None
+        } else {
+            CoreId::from_raw(((r & R_COREID_MASK) >> R_COREID_POS) as u8).ok()
+        }
+    }
+
+    /// Release all semaphores held by the current core, in any context.
+    ///
+    /// This is intended for recovery, for example after a guard was leaked
+    /// with [`core::mem::forget`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_hsem::Hsem;
+    ///
+    /// let mut hsem = unsafe { Hsem::steal() };
+    /// hsem.release_all();
+    /// ```
+    pub fn release_all(&mut self) {
+        let key: u32 = self.hsem.keyr.read().bits() & KEYR_KEY_MASK;
+        compiler_fence(SeqCst);
+        let val: u32 = key | ((CoreId::CURRENT as u32) << R_COREID_POS);
+        self.hsem.cr.write(|w| unsafe { w.bits(val) });
+    }
+
+    fn unlock(&self, sem: Semaphore, procid: u8) {
+        // complete accesses to the shared resource before releasing
+        compiler_fence(SeqCst);
+        // LOCK cleared, COREID and PROCID of the lock
+        let val: u32 = ((CoreId::CURRENT as u32) << R_COREID_POS) | u32::from(procid);
+        self.hsem.r[sem.idx()].write(|w| unsafe { w.bits(val) });
+    }
+}
+
+/// Semaphore lock, the semaphore is released when the guard is dropped.
+///
+/// Created by [`Hsem::lock`] and [`Hsem::try_lock`].
+#[must_use = "the semaphore is released when the guard is dropped"]
+pub struct Guard<'a> {
+    hsem: &'a Hsem,
+    sem: Semaphore,
+    procid: u8,
+}
+
+#[cfg(feature = "defmt")]
+impl<'a> defmt::Format for Guard<'a> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Guard {{ sem: {}, procid: {} }}",
+            self.sem,
+            self.procid
+        )
+    }
+}
+
+impl<'a> Guard<'a> {
+    /// Semaphore held by this guard.
+    pub fn semaphore(&self) -> Semaphore {
+        self.sem
+    }
+
+    /// Process ID the semaphore was locked with.
+    pub fn procid(&self) -> u8 {
+        self.procid
+    }
+}
+
+impl<'a> Drop for Guard<'a> {
+    fn drop(&mut self) {
+        self.hsem.unlock(self.sem, self.procid)
+    }
+}
@@ -5,6 +5,7 @@ use cortex_m::interrupt::CriticalSection;
 /// GPIO output types.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OutputType {
     /// Push-pull output.
     PushPull = 0b0,
@@ -20,6 +21,7 @@ pub enum OutputType {
 /// supply and load conditions for each speed.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(missing_docs)]
 pub enum Speed {
     Low = 0b00,
@@ -31,6 +33,7 @@ pub enum Speed {
 /// GPIO pull-up and pull-down.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive] // maybe they will use that reserved value one day...
 pub enum Pull {
     /// No pull-up, no pull-down.
@@ -42,6 +45,7 @@ pub enum Pull {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 struct Pin<const BASE: usize, const N: u8> {}
 
 impl<const BASE: usize, const N: u8> Pin<BASE, N> {
@@ -208,6 +212,7 @@ pub mod pins {
         ($name:ident, $base:expr, $n:expr, $doc:expr) => {
             #[doc=$doc]
             #[derive(Debug)]
+            #[cfg_attr(feature = "defmt", derive(defmt::Format))]
             pub struct $name {
                 pin: Pin<$base, $n>,
             }
@@ -382,6 +387,7 @@ pub mod pins {
 
 /// Port A GPIOs
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(missing_docs)]
 pub struct PortA {
     pub pa0: pins::A0,
@@ -451,6 +457,7 @@ impl PortA {
 
 /// Port B GPIOs
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(missing_docs)]
 pub struct PortB {
     pub pb0: pins::B0,
@@ -520,6 +527,7 @@ impl PortB {
 
 /// Port C GPIOs
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(missing_docs)]
 pub struct PortC {
     pub pc0: pins::C0,
@@ -578,6 +586,7 @@ impl PortC {
 
 /// Digital input or output level.
 #[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Level {
     /// GPIO logic low.
     Low,
@@ -608,6 +617,7 @@ impl Level {
 ///
 /// Argument of [`Output::new`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OutputArgs {
     /// Output speed.
     pub speed: Speed,
@@ -651,6 +661,7 @@ impl Default for OutputArgs {
 
 /// Output pin.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Output<P> {
     pin: P,
 }
@@ -821,6 +832,7 @@ where
 
 /// Input pin.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Input<P> {
     pin: P,
 }
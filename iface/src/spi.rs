@@ -7,6 +7,7 @@ use core::ptr::{read_volatile, write_volatile};
 
 /// SPI errors.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Frame format error.
     Framing,
@@ -31,6 +32,24 @@ pub struct Spi1<MOSI, MISO, SCK> {
     sck: SCK,
 }
 
+#[cfg(feature = "defmt")]
+impl<MOSI, MISO, SCK> defmt::Format for Spi1<MOSI, MISO, SCK>
+where
+    MOSI: defmt::Format,
+    MISO: defmt::Format,
+    SCK: defmt::Format,
+{
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Spi1 {{ mosi: {}, miso: {}, sck: {} }}",
+            self.mosi,
+            self.miso,
+            self.sck
+        )
+    }
+}
+
 impl<MOSI, MISO, SCK> Spi1<MOSI, MISO, SCK>
 where
     MOSI: gpio::sealed::Spi1Mosi,
@@ -37,6 +37,7 @@ enum Error {
 
 /// Errors from an ECDSA signing operation.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum EcdsaSignError {
     /// Address access is out of range (unmapped address).
     Address,
@@ -66,6 +67,7 @@ impl From<Error> for EcdsaSignError {
 
 /// Errors from an ECDSA verify operation.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum EcdsaVerifyError {
     /// Address access is out of range (unmapped address).
     Address,
@@ -142,6 +144,13 @@ pub struct Pka {
     pka: pac::PKA,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pka {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Pka {{ .. }}")
+    }
+}
+
 impl Pka {
     const BASE: usize = 0x5800_2000;
     const RAM_BASE: usize = Self::BASE + 0x400;
@@ -476,6 +485,7 @@ impl Pka {
 /// Sign bit for ECDSA coefficient signing and verification.
 #[repr(u32)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Sign {
     /// Positive.
     Pos = 0,
@@ -498,6 +508,18 @@ pub struct EcdsaSignature<const MODULUS_SIZE: usize> {
     pub s_sign: [u32; MODULUS_SIZE],
 }
 
+#[cfg(feature = "defmt")]
+impl<const MODULUS_SIZE: usize> defmt::Format for EcdsaSignature<MODULUS_SIZE> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "EcdsaSignature {{ r_sign: {}, s_sign: {} }}",
+            &self.r_sign[..],
+            &self.s_sign[..],
+        )
+    }
+}
+
 /// ECDSA public key.
 pub struct EcdsaPublicKey<const MODULUS_SIZE: usize> {
     /// Public-key curve point xQ.
@@ -506,6 +528,18 @@ pub struct EcdsaPublicKey<const MODULUS_SIZE: usize> {
     pub curve_pt_y: [u32; MODULUS_SIZE],
 }
 
+#[cfg(feature = "defmt")]
+impl<const MODULUS_SIZE: usize> defmt::Format for EcdsaPublicKey<MODULUS_SIZE> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "EcdsaPublicKey {{ curve_pt_x: {}, curve_pt_y: {} }}",
+            &self.curve_pt_x[..],
+            &self.curve_pt_y[..],
+        )
+    }
+}
+
 /// Elliptic curve.
 ///
 /// Used to ECDSA signing and verification.
@@ -537,6 +571,24 @@ pub struct EllipticCurve<const MODULUS_SIZE: usize, const PRIME_ORDER_SIZE: usiz
     pub prime_order: [u32; PRIME_ORDER_SIZE],
 }
 
+#[cfg(feature = "defmt")]
+impl<const MODULUS_SIZE: usize, const PRIME_ORDER_SIZE: usize> defmt::Format
+    for EllipticCurve<MODULUS_SIZE, PRIME_ORDER_SIZE>
+{
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "EllipticCurve {{ coef_sign: {}, coef: {}, modulus: {}, base_point_x: {}, base_point_y: {}, prime_order: {} }}",
+            self.coef_sign,
+            &self.coef[..],
+            &self.modulus[..],
+            &self.base_point_x[..],
+            &self.base_point_y[..],
+            &self.prime_order[..],
+        )
+    }
+}
+
 /// Pre-defined elliptic curves.
 pub mod curve {
     use super::{
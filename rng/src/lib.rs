@@ -26,6 +26,7 @@ cfg_if::cfg_if! {
 
 /// RNG error types
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// A noise error (seed error) occured and automatic correction failed.
     UncorrectableNoise,
@@ -53,6 +54,13 @@ pub struct Rng {
     err_cnt: u32,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Rng {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Rng {{ err_cnt: {} }}", self.err_cnt)
+    }
+}
+
 impl Rng {
     /// Create a new `Rng` driver from a RNG peripheral.
     ///
@@ -0,0 +1,555 @@
+use crate::{
+    irq::IRQ_ALL,
+    protocol::{mtu_len, RadioState},
+    Irq, ModemCfg, StandbyClk, SubGhz, SubGhzError, Timeout,
+};
+use core::time::Duration;
+
+/// Length of the link-layer header in bytes.
+///
+/// The header is:
+///
+/// | Byte | Field                             |
+/// |------|-----------------------------------|
+/// |    0 | Frame type, data (0) or ACK (1)   |
+/// |    1 | Sequence number                   |
+pub const ARQ_HEADER_LEN: usize = 2;
+
+const FRAME_DATA: u8 = 0x00;
+const FRAME_ACK: u8 = 0x01;
+
+/// Acknowledged link errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArqError {
+    /// A frame is already waiting for an acknowledgement.
+    Busy,
+    /// The payload does not fit in a frame of `MTU` bytes.
+    PayloadLen,
+    /// The received frame is invalid.
+    Frame,
+    /// The received payload does not fit in the message buffer.
+    BufferTooSmall,
+}
+
+impl From<SubGhzError> for ArqError {
+    fn from(e: SubGhzError) -> Self {
+        match e {}
+    }
+}
+
+/// Acknowledged link events.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArqEvent {
+    /// A new payload was received, the inner value is the payload length.
+    ///
+    /// Retransmissions of a payload that was already received are
+    /// acknowledged, but not reported again.
+    Received(usize),
+    /// The frame with the inner sequence number was acknowledged.
+    Acked(u8),
+    /// The frame with the inner sequence number was not acknowledged after
+    /// all retransmissions.
+    Failed(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct Pending {
+    seq: u8,
+    len: u8,
+    attempts: u8,
+    sent_at: Option<u32>,
+}
+
+/// Point-to-point acknowledged link with retransmissions (ARQ).
+///
+/// One data frame at a time is sent and retransmitted until the peer
+/// acknowledges it, or until the retransmissions are exhausted (stop and
+/// wait).
+/// Each data frame carries a sequence number, a receiver acknowledges every
+/// data frame, and drops data frames with the same sequence number as the
+/// previous frame, which are retransmissions after a lost ACK.
+///
+/// `MTU` is the maximum frame length including the [`ARQ_HEADER_LEN`] byte
+/// header, and must not exceed 255.
+/// Frames vary in length, use a variable length header in the packet
+/// parameters.
+///
+/// Timing uses caller defined ticks, see
+/// [protocol layers](crate#protocol-layers).
+/// The ACK timeout is measured from the start of the data frame, and is
+/// sized from the time on air of a `MTU` byte data frame and of an ACK frame,
+/// plus a turnaround time for the peer to process the frame and switch to
+/// transmit.
+///
+/// [`poll_tx`], [`receive`], and [`poll`] are the transport interface,
+/// [`SubGhz::poll_arq`] drives them with the sub-GHz radio, and works with
+/// the LoRa and (G)FSK modems.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{Arq, ArqEvent, FskModParams, GenericPacketParams, ModemCfg};
+///
+/// const MODEM_CFG: ModemCfg = ModemCfg::Fsk {
+///     mod_params: FskModParams::new(),
+///     packet_params: GenericPacketParams::new(),
+/// };
+///
+/// // 1 kHz tick
+/// let mut alice: Arq<64> = Arq::new(MODEM_CFG, 1000).set_retries(5);
+/// let mut bob: Arq<64> = Arq::new(MODEM_CFG, 1000);
+///
+/// let seq: u8 = alice.send(b"hello").unwrap();
+///
+/// let mut frame: [u8; 64] = [0; 64];
+/// let mut msg: [u8; 64] = [0; 64];
+///
+/// let len: usize = alice.poll_tx(0, &mut frame).unwrap();
+/// assert_eq!(bob.receive(&frame[..len], &mut msg), Ok(Some(ArqEvent::Received(5))));
+/// assert_eq!(&msg[..5], b"hello");
+///
+/// let len: usize = bob.poll_tx(5, &mut frame).unwrap();
+/// assert_eq!(alice.receive(&frame[..len], &mut msg), Ok(Some(ArqEvent::Acked(seq))));
+/// assert!(!alice.is_busy());
+/// ```
+///
+/// [`poll_tx`]: Arq::poll_tx
+/// [`receive`]: Arq::receive
+/// [`poll`]: Arq::poll
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Arq<const MTU: usize> {
+    cfg: ModemCfg,
+    tick_hz: u32,
+    retries: u8,
+    turnaround: u32,
+    ack_timeout: u32,
+    buf: [u8; MTU],
+    pending: Option<Pending>,
+    next_seq: u8,
+    rx_seq: Option<u8>,
+    ack: Option<u8>,
+    radio: RadioState,
+    rx_busy_at: Option<u32>,
+    retransmits: u32,
+    duplicates: u32,
+}
+
+#[cfg(feature = "defmt")]
+impl<const MTU: usize> defmt::Format for Arq<MTU> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Arq {{ pending: {}, next_seq: {}, retransmits: {}, duplicates: {} }}",
+            self.pending.is_some(),
+            self.next_seq,
+            self.retransmits,
+            self.duplicates
+        )
+    }
+}
+
+impl<const MTU: usize> Arq<MTU> {
+    /// Create a new link.
+    ///
+    /// `cfg` is the modem configuration used to size the ACK timeout, and by
+    /// [`SubGhz::poll_arq`] for the packet parameters, the payload length is
+    /// replaced with the length of each frame.
+    ///
+    /// `tick_hz` is the tick frequency.
+    ///
+    /// The default is 3 retransmissions and a 10 ms turnaround.
+    pub const fn new(cfg: ModemCfg, tick_hz: u32) -> Arq<MTU> {
+        Arq {
+            cfg,
+            tick_hz,
+            retries: 3,
+            turnaround: 0,
+            ack_timeout: 0,
+            buf: [0; MTU],
+            pending: None,
+            next_seq: 0,
+            rx_seq: None,
+            ack: None,
+            radio: RadioState::Idle,
+            rx_busy_at: None,
+            retransmits: 0,
+            duplicates: 0,
+        }
+        .set_turnaround(tick_hz / 100)
+    }
+
+    /// Set the number of retransmissions before a frame is reported as
+    /// [`ArqEvent::Failed`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{Arq, FskModParams, GenericPacketParams, ModemCfg};
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::Fsk {
+    ///     mod_params: FskModParams::new(),
+    ///     packet_params: GenericPacketParams::new(),
+    /// };
+    ///
+    /// const ARQ: Arq<32> = Arq::new(MODEM_CFG, 1000).set_retries(0);
+    /// # assert_eq!(ARQ.retries(), 0);
+    /// ```
+    #[must_use = "set_retries returns a modified Arq"]
+    pub const fn set_retries(mut self, retries: u8) -> Arq<MTU> {
+        self.retries = retries;
+        self
+    }
+
+    /// Number of retransmissions.
+    pub const fn retries(&self) -> u8 {
+        self.retries
+    }
+
+    /// Set the turnaround time in ticks.
+    ///
+    /// This is the time from the end of a data frame to the start of the ACK,
+    /// and should include the processing time of the peer and the radio TX
+    /// ramp up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{
+    ///     Arq, CodingRate, HeaderType, LoRaBandwidth, LoRaModParams, LoRaPacketParams,
+    ///     ModemCfg, SpreadingFactor,
+    /// };
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::LoRa {
+    ///     mod_params: LoRaModParams::new()
+    ///         .set_sf(SpreadingFactor::Sf7)
+    ///         .set_bw(LoRaBandwidth::Bw125)
+    ///         .set_cr(CodingRate::Cr45),
+    ///     packet_params: LoRaPacketParams::new()
+    ///         .set_preamble_len(8)
+    ///         .set_header_type(HeaderType::Variable)
+    ///         .set_crc_en(true),
+    /// };
+    ///
+    /// // 1 kHz tick, 20 ms turnaround
+    /// const ARQ: Arq<64> = Arq::new(MODEM_CFG, 1000).set_turnaround(20);
+    ///
+    /// // 118.016 ms data frame, 30.976 ms ACK frame, each rounded up to ticks
+    /// assert_eq!(ARQ.ack_timeout(), 119 + 31 + 20);
+    /// ```
+    #[must_use = "set_turnaround returns a modified Arq"]
+    pub const fn set_turnaround(mut self, ticks: u32) -> Arq<MTU> {
+        let air: Duration = self.cfg.time_on_air(mtu_len(MTU));
+        let ack_air: Duration = self.cfg.time_on_air(ARQ_HEADER_LEN as u8);
+        self.turnaround = ticks;
+        self.ack_timeout = duration_to_ticks(air, self.tick_hz)
+            .saturating_add(duration_to_ticks(ack_air, self.tick_hz))
+            .saturating_add(ticks);
+        self
+    }
+
+    /// Turnaround time in ticks.
+    pub const fn turnaround(&self) -> u32 {
+        self.turnaround
+    }
+
+    /// ACK timeout in ticks, measured from the start of a data frame.
+    pub const fn ack_timeout(&self) -> u32 {
+        self.ack_timeout
+    }
+
+    /// Returns `true` if a frame is waiting for an acknowledgement.
+    pub const fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Number of retransmitted data frames.
+    pub const fn retransmits(&self) -> u32 {
+        self.retransmits
+    }
+
+    /// Number of duplicate data frames received.
+    pub const fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    /// Queue a payload for transmission.
+    ///
+    /// Returns the sequence number of the frame, this is reported in the
+    /// [`ArqEvent`] when the frame is acknowledged or fails.
+    pub fn send(&mut self, payload: &[u8]) -> Result<u8, ArqError> {
+        if self.is_busy() {
+            return Err(ArqError::Busy);
+        }
+        let len: usize = ARQ_HEADER_LEN + payload.len();
+        if len > usize::from(mtu_len(MTU)) {
+            return Err(ArqError::PayloadLen);
+        }
+
+        let seq: u8 = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.buf[0] = FRAME_DATA;
+        self.buf[1] = seq;
+        self.buf[ARQ_HEADER_LEN..len].copy_from_slice(payload);
+        self.pending = Some(Pending {
+            seq,
+            len: len as u8,
+            attempts: 0,
+            sent_at: None,
+        });
+        Ok(seq)
+    }
+
+    /// Drop the frame waiting for an acknowledgement.
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// Write the next frame to transmit to `buf`, returns the length of the
+    /// frame.
+    ///
+    /// `now` is the current time in ticks.
+    ///
+    /// ACK frames are sent before data frames, data frames are sent when
+    /// queued with [`send`], and again each time the ACK timeout expires.
+    ///
+    /// [`send`]: Arq::send
+    pub fn poll_tx(&mut self, now: u32, buf: &mut [u8]) -> Option<usize> {
+        if buf.len() < ARQ_HEADER_LEN {
+            return None;
+        }
+        if let Some(seq) = self.ack.take() {
+            buf[0] = FRAME_ACK;
+            buf[1] = seq;
+            return Some(ARQ_HEADER_LEN);
+        }
+
+        let ack_timeout: u32 = self.ack_timeout;
+        let retries: u8 = self.retries;
+        let pending: &mut Pending = self.pending.as_mut()?;
+        let len: usize = usize::from(pending.len);
+        match pending.sent_at {
+            Some(sent_at) if now.wrapping_sub(sent_at) < ack_timeout => return None,
+            Some(_) if pending.attempts > retries => return None,
+            Some(_) => self.retransmits = self.retransmits.saturating_add(1),
+            None => (),
+        }
+        if buf.len() < len {
+            return None;
+        }
+        pending.attempts = pending.attempts.saturating_add(1);
+        pending.sent_at = Some(now);
+        buf[..len].copy_from_slice(&self.buf[..len]);
+        Some(len)
+    }
+
+    /// Report a frame that was not acknowledged after all retransmissions.
+    ///
+    /// `now` is the current time in ticks.
+    pub fn poll(&mut self, now: u32) -> Option<ArqEvent> {
+        let pending: Pending = self.pending?;
+        match pending.sent_at {
+            Some(sent_at)
+                if pending.attempts > self.retries
+                    && now.wrapping_sub(sent_at) >= self.ack_timeout =>
+            {
+                self.pending = None;
+                Some(ArqEvent::Failed(pending.seq))
+            }
+            _ => None,
+        }
+    }
+
+    /// Process a received frame.
+    ///
+    /// The payload of new data frames is copied into `msg`, and an ACK is
+    /// queued for transmission with [`poll_tx`].
+    ///
+    /// [`poll_tx`]: Arq::poll_tx
+    pub fn receive(&mut self, frame: &[u8], msg: &mut [u8]) -> Result<Option<ArqEvent>, ArqError> {
+        if frame.len() < ARQ_HEADER_LEN {
+            return Err(ArqError::Frame);
+        }
+        let seq: u8 = frame[1];
+        let payload: &[u8] = &frame[ARQ_HEADER_LEN..];
+        match frame[0] {
+            FRAME_ACK if payload.is_empty() => match self.pending {
+                Some(pending) if pending.seq == seq => {
+                    self.pending = None;
+                    Ok(Some(ArqEvent::Acked(seq)))
+                }
+                _ => Ok(None),
+            },
+            FRAME_DATA => {
+                if self.rx_seq == Some(seq) {
+                    self.duplicates = self.duplicates.saturating_add(1);
+                    self.ack = Some(seq);
+                    Ok(None)
+                } else if payload.len() > msg.len() {
+                    Err(ArqError::BufferTooSmall)
+                } else {
+                    msg[..payload.len()].copy_from_slice(payload);
+                    self.rx_seq = Some(seq);
+                    self.ack = Some(seq);
+                    Ok(Some(ArqEvent::Received(payload.len())))
+                }
+            }
+            _ => Err(ArqError::Frame),
+        }
+    }
+}
+
+/// Convert a duration to ticks, rounded up.
+const fn duration_to_ticks(duration: Duration, tick_hz: u32) -> u32 {
+    let num: u128 = duration.as_nanos() * (tick_hz as u128);
+    let mut ticks: u128 = num / 1_000_000_000;
+    if ticks * 1_000_000_000 < num {
+        ticks += 1;
+    }
+    if ticks > u32::MAX as u128 {
+        u32::MAX
+    } else {
+        ticks as u32
+    }
+}
+
+/// Acknowledged link.
+impl SubGhz {
+    /// Drive an [`Arq`] link with the radio.
+    ///
+    /// The packet type and modulation parameters must be configured before
+    /// calling this function, the packet parameters are written for each
+    /// frame.
+    ///
+    /// Call this when the [`Irq::TxDone`], [`Irq::RxDone`], or
+    /// [`Irq::Timeout`] interrupts are asserted, and periodically to handle
+    /// the ACK timeout.
+    /// `now` is the current time in ticks.
+    ///
+    /// The radio is in RX mode with [`Timeout::MAX`] when it is not
+    /// transmitting, received payloads are copied into `msg`.
+    ///
+    /// Frames are not sent while [`Irq::PreambleDetected`],
+    /// [`Irq::SyncDetected`], or [`Irq::HeaderValid`] are asserted, to avoid
+    /// transmitting over a frame being received.
+    /// Enable these interrupts in the IRQ configuration to use this, a false
+    /// detection delays transmission by at most the ACK timeout.
+    ///
+    /// # Errors
+    ///
+    /// * [`ArqError::BufferTooSmall`] the received payload does not fit in
+    ///   `msg`, the frame is not acknowledged and reception restarts on the
+    ///   next call.
+    ///
+    /// Invalid frames are ignored, they are noise or from another link.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// # fn ticks() -> u32 { 0 }
+    /// use stm32wl_hal_subghz::{
+    ///     Arq, ArqEvent, CodingRate, HeaderType, LoRaBandwidth, LoRaModParams,
+    ///     LoRaPacketParams, ModemCfg, SpreadingFactor,
+    /// };
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::LoRa {
+    ///     mod_params: LoRaModParams::new()
+    ///         .set_sf(SpreadingFactor::Sf7)
+    ///         .set_bw(LoRaBandwidth::Bw125)
+    ///         .set_cr(CodingRate::Cr45),
+    ///     packet_params: LoRaPacketParams::new()
+    ///         .set_preamble_len(8)
+    ///         .set_header_type(HeaderType::Variable)
+    ///         .set_crc_en(true),
+    /// };
+    ///
+    /// sg.set_modem_cfg(&MODEM_CFG)?.unwrap();
+    /// let mut link: Arq<128> = Arq::new(MODEM_CFG, 1000);
+    /// let mut msg: [u8; 128] = [0; 128];
+    ///
+    /// link.send(b"reading: 21.5C").unwrap();
+    /// loop {
+    ///     match sg.poll_arq(&mut link, ticks(), &mut msg)? {
+    ///         Some(ArqEvent::Received(len)) => { /* ... use msg[..len] */ }
+    ///         Some(ArqEvent::Acked(seq)) => { /* delivered */ }
+    ///         Some(ArqEvent::Failed(seq)) => { /* link lost */ }
+    ///         None => (),
+    ///     }
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::ArqError>(())
+    /// ```
+    pub fn poll_arq<const MTU: usize>(
+        &mut self,
+        arq: &mut Arq<MTU>,
+        now: u32,
+        msg: &mut [u8],
+    ) -> Result<Option<ArqEvent>, ArqError> {
+        const IRQ_DONE: u16 = Irq::TxDone.mask() | Irq::RxDone.mask() | Irq::Timeout.mask();
+        const IRQ_RX_BUSY: u16 =
+            Irq::PreambleDetected.mask() | Irq::SyncDetected.mask() | Irq::HeaderValid.mask();
+
+        let mut frame: [u8; 255] = [0; 255];
+        let mut event: Option<ArqEvent> = None;
+
+        let (_, irq) = self.irq_status()?;
+        if arq.radio != RadioState::Idle && irq & IRQ_DONE != 0 {
+            let mut result: Result<Option<ArqEvent>, ArqError> = Ok(None);
+            if arq.radio == RadioState::Rx
+                && irq & Irq::RxDone.mask() != 0
+                && irq & (Irq::Err.mask() | Irq::HeaderErr.mask()) == 0
+            {
+                let (_, len, ptr) = self.rx_buffer_status()?;
+                let frame: &mut [u8] = &mut frame[..usize::from(len)];
+                self.read_buffer(ptr, frame)?;
+                result = arq.receive(frame, msg);
+            }
+            self.clear_irq_status(IRQ_ALL)?;
+            arq.radio = RadioState::Idle;
+            arq.rx_busy_at = None;
+            event = match result {
+                Ok(event) => event,
+                Err(ArqError::Frame) => None,
+                Err(e) => return Err(e),
+            };
+        }
+
+        if event.is_none() {
+            event = arq.poll(now);
+        }
+
+        if arq.radio == RadioState::Rx && irq & IRQ_RX_BUSY != 0 {
+            match arq.rx_busy_at {
+                None => {
+                    arq.rx_busy_at = Some(now);
+                    return Ok(event);
+                }
+                Some(at) if now.wrapping_sub(at) < arq.ack_timeout => return Ok(event),
+                Some(_) => {
+                    self.clear_irq_status(IRQ_RX_BUSY)?;
+                    arq.rx_busy_at = None;
+                }
+            }
+        }
+
+        if arq.radio != RadioState::Tx {
+            if let Some(len) = arq.poll_tx(now, &mut frame) {
+                self.set_standby(StandbyClk::Rc)?;
+                self.clear_irq_status(IRQ_ALL)?;
+                self.set_buffer_base_address(0, 0)?;
+                self.write_buffer(0, &frame[..len])?;
+                self.set_modem_packet_params(&arq.cfg.set_payload_len(len as u8))?;
+                self.set_tx(Timeout::DISABLED)?;
+                arq.radio = RadioState::Tx;
+            } else if arq.radio == RadioState::Idle {
+                self.set_buffer_base_address(0, 0)?;
+                self.set_modem_packet_params(&arq.cfg.set_payload_len(mtu_len(MTU)))?;
+                self.set_rx(Timeout::MAX)?;
+                arq.radio = RadioState::Rx;
+            }
+        }
+
+        Ok(event)
+    }
+}
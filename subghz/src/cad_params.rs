@@ -1,9 +1,10 @@
-use crate::Timeout;
+use crate::{LoRaBandwidth, LoRaModParams, SpreadingFactor, Timeout};
 
 /// Number of symbols used for channel activity detection scans.
 ///
 /// Argument of [`CadParams::set_num_symbol`].
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum NbCadSymbol {
     /// 1 symbol.
@@ -18,9 +19,22 @@ pub enum NbCadSymbol {
     S16 = 0x4,
 }
 
+impl NbCadSymbol {
+    const fn double(self) -> NbCadSymbol {
+        match self {
+            NbCadSymbol::S1 => NbCadSymbol::S2,
+            NbCadSymbol::S2 => NbCadSymbol::S4,
+            NbCadSymbol::S4 => NbCadSymbol::S8,
+            NbCadSymbol::S8 | NbCadSymbol::S16 => NbCadSymbol::S16,
+        }
+    }
+}
+
 /// Mode to enter after a channel activity detection scan is finished.
 ///
 /// Argument of [`CadParams::set_exit_mode`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum ExitMode {
     /// Standby with RC 13 MHz mode entry after CAD.
@@ -32,6 +46,23 @@ pub enum ExitMode {
     StandbyLoRa = 1,
 }
 
+/// Trade-off for the recommended CAD settings.
+///
+/// Argument of [`CadParams::for_lora`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CadBias {
+    /// Application note values.
+    Balanced,
+    /// Raise the detection peak threshold to reduce false detections on
+    /// noise, at the cost of more missed detections near sensitivity.
+    FewerFalseDetections,
+    /// Lower the detection peak threshold and scan more symbols to reduce
+    /// missed detections, at the cost of more false detections and a longer
+    /// scan.
+    FewerMissedDetections,
+}
+
 /// Channel activity detection (CAD) parameters.
 ///
 /// Argument of [`set_cad_params`].
@@ -55,10 +86,16 @@ pub enum ExitMode {
 /// |                    11 |             0x24 |            0x10 |
 /// |                    12 |             0x25 |            0x10 |
 ///
+/// The values in the reference manual table are decimal, despite the `0x`
+/// prefix.
+/// [`CadParams::for_lora`] provides the recommended settings from the Semtech
+/// CAD application note (AN1200.48) instead.
+///
 /// [`set_cad_params`]: crate::SubGhz::set_cad_params
 /// [`set_det_peak`]: crate::CadParams::set_det_peak
 /// [`set_det_min`]: crate::CadParams::set_det_min
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CadParams {
     buf: [u8; 8],
 }
@@ -86,6 +123,96 @@ impl CadParams {
         .set_exit_mode(ExitMode::Standby)
     }
 
+    /// Create the recommended `CadParams` for a LoRa modulation.
+    ///
+    /// The detection thresholds and number of symbols are from the Semtech
+    /// CAD application note (AN1200.48), which was characterized at 125 kHz.
+    ///
+    /// | SF | Symbols | [`set_det_peak`] | [`set_det_min`] |
+    /// |----|---------|------------------|-----------------|
+    /// |  5 |       2 |               18 |              10 |
+    /// |  6 |       2 |               19 |              10 |
+    /// |  7 |       2 |               22 |              10 |
+    /// |  8 |       2 |               22 |              10 |
+    /// |  9 |       4 |               23 |              10 |
+    /// | 10 |       4 |               24 |              10 |
+    /// | 11 |       4 |               25 |              10 |
+    /// | 12 |       4 |               28 |              10 |
+    ///
+    /// SF5 and SF6 are not in the application note, the reference manual
+    /// values are used instead.
+    ///
+    /// * For bandwidths above 125 kHz the number of symbols is doubled, the
+    ///   symbols are short so the scan time stays low.
+    /// * [`CadBias::FewerFalseDetections`] adds 2 to the detection peak.
+    /// * [`CadBias::FewerMissedDetections`] subtracts 2 from the detection
+    ///   peak and doubles the number of symbols.
+    ///
+    /// The exit mode and timeout are the same as [`new`](Self::new).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{
+    ///     CadBias, CadParams, LoRaBandwidth, LoRaModParams, NbCadSymbol, SpreadingFactor,
+    /// };
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new()
+    ///     .set_sf(SpreadingFactor::Sf9)
+    ///     .set_bw(LoRaBandwidth::Bw125);
+    ///
+    /// const CAD_PARAMS: CadParams = CadParams::for_lora(&MOD_PARAMS, CadBias::Balanced);
+    /// assert_eq!(
+    ///     CAD_PARAMS,
+    ///     CadParams::new()
+    ///         .set_num_symbol(NbCadSymbol::S4)
+    ///         .set_det_peak(23)
+    ///         .set_det_min(10)
+    /// );
+    ///
+    /// const FEWER_MISSED: CadParams =
+    ///     CadParams::for_lora(&MOD_PARAMS, CadBias::FewerMissedDetections);
+    /// assert_eq!(
+    ///     FEWER_MISSED,
+    ///     CadParams::new()
+    ///         .set_num_symbol(NbCadSymbol::S8)
+    ///         .set_det_peak(21)
+    ///         .set_det_min(10)
+    /// );
+    /// ```
+    ///
+    /// [`set_det_peak`]: crate::CadParams::set_det_peak
+    /// [`set_det_min`]: crate::CadParams::set_det_min
+    pub const fn for_lora(mod_params: &LoRaModParams, bias: CadBias) -> CadParams {
+        // the setters only write valid values, fall back to SF12 regardless
+        let (symbols, peak): (NbCadSymbol, u8) = match mod_params.sf() {
+            Ok(SpreadingFactor::Sf5) => (NbCadSymbol::S2, 18),
+            Ok(SpreadingFactor::Sf6) => (NbCadSymbol::S2, 19),
+            Ok(SpreadingFactor::Sf7) => (NbCadSymbol::S2, 22),
+            Ok(SpreadingFactor::Sf8) => (NbCadSymbol::S2, 22),
+            Ok(SpreadingFactor::Sf9) => (NbCadSymbol::S4, 23),
+            Ok(SpreadingFactor::Sf10) => (NbCadSymbol::S4, 24),
+            Ok(SpreadingFactor::Sf11) => (NbCadSymbol::S4, 25),
+            Ok(SpreadingFactor::Sf12) | Err(_) => (NbCadSymbol::S4, 28),
+        };
+
+        let symbols: NbCadSymbol = match mod_params.bw() {
+            Ok(LoRaBandwidth::Bw250) | Ok(LoRaBandwidth::Bw500) => symbols.double(),
+            _ => symbols,
+        };
+
+        let (symbols, peak): (NbCadSymbol, u8) = match bias {
+            CadBias::Balanced => (symbols, peak),
+            CadBias::FewerFalseDetections => (symbols, peak + 2),
+            CadBias::FewerMissedDetections => (symbols.double(), peak - 2),
+        };
+
+        CadParams::new()
+            .set_num_symbol(symbols)
+            .set_det_peak(peak)
+            .set_det_min(10)
+    }
+
     /// Number of symbols used for a CAD scan.
     ///
     /// # Example
@@ -0,0 +1,277 @@
+use crate::CrcType;
+
+/// Software model of the generic packet CRC engine.
+///
+/// This computes the same CRC as the radio for a given [`CrcType`],
+/// polynomial, and initial value, which allows frames to be generated and
+/// checked on systems without the sub-GHz radio (gateways, test tools).
+///
+/// The CRC is computed MSB first without reflection, and transmitted MSB
+/// first after the payload.
+/// For 1-byte CRCs only the least significant byte of the polynomial and the
+/// initial value are used.
+///
+/// The radio registers are named somewhat confusingly:
+///
+/// * The polynomial is programmed with [`set_initial_crc_polynomial`].
+/// * The initial value is programmed with [`set_crc_polynomial`].
+///
+/// # Software CRC fallback
+///
+/// When the hardware CRC is disabled with [`CrcType::Disabled`] the payload
+/// is sent as-is, and the CRC can be appended in software instead.
+/// This is useful for CRCs the hardware cannot generate, or for checking the
+/// CRC of frames that are received with an unknown length.
+///
+/// ```
+/// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+///
+/// const CRC: GenericCrc = GenericCrc::new(CrcType::Byte2);
+///
+/// let payload: &[u8] = b"123456789";
+/// let mut frame: [u8; 11] = [0; 11];
+/// frame[..9].copy_from_slice(payload);
+/// frame[9..].copy_from_slice(&CRC.checksum(payload).to_be_bytes());
+///
+/// // ... send with GenericPacketParams::set_crc_type(CrcType::Disabled)
+///
+/// assert!(CRC.verify(&frame));
+/// ```
+///
+/// [`set_initial_crc_polynomial`]: crate::SubGhz::set_initial_crc_polynomial
+/// [`set_crc_polynomial`]: crate::SubGhz::set_crc_polynomial
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GenericCrc {
+    crc_type: CrcType,
+    poly: u16,
+    init: u16,
+}
+
+impl GenericCrc {
+    /// IBM CRC-16, polynomial `0x8005` with an initial value of `0xFFFF`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::GenericCrc;
+    ///
+    /// assert_eq!(GenericCrc::IBM.checksum(b"123456789"), 0xAEE7);
+    /// ```
+    pub const IBM: GenericCrc = GenericCrc::new(CrcType::Byte2)
+        .set_polynomial(0x8005)
+        .set_initial(0xFFFF);
+
+    /// CCITT CRC-16, polynomial `0x1021` with an initial value of `0x1D0F`,
+    /// inverted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::GenericCrc;
+    ///
+    /// assert_eq!(GenericCrc::CCITT.checksum(b"123456789"), 0x1A33);
+    /// ```
+    pub const CCITT: GenericCrc = GenericCrc::new(CrcType::Byte2Inverted)
+        .set_polynomial(0x1021)
+        .set_initial(0x1D0F);
+
+    /// Create a new `GenericCrc`.
+    ///
+    /// The polynomial and initial value default to the power-on-reset values
+    /// of the radio, `0x1021` and `0x1D0F` respectively.
+    ///
+    /// [`CrcType::Disabled`] results in a zero length CRC.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// const CRC: GenericCrc = GenericCrc::new(CrcType::Byte1);
+    /// assert_eq!(CRC.crc_len(), 1);
+    /// ```
+    pub const fn new(crc_type: CrcType) -> GenericCrc {
+        GenericCrc {
+            crc_type,
+            poly: 0x1021,
+            init: 0x1D0F,
+        }
+    }
+
+    /// Set the CRC type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// const CRC: GenericCrc = GenericCrc::IBM.set_crc_type(CrcType::Byte2Inverted);
+    /// assert_eq!(CRC.crc_type(), CrcType::Byte2Inverted);
+    /// ```
+    #[must_use = "set_crc_type returns a modified GenericCrc"]
+    pub const fn set_crc_type(mut self, crc_type: CrcType) -> GenericCrc {
+        self.crc_type = crc_type;
+        self
+    }
+
+    /// Set the CRC polynomial.
+    ///
+    /// This should match the value written with
+    /// [`set_initial_crc_polynomial`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// const CRC: GenericCrc = GenericCrc::new(CrcType::Byte1)
+    ///     .set_polynomial(0x07)
+    ///     .set_initial(0x00);
+    /// assert_eq!(CRC.checksum(b"123456789"), 0xF4);
+    /// ```
+    ///
+    /// [`set_initial_crc_polynomial`]: crate::SubGhz::set_initial_crc_polynomial
+    #[must_use = "set_polynomial returns a modified GenericCrc"]
+    pub const fn set_polynomial(mut self, poly: u16) -> GenericCrc {
+        self.poly = poly;
+        self
+    }
+
+    /// Set the CRC initial value.
+    ///
+    /// This should match the value written with [`set_crc_polynomial`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// const CRC: GenericCrc = GenericCrc::new(CrcType::Byte2)
+    ///     .set_polynomial(0x1021)
+    ///     .set_initial(0x1D0F);
+    /// assert_eq!(CRC.checksum(b"123456789"), 0xE5CC);
+    /// ```
+    ///
+    /// [`set_crc_polynomial`]: crate::SubGhz::set_crc_polynomial
+    #[must_use = "set_initial returns a modified GenericCrc"]
+    pub const fn set_initial(mut self, init: u16) -> GenericCrc {
+        self.init = init;
+        self
+    }
+
+    /// Get the CRC type.
+    pub const fn crc_type(&self) -> CrcType {
+        self.crc_type
+    }
+
+    /// Get the CRC polynomial.
+    pub const fn polynomial(&self) -> u16 {
+        self.poly
+    }
+
+    /// Get the CRC initial value.
+    pub const fn initial(&self) -> u16 {
+        self.init
+    }
+
+    /// Length of the CRC in bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// assert_eq!(GenericCrc::new(CrcType::Disabled).crc_len(), 0);
+    /// assert_eq!(GenericCrc::new(CrcType::Byte1).crc_len(), 1);
+    /// assert_eq!(GenericCrc::new(CrcType::Byte1Inverted).crc_len(), 1);
+    /// assert_eq!(GenericCrc::new(CrcType::Byte2).crc_len(), 2);
+    /// assert_eq!(GenericCrc::new(CrcType::Byte2Inverted).crc_len(), 2);
+    /// ```
+    pub const fn crc_len(&self) -> usize {
+        match self.crc_type {
+            CrcType::Disabled => 0,
+            CrcType::Byte1 | CrcType::Byte1Inverted => 1,
+            CrcType::Byte2 | CrcType::Byte2Inverted => 2,
+        }
+    }
+
+    const fn inverted(&self) -> bool {
+        matches!(
+            self.crc_type,
+            CrcType::Byte1Inverted | CrcType::Byte2Inverted
+        )
+    }
+
+    /// Compute the CRC of `data`.
+    ///
+    /// 1-byte CRCs are returned in the least significant byte.
+    /// [`CrcType::Disabled`] always returns zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CrcType, GenericCrc};
+    ///
+    /// const CRC: GenericCrc = GenericCrc::new(CrcType::Byte1Inverted)
+    ///     .set_polynomial(0x2F)
+    ///     .set_initial(0xFF);
+    /// assert_eq!(CRC.checksum(b"123456789"), 0xDF);
+    /// ```
+    pub const fn checksum(&self, data: &[u8]) -> u16 {
+        let width: u32 = (self.crc_len() as u32) * 8;
+        if width == 0 {
+            return 0;
+        }
+        let mask: u16 = (((1_u32) << width) - 1) as u16;
+        let top: u16 = 1 << (width - 1);
+        let poly: u16 = self.poly & mask;
+        let mut crc: u16 = self.init & mask;
+
+        let mut idx: usize = 0;
+        while idx < data.len() {
+            crc ^= (data[idx] as u16) << (width - 8);
+            let mut bit: u8 = 0;
+            while bit < 8 {
+                if crc & top != 0 {
+                    crc = ((crc << 1) ^ poly) & mask;
+                } else {
+                    crc = (crc << 1) & mask;
+                }
+                bit += 1;
+            }
+            idx += 1;
+        }
+
+        if self.inverted() {
+            crc ^ mask
+        } else {
+            crc
+        }
+    }
+
+    /// Returns `true` if the CRC at the end of `frame` matches the data
+    /// before it.
+    ///
+    /// The CRC is expected MSB first, as transmitted by the radio.
+    /// Frames shorter than the CRC length return `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::GenericCrc;
+    ///
+    /// assert!(GenericCrc::IBM.verify(&[0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xAE, 0xE7]));
+    /// assert!(!GenericCrc::IBM.verify(&[0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xAE, 0xE6]));
+    /// assert!(!GenericCrc::IBM.verify(&[0xAE]));
+    /// ```
+    pub fn verify(&self, frame: &[u8]) -> bool {
+        let crc_len: usize = self.crc_len();
+        if frame.len() < crc_len {
+            return false;
+        }
+        let (data, crc) = frame.split_at(frame.len() - crc_len);
+        let expected: u16 = crc.iter().fold(0, |acc, &b| (acc << 8) | u16::from(b));
+        self.checksum(data) == expected
+    }
+}
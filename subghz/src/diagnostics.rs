@@ -0,0 +1,114 @@
+use crate::{FskStats, LoRaStats, PacketType, Stats, Status};
+
+/// Snapshot of the radio state.
+///
+/// Returned by [`diagnostics`].
+///
+/// All values are stored as they were read from the radio so that the
+/// snapshot can be logged or serialized and decoded elsewhere.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{Diagnostics, PacketType, StatusMode};
+///
+/// let diag: Diagnostics = Diagnostics {
+///     status: 0x54,
+///     packet_type: 1,
+///     ..Diagnostics::default()
+/// };
+/// assert_eq!(diag.status().mode(), Ok(StatusMode::Rx));
+/// assert_eq!(diag.packet_type(), Ok(PacketType::LoRa));
+/// ```
+///
+/// [`diagnostics`]: crate::SubGhz::diagnostics
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// Radio status, see [`Status`].
+    pub status: u8,
+    /// Operation error mask, see [`OpError`](crate::OpError).
+    pub op_error: u16,
+    /// IRQ status mask, see [`Irq`](crate::Irq).
+    pub irq_status: u16,
+    /// Packet type, see [`PacketType`].
+    pub packet_type: u8,
+    /// Payload length of the last received packet.
+    pub rx_payload_len: u8,
+    /// Buffer offset of the last received packet.
+    pub rx_buffer_ptr: u8,
+    /// Raw packet statistics, see [`Stats`].
+    pub stats: [u8; 7],
+    /// [`Register::PAOCP`](crate::Register::PAOCP)
+    pub pa_ocp: u8,
+    /// [`Register::HSEINTRIM`](crate::Register::HSEINTRIM)
+    pub hse_in_trim: u8,
+    /// [`Register::HSEOUTTRIM`](crate::Register::HSEOUTTRIM)
+    pub hse_out_trim: u8,
+    /// [`Register::RXGAINC`](crate::Register::RXGAINC)
+    pub rx_gain: u8,
+    /// [`Register::SMPSC0`](crate::Register::SMPSC0)
+    pub smpsc0: u8,
+    /// [`Register::SMPSC2`](crate::Register::SMPSC2)
+    pub smpsc2: u8,
+    /// RFBUSYS, `true` if the radio was busy.
+    pub rfbusys: bool,
+    /// `PWR.SR2` register.
+    pub pwr_sr2: u32,
+    /// `PWR.SUBGHZSPICR` register.
+    pub pwr_subghzspicr: u32,
+    /// `PWR.CR1` register.
+    pub pwr_cr1: u32,
+}
+
+impl Diagnostics {
+    /// Decoded radio status.
+    pub const fn status(&self) -> Status {
+        Status::from_raw(self.status)
+    }
+
+    /// Decoded packet type.
+    pub const fn packet_type(&self) -> Result<PacketType, u8> {
+        PacketType::from_raw(self.packet_type)
+    }
+
+    /// Packet statistics, decoded for (G)FSK.
+    pub const fn fsk_stats(&self) -> Stats<FskStats> {
+        Stats::from_raw_fsk(self.stats)
+    }
+
+    /// Packet statistics, decoded for LoRa.
+    pub const fn lora_stats(&self) -> Stats<LoRaStats> {
+        Stats::from_raw_lora(self.stats)
+    }
+}
+
+impl core::fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("status", &format_args!("{}", self.status()))
+            .field("op_error", &format_args!("0x{:04X}", self.op_error))
+            .field("irq_status", &format_args!("0x{:04X}", self.irq_status))
+            .field("packet_type", &self.packet_type())
+            .field("rx_payload_len", &self.rx_payload_len)
+            .field("rx_buffer_ptr", &self.rx_buffer_ptr)
+            .field("pkt_rx", &self.fsk_stats().pkt_rx())
+            .field("pkt_crc", &self.fsk_stats().pkt_crc())
+            .field("pkt_len_or_hdr_err", &self.fsk_stats().pkt_len_err())
+            .field("pa_ocp", &format_args!("0x{:02X}", self.pa_ocp))
+            .field("hse_in_trim", &format_args!("0x{:02X}", self.hse_in_trim))
+            .field("hse_out_trim", &format_args!("0x{:02X}", self.hse_out_trim))
+            .field("rx_gain", &format_args!("0x{:02X}", self.rx_gain))
+            .field("smpsc0", &format_args!("0x{:02X}", self.smpsc0))
+            .field("smpsc2", &format_args!("0x{:02X}", self.smpsc2))
+            .field("rfbusys", &self.rfbusys)
+            .field("pwr_sr2", &format_args!("0x{:X}", self.pwr_sr2))
+            .field(
+                "pwr_subghzspicr",
+                &format_args!("0x{:X}", self.pwr_subghzspicr),
+            )
+            .field("pwr_cr1", &format_args!("0x{:X}", self.pwr_cr1))
+            .finish()
+    }
+}
@@ -0,0 +1,541 @@
+use crate::{pac, OpCode, SubGhz};
+use core::{
+    ptr::{addr_of, read_volatile},
+    sync::atomic::{compiler_fence, Ordering::SeqCst},
+};
+
+/// DMAMUX request line for the sub-GHz radio SPI RX.
+const SUBGHZSPI_RX_REQ: u32 = 41;
+/// DMAMUX request line for the sub-GHz radio SPI TX.
+const SUBGHZSPI_TX_REQ: u32 = 42;
+
+// DMA_CCRx bits
+const CCR_EN: u32 = 1 << 0;
+const CCR_TCIE: u32 = 1 << 1;
+const CCR_TEIE: u32 = 1 << 3;
+const CCR_DIR: u32 = 1 << 4;
+const CCR_MINC: u32 = 1 << 7;
+const CCR_PL_HIGH: u32 = 0b10 << 12;
+
+/// Sent while reading the radio buffer.
+static DMA_TX_NOP: u8 = 0xFF;
+
+/// Evaluate `$body` with the CCR, CNDTR, CPAR, and CMAR registers of the
+/// channel with index `$idx` bound to the given patterns.
+macro_rules! with_ch {
+    ($dma:expr, $idx:expr, |$ccr:pat, $cndtr:pat, $cpar:pat, $cmar:pat| $body:block) => {
+        match $idx {
+            0 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr1, &$dma.cndtr1, &$dma.cpar1, &$dma.cmar1);
+                $body
+            }
+            1 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr2, &$dma.cndtr2, &$dma.cpar2, &$dma.cmar2);
+                $body
+            }
+            2 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr3, &$dma.cndtr3, &$dma.cpar3, &$dma.cmar3);
+                $body
+            }
+            3 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr4, &$dma.cndtr4, &$dma.cpar4, &$dma.cmar4);
+                $body
+            }
+            4 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr5, &$dma.cndtr5, &$dma.cpar5, &$dma.cmar5);
+                $body
+            }
+            5 => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr6, &$dma.cndtr6, &$dma.cpar6, &$dma.cmar6);
+                $body
+            }
+            _ => {
+                let ($ccr, $cndtr, $cpar, $cmar) =
+                    (&$dma.ccr7, &$dma.cndtr7, &$dma.cpar7, &$dma.cmar7);
+                $body
+            }
+        }
+    };
+}
+
+/// Evaluate `$body` with the DMAMUX channel configuration register with index
+/// `$idx` bound to `$ccr`.
+macro_rules! with_mux {
+    ($mux:expr, $idx:expr, |$ccr:pat| $body:block) => {
+        match $idx {
+            0 => {
+                let $ccr = &$mux.c0cr;
+                $body
+            }
+            1 => {
+                let $ccr = &$mux.c1cr;
+                $body
+            }
+            2 => {
+                let $ccr = &$mux.c2cr;
+                $body
+            }
+            3 => {
+                let $ccr = &$mux.c3cr;
+                $body
+            }
+            4 => {
+                let $ccr = &$mux.c4cr;
+                $body
+            }
+            5 => {
+                let $ccr = &$mux.c5cr;
+                $body
+            }
+            6 => {
+                let $ccr = &$mux.c6cr;
+                $body
+            }
+            7 => {
+                let $ccr = &$mux.c7cr;
+                $body
+            }
+            8 => {
+                let $ccr = &$mux.c8cr;
+                $body
+            }
+            9 => {
+                let $ccr = &$mux.c9cr;
+                $body
+            }
+            10 => {
+                let $ccr = &$mux.c10cr;
+                $body
+            }
+            11 => {
+                let $ccr = &$mux.c11cr;
+                $body
+            }
+            12 => {
+                let $ccr = &$mux.c12cr;
+                $body
+            }
+            _ => {
+                let $ccr = &$mux.c13cr;
+                $body
+            }
+        }
+    };
+}
+
+/// DMA channels.
+///
+/// Argument of [`write_buffer_dma`] and [`read_buffer_dma`].
+///
+/// [`write_buffer_dma`]: crate::SubGhz::write_buffer_dma
+/// [`read_buffer_dma`]: crate::SubGhz::read_buffer_dma
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(missing_docs)]
+pub enum DmaCh {
+    Dma1Ch1,
+    Dma1Ch2,
+    Dma1Ch3,
+    Dma1Ch4,
+    Dma1Ch5,
+    Dma1Ch6,
+    Dma1Ch7,
+    Dma2Ch1,
+    Dma2Ch2,
+    Dma2Ch3,
+    Dma2Ch4,
+    Dma2Ch5,
+    Dma2Ch6,
+    Dma2Ch7,
+}
+
+impl DmaCh {
+    /// DMAMUX channel index, 0 to 13.
+    const fn mux_idx(self) -> usize {
+        self as usize
+    }
+
+    /// Channel index within the DMA controller, 0 to 6.
+    const fn idx(self) -> usize {
+        self.mux_idx() % 7
+    }
+
+    /// Transfer complete flag offset in ISR and IFCR.
+    const fn tc_flag(self) -> u32 {
+        1 << (4 * self.idx() + 1)
+    }
+
+    /// Transfer error flag offset in ISR and IFCR.
+    const fn te_flag(self) -> u32 {
+        1 << (4 * self.idx() + 3)
+    }
+
+    /// Global flag offset in IFCR, clears all flags for the channel.
+    const fn gif_flag(self) -> u32 {
+        1 << (4 * self.idx())
+    }
+}
+
+/// DMA controllers and request multiplexer.
+///
+/// Argument of [`write_buffer_dma`] and [`read_buffer_dma`], this is returned
+/// by [`DmaTransfer::finish`].
+///
+/// [`write_buffer_dma`]: crate::SubGhz::write_buffer_dma
+/// [`read_buffer_dma`]: crate::SubGhz::read_buffer_dma
+pub struct Dma {
+    dma1: pac::DMA1,
+    dma2: pac::DMA2,
+    dmamux: pac::DMAMUX,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dma {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Dma {{ .. }}")
+    }
+}
+
+impl Dma {
+    /// Create a new `Dma`, this enables the DMA and DMAMUX clocks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_subghz::{pac, Dma};
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let mut rcc = dp.RCC;
+    /// let dma: Dma = Dma::new(dp.DMA1, dp.DMA2, dp.DMAMUX, &mut rcc);
+    /// ```
+    pub fn new(dma1: pac::DMA1, dma2: pac::DMA2, dmamux: pac::DMAMUX, rcc: &mut pac::RCC) -> Dma {
+        rcc.ahb1enr.modify(|_, w| {
+            w.dma1en().set_bit();
+            w.dma2en().set_bit();
+            w.dmamux1en().set_bit()
+        });
+        rcc.ahb1enr.read(); // Delay after an RCC peripheral clock enabling
+        Dma { dma1, dma2, dmamux }
+    }
+
+    /// Free the DMA peripherals from the driver.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use stm32wl_hal_subghz::{pac, Dma};
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let mut rcc = dp.RCC;
+    /// let dma: Dma = Dma::new(dp.DMA1, dp.DMA2, dp.DMAMUX, &mut rcc);
+    /// let (dma1, dma2, dmamux): (pac::DMA1, pac::DMA2, pac::DMAMUX) = dma.free();
+    /// ```
+    pub fn free(self) -> (pac::DMA1, pac::DMA2, pac::DMAMUX) {
+        (self.dma1, self.dma2, self.dmamux)
+    }
+
+    fn regs(&self, ch: DmaCh) -> &pac::dma1::RegisterBlock {
+        if ch.mux_idx() < 7 {
+            &self.dma1
+        } else {
+            &self.dma2
+        }
+    }
+
+    fn flags(&self, ch: DmaCh) -> u32 {
+        self.regs(ch).isr.read().bits()
+    }
+
+    unsafe fn start(&self, ch: DmaCh, req: u32, par: u32, mar: u32, len: u16, ccr: u32) {
+        let dma = self.regs(ch);
+        with_ch!(dma, ch.idx(), |ccr_reg, cndtr, cpar, cmar| {
+            ccr_reg.write(|w| w.bits(0));
+            dma.ifcr.write(|w| w.bits(ch.gif_flag()));
+            with_mux!(self.dmamux, ch.mux_idx(), |muxccr| {
+                muxccr.write(|w| w.bits(req));
+            });
+            cpar.write(|w| w.bits(par));
+            cmar.write(|w| w.bits(mar));
+            cndtr.write(|w| w.bits(u32::from(len)));
+            compiler_fence(SeqCst);
+            ccr_reg.write(|w| w.bits(ccr | CCR_EN));
+        });
+    }
+
+    unsafe fn stop(&self, ch: DmaCh) {
+        let dma = self.regs(ch);
+        with_ch!(dma, ch.idx(), |ccr, _, _, _| {
+            ccr.write(|w| w.bits(0));
+        });
+        dma.ifcr.write(|w| w.bits(ch.gif_flag()));
+        compiler_fence(SeqCst);
+    }
+}
+
+/// In-progress DMA transfer to or from the radio buffer.
+///
+/// Created by [`write_buffer_dma`] and [`read_buffer_dma`].
+///
+/// The transfer complete and transfer error interrupts are enabled on the
+/// channel that completes last, the `tx` channel for a write, and the `rx`
+/// channel for a read.
+/// Call [`is_complete`] from the interrupt handler of that channel, and
+/// [`finish`] to release the SPI bus and get the radio back.
+///
+/// [`write_buffer_dma`]: crate::SubGhz::write_buffer_dma
+/// [`read_buffer_dma`]: crate::SubGhz::read_buffer_dma
+/// [`is_complete`]: DmaTransfer::is_complete
+/// [`finish`]: DmaTransfer::finish
+pub struct DmaTransfer<B> {
+    sg: SubGhz,
+    dma: Dma,
+    tx: DmaCh,
+    rx: Option<DmaCh>,
+    buf: B,
+    empty: bool,
+}
+
+#[cfg(feature = "defmt")]
+impl<B> defmt::Format for DmaTransfer<B> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "DmaTransfer {{ tx: {}, rx: {}, complete: {} }}",
+            self.tx,
+            self.rx,
+            self.is_complete()
+        )
+    }
+}
+
+impl<B> DmaTransfer<B> {
+    /// Channel with the transfer complete interrupt enabled.
+    fn irq_ch(&self) -> DmaCh {
+        self.rx.unwrap_or(self.tx)
+    }
+
+    /// Returns `true` if the transfer has completed, or ended with an error.
+    pub fn is_complete(&self) -> bool {
+        let ch: DmaCh = self.irq_ch();
+        self.empty || self.dma.flags(ch) & (ch.tc_flag() | ch.te_flag()) != 0
+    }
+
+    /// Returns `true` if a DMA transfer error occurred.
+    pub fn is_error(&self) -> bool {
+        let tx: u32 = self.dma.flags(self.tx) & self.tx.te_flag();
+        let rx: u32 = match self.rx {
+            Some(rx) => self.dma.flags(rx) & rx.te_flag(),
+            None => 0,
+        };
+        rx | tx != 0
+    }
+
+    /// Wait for the transfer to complete, then release the SPI bus.
+    ///
+    /// This returns the radio, the DMA peripherals, and the buffer.
+    pub fn finish(self) -> (SubGhz, Dma, B) {
+        while !self.is_complete() {
+            compiler_fence(SeqCst);
+        }
+
+        unsafe {
+            self.dma.stop(self.tx);
+            if let Some(rx) = self.rx {
+                self.dma.stop(rx);
+            }
+        }
+
+        // the last bytes of a write are still shifted out after the TX DMA
+        // channel completes, and the bytes received are not read by DMA
+        while self.sg.spi.sr.read().bsy().bit_is_set() {}
+        while self.sg.spi.sr.read().rxne().bit_is_set() {
+            unsafe { read_volatile(&self.sg.spi.dr as *const _ as *const u8) };
+        }
+        // clears the overrun flag
+        self.sg.spi.sr.read();
+
+        self.sg.spi.cr2.modify(|_, w| {
+            w.txdmaen().clear_bit();
+            w.rxdmaen().clear_bit()
+        });
+
+        let dp = unsafe { pac::Peripherals::steal() };
+        dp.PWR.subghzspicr.write(|w| w.nss().set_bit());
+        self.sg.poll_not_busy();
+
+        (self.sg, self.dma, self.buf)
+    }
+}
+
+// 5.8.2
+/// Buffer access commands with DMA.
+impl SubGhz {
+    /// Write the radio buffer with DMA.
+    ///
+    /// This sends the command header, then starts the DMA transfer of `data`
+    /// on the `tx` channel and returns without waiting for it to complete.
+    /// The bytes received from the radio during the write are discarded.
+    ///
+    /// The interrupt for the `tx` channel should be unmasked in the NVIC to be
+    /// signalled on completion.
+    ///
+    /// # Panics
+    ///
+    /// * `data` is longer than 256 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{pac, Dma, DmaCh, DmaTransfer, SubGhz};
+    ///
+    /// static DATA: [u8; 64] = [0xAA; 64];
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let mut rcc = dp.RCC;
+    /// let dma: Dma = Dma::new(dp.DMA1, dp.DMA2, dp.DMAMUX, &mut rcc);
+    ///
+    /// let transfer: DmaTransfer<&'static [u8]> =
+    ///     sg.write_buffer_dma(dma, DmaCh::Dma1Ch1, 0, &DATA);
+    ///
+    /// // ... sleep until the DMA1 channel 1 interrupt
+    ///
+    /// if transfer.is_complete() {
+    ///     let (sg, dma, _data): (SubGhz, Dma, &'static [u8]) = transfer.finish();
+    /// }
+    /// ```
+    pub fn write_buffer_dma(
+        self,
+        dma: Dma,
+        tx: DmaCh,
+        offset: u8,
+        data: &'static [u8],
+    ) -> DmaTransfer<&'static [u8]> {
+        assert!(data.len() <= 256);
+        let dp = unsafe { pac::Peripherals::steal() };
+        self.poll_not_busy();
+
+        dp.PWR.subghzspicr.write(|w| w.nss().clear_bit());
+        self.write_byte_raw(OpCode::WriteBuffer as u8);
+        self.write_byte_raw(offset);
+
+        if !data.is_empty() {
+            let dr: u32 = addr_of!(self.spi.dr) as u32;
+            unsafe {
+                dma.start(
+                    tx,
+                    SUBGHZSPI_TX_REQ,
+                    dr,
+                    data.as_ptr() as u32,
+                    data.len() as u16,
+                    CCR_PL_HIGH | CCR_MINC | CCR_DIR | CCR_TCIE | CCR_TEIE,
+                );
+            }
+            self.spi.cr2.modify(|_, w| w.txdmaen().set_bit());
+        }
+
+        DmaTransfer {
+            sg: self,
+            dma,
+            tx,
+            rx: None,
+            empty: data.is_empty(),
+            buf: data,
+        }
+    }
+
+    /// Read the radio buffer with DMA.
+    ///
+    /// This sends the command header, then starts the DMA transfer into `buf`
+    /// and returns without waiting for it to complete.
+    ///
+    /// The interrupt for the `rx` channel should be unmasked in the NVIC to be
+    /// signalled on completion.
+    ///
+    /// # Panics
+    ///
+    /// * `buf` is longer than 256 bytes.
+    /// * `tx` and `rx` are the same channel.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{pac, Dma, DmaCh, DmaTransfer, SubGhz};
+    ///
+    /// static mut BUF: [u8; 255] = [0; 255];
+    ///
+    /// let dp: pac::Peripherals = pac::Peripherals::take().unwrap();
+    /// let mut rcc = dp.RCC;
+    /// let dma: Dma = Dma::new(dp.DMA1, dp.DMA2, dp.DMAMUX, &mut rcc);
+    ///
+    /// let (status, len, ptr) = sg.rx_buffer_status()?;
+    /// let buf: &'static mut [u8] = unsafe { &mut BUF[..usize::from(len)] };
+    /// let transfer: DmaTransfer<&'static mut [u8]> =
+    ///     sg.read_buffer_dma(dma, DmaCh::Dma1Ch1, DmaCh::Dma1Ch2, ptr, buf);
+    ///
+    /// // ... sleep until the DMA1 channel 2 interrupt
+    ///
+    /// let (sg, dma, data): (SubGhz, Dma, &'static mut [u8]) = transfer.finish();
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn read_buffer_dma(
+        self,
+        dma: Dma,
+        tx: DmaCh,
+        rx: DmaCh,
+        offset: u8,
+        buf: &'static mut [u8],
+    ) -> DmaTransfer<&'static mut [u8]> {
+        assert!(buf.len() <= 256);
+        assert_ne!(tx, rx);
+        let dp = unsafe { pac::Peripherals::steal() };
+        self.poll_not_busy();
+
+        dp.PWR.subghzspicr.write(|w| w.nss().clear_bit());
+        self.write_byte_raw(OpCode::ReadBuffer as u8);
+        self.write_byte_raw(offset);
+        // status byte
+        self.read_byte_raw();
+
+        if !buf.is_empty() {
+            let dr: u32 = addr_of!(self.spi.dr) as u32;
+            let len: u16 = buf.len() as u16;
+            self.spi.cr2.modify(|_, w| w.rxdmaen().set_bit());
+            unsafe {
+                dma.start(
+                    rx,
+                    SUBGHZSPI_RX_REQ,
+                    dr,
+                    buf.as_mut_ptr() as u32,
+                    len,
+                    CCR_PL_HIGH | CCR_MINC | CCR_TCIE | CCR_TEIE,
+                );
+                dma.start(
+                    tx,
+                    SUBGHZSPI_TX_REQ,
+                    dr,
+                    addr_of!(DMA_TX_NOP) as u32,
+                    len,
+                    CCR_PL_HIGH | CCR_DIR,
+                );
+            }
+            self.spi.cr2.modify(|_, w| w.txdmaen().set_bit());
+        }
+
+        DmaTransfer {
+            sg: self,
+            dma,
+            tx,
+            rx: Some(rx),
+            empty: buf.is_empty(),
+            buf,
+        }
+    }
+}
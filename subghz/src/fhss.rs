@@ -0,0 +1,237 @@
+use crate::{protocol::xorshift32, RfFreq, SubGhz, SubGhzError};
+
+/// Frequency hopping spread spectrum (FHSS) channel schedule.
+///
+/// The `N` channels are `base + i × spacing` for `i` in `0..N`, visited in a
+/// pseudorandom order derived from a seed, `N` must not be zero, an `Fhss<0>`
+/// fails to compile.
+/// Each channel in the sequence is used once before the sequence repeats,
+/// giving equal average occupancy.
+///
+/// ```compile_fail
+/// use stm32wl_hal_subghz::Fhss;
+///
+/// const FHSS: Fhss<0> = Fhss::new(902_500_000, 500_000, 1, 100);
+/// ```
+///
+/// Hopping is driven by a hop clock in caller defined ticks, see
+/// [protocol layers](crate#protocol-layers).
+/// The channel is `ticks / dwell`, modulo `N`, in the hop sequence, a
+/// transmitter and receiver with the same seed, dwell, and a synchronised hop
+/// clock are on the same channel at the same time.
+/// The clocks can be synchronised with the timestamps of received packets,
+/// see [`RxPacket::timestamp`].
+///
+/// # FCC Part 15.247
+///
+/// For FHSS in the 902 - 928 MHz band with a 20 dB bandwidth of less than
+/// 250 kHz:
+///
+/// * At least 50 channels ([`FCC_MIN_CHANNELS`]), separated by at least the
+///   20 dB bandwidth.
+/// * An average dwell time of no more than 0.4 s per channel within a 20 s
+///   period.
+///
+/// It is the responsibility of the caller to select channels and a dwell
+/// time that meet the regulations.
+///
+/// # Example
+///
+/// 50 channels with 500 kHz spacing starting at 902.5 MHz.
+///
+/// ```
+/// use stm32wl_hal_subghz::{Fhss, RfFreq};
+///
+/// const FHSS: Fhss<50> = Fhss::new(902_500_000, 500_000, 0x1234_5678, 100);
+///
+/// // ticks 0 - 99 are slot 0, ticks 100 - 199 are slot 1, ...
+/// assert_eq!(FHSS.slot(250), 2);
+/// assert_eq!(FHSS.ticks_to_next_hop(250), 50);
+/// assert_eq!(FHSS.freq_at(250), FHSS.channels()[2]);
+///
+/// // the sequence repeats after 50 slots
+/// assert_eq!(FHSS.freq(2), FHSS.freq(52));
+/// ```
+///
+/// [`FCC_MIN_CHANNELS`]: Fhss::FCC_MIN_CHANNELS
+/// [`RxPacket::timestamp`]: crate::RxPacket::timestamp
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Fhss<const N: usize> {
+    channels: [RfFreq; N],
+    dwell: u32,
+    slot: Option<u32>,
+}
+
+impl<const N: usize> Fhss<N> {
+    /// Minimum number of hopping channels for FCC Part 15.247 in the
+    /// 902 - 928 MHz band with a 20 dB bandwidth of less than 250 kHz.
+    pub const FCC_MIN_CHANNELS: usize = 50;
+
+    /// Evaluated by [`new`](Self::new), fails to compile if `N` is zero.
+    const NON_ZERO: () = assert!(N != 0, "Fhss must have at least one channel");
+
+    /// Create a new hop schedule.
+    ///
+    /// * `base` is the frequency of the lowest channel in hertz.
+    /// * `spacing` is the channel spacing in hertz.
+    /// * `seed` selects the hop sequence, a seed of zero is replaced with a
+    ///   non-zero constant.
+    /// * `dwell` is the number of hop clock ticks per channel, a dwell of zero
+    ///   is replaced with one.
+    ///
+    /// # Panics
+    ///
+    /// * The highest channel, `base + (N - 1) × spacing`, exceeds `u32::MAX`
+    ///   hertz.
+    pub const fn new(base: u32, spacing: u32, seed: u32, dwell: u32) -> Fhss<N> {
+        #[allow(clippy::let_unit_value)]
+        let _: () = Self::NON_ZERO;
+
+        // channel order, Fisher-Yates shuffle with xorshift32
+        let mut order: [usize; N] = [0; N];
+        let mut idx: usize = 0;
+        while idx < N {
+            order[idx] = idx;
+            idx += 1;
+        }
+        let mut state: u32 = if seed == 0 { 0x9E37_79B9 } else { seed };
+        idx = N;
+        while idx > 1 {
+            state = xorshift32(state);
+            let swap: usize = (state as usize) % idx;
+            idx -= 1;
+            let tmp: usize = order[idx];
+            order[idx] = order[swap];
+            order[swap] = tmp;
+        }
+
+        let mut channels: [RfFreq; N] = [RfFreq::from_raw(0); N];
+        idx = 0;
+        while idx < N {
+            let hz: u64 = (base as u64) + (order[idx] as u64) * (spacing as u64);
+            assert!(hz <= u32::MAX as u64, "Fhss channel frequency overflow");
+            channels[idx] = RfFreq::from_frequency(hz as u32);
+            idx += 1;
+        }
+
+        Fhss {
+            channels,
+            dwell: if dwell == 0 { 1 } else { dwell },
+            slot: None,
+        }
+    }
+
+    /// Channels in hop sequence order.
+    ///
+    /// This table can be used to retune without recomputing frequencies.
+    pub const fn channels(&self) -> &[RfFreq; N] {
+        &self.channels
+    }
+
+    /// Number of hop clock ticks per channel.
+    pub const fn dwell(&self) -> u32 {
+        self.dwell
+    }
+
+    /// Hop slot for a hop clock value.
+    pub const fn slot(&self, ticks: u32) -> u32 {
+        ticks / self.dwell
+    }
+
+    /// Number of hop clock ticks until the next hop.
+    ///
+    /// When the hop clock wraps the slot returns to zero, if `dwell` does
+    /// not divide 2<sup>32</sup> the last slot before the wrap is shorter.
+    pub const fn ticks_to_next_hop(&self, ticks: u32) -> u32 {
+        let to_slot_end: u32 = self.dwell - ticks % self.dwell;
+        let to_wrap: u64 = (u32::MAX as u64) + 1 - (ticks as u64);
+        if (to_slot_end as u64) < to_wrap {
+            to_slot_end
+        } else {
+            to_wrap as u32
+        }
+    }
+
+    /// Channel frequency for a hop slot.
+    pub const fn freq(&self, slot: u32) -> RfFreq {
+        self.channels[(slot as usize) % N]
+    }
+
+    /// Channel frequency for a hop clock value.
+    pub const fn freq_at(&self, ticks: u32) -> RfFreq {
+        self.freq(self.slot(ticks))
+    }
+
+    /// Force a retune on the next call to [`fhss_hop`].
+    ///
+    /// [`fhss_hop`]: crate::SubGhz::fhss_hop
+    pub fn reset(&mut self) {
+        self.slot = None;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const N: usize> defmt::Format for Fhss<N> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Fhss {{ channels: {=[?]}, dwell: {}, slot: {} }}",
+            &self.channels[..],
+            self.dwell,
+            self.slot
+        )
+    }
+}
+
+/// Frequency hopping.
+impl SubGhz {
+    /// Retune to the channel for the hop clock value, if it has changed since
+    /// the last call.
+    ///
+    /// Returns `true` if the radio was retuned.
+    ///
+    /// The RF frequency can only be changed between packets, retuning while a
+    /// packet is in flight corrupts the packet.
+    /// Call this before each [`set_tx`] or [`set_rx`], and from the
+    /// [`Irq::TxDone`] or [`Irq::RxDone`] interrupts; the time on air of a
+    /// packet should be less than the dwell time.
+    ///
+    /// Hopping within a packet is out of scope, a packet that is still in
+    /// flight at the end of the slot stays on its channel, and the next call
+    /// after it completes retunes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// # fn hop_clock() -> u32 { 0 }
+    /// use stm32wl_hal_subghz::{Fhss, Timeout};
+    ///
+    /// // 1 kHz hop clock, 400 ms dwell
+    /// let mut fhss: Fhss<50> = Fhss::new(902_500_000, 500_000, 0xCAFE, 400);
+    ///
+    /// sg.fhss_hop(&mut fhss, hop_clock())?;
+    /// sg.write_buffer(0, b"hop")?;
+    /// sg.set_tx(Timeout::DISABLED)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`set_tx`]: crate::SubGhz::set_tx
+    /// [`set_rx`]: crate::SubGhz::set_rx
+    /// [`Irq::TxDone`]: crate::Irq::TxDone
+    /// [`Irq::RxDone`]: crate::Irq::RxDone
+    pub fn fhss_hop<const N: usize>(
+        &mut self,
+        fhss: &mut Fhss<N>,
+        ticks: u32,
+    ) -> Result<bool, SubGhzError> {
+        let slot: u32 = fhss.slot(ticks);
+        if fhss.slot == Some(slot) {
+            Ok(false)
+        } else {
+            self.set_rf_frequency(&fhss.freq(slot))?;
+            fhss.slot = Some(slot);
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,358 @@
+/// Length of the fragment header in bytes.
+///
+/// The header is:
+///
+/// | Byte | Field                                          |
+/// |------|------------------------------------------------|
+/// |    0 | Message ID                                     |
+/// |    1 | Fragment index                                 |
+/// |    2 | Index of the last fragment                     |
+/// |    3 | Payload length of every fragment but the last  |
+pub const FRAG_HEADER_LEN: usize = 4;
+
+/// Maximum number of fragments in a message.
+pub const FRAG_MAX: usize = 256;
+
+/// Fragmentation errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FragError {
+    /// The message needs more than [`FRAG_MAX`] fragments, or the MTU does
+    /// not have room for the header and one byte of payload.
+    TooLong,
+    /// The output or reassembly buffer is too small.
+    BufferTooSmall,
+    /// The fragment header is invalid.
+    Header,
+}
+
+/// Splits a message into numbered fragments.
+///
+/// Each fragment is at most `mtu` bytes, including the
+/// [`FRAG_HEADER_LEN`] byte header, use the maximum payload length of the
+/// packet parameters (255 bytes for LoRa and (G)FSK variable length packets).
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{Fragmenter, FRAG_HEADER_LEN};
+///
+/// let data: [u8; 600] = [0xAB; 600];
+/// let mut frag = Fragmenter::new(&data, 7, 255).unwrap();
+/// assert_eq!(frag.count(), 3);
+///
+/// let mut buf: [u8; 255] = [0; 255];
+/// let mut total: usize = 0;
+/// while let Some(len) = frag.next_into(&mut buf).unwrap() {
+///     // ... write_buffer(0, &buf[..len]) and set_tx
+///     total += len - FRAG_HEADER_LEN;
+/// }
+/// assert_eq!(total, data.len());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fragmenter<'a> {
+    data: &'a [u8],
+    msg_id: u8,
+    size: u8,
+    last: u8,
+    next: usize,
+}
+
+impl<'a> Fragmenter<'a> {
+    /// Create a new fragmenter.
+    ///
+    /// `msg_id` identifies the message at the receiver, it should be different
+    /// for consecutive messages.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FragError, Fragmenter};
+    ///
+    /// assert!(Fragmenter::new(&[0; 1000], 0, 64).is_ok());
+    /// assert_eq!(
+    ///     Fragmenter::new(&[0; 1000], 0, 4),
+    ///     Err(FragError::TooLong)
+    /// );
+    /// assert_eq!(
+    ///     Fragmenter::new(&[0; 1000], 0, 6),
+    ///     Err(FragError::TooLong)
+    /// );
+    /// ```
+    pub fn new(data: &'a [u8], msg_id: u8, mtu: u8) -> Result<Fragmenter<'a>, FragError> {
+        let size: usize = usize::from(mtu).saturating_sub(FRAG_HEADER_LEN);
+        if size == 0 {
+            return Err(FragError::TooLong);
+        }
+        let mut count: usize = data.len() / size;
+        if count == 0 || count * size < data.len() {
+            count += 1;
+        }
+        if count > FRAG_MAX {
+            return Err(FragError::TooLong);
+        }
+        Ok(Fragmenter {
+            data,
+            msg_id,
+            size: size as u8,
+            last: (count - 1) as u8,
+            next: 0,
+        })
+    }
+
+    /// Number of fragments.
+    pub fn count(&self) -> usize {
+        usize::from(self.last) + 1
+    }
+
+    /// Write a fragment to `buf`, returns the length of the fragment, or
+    /// `None` if `index` is out of range.
+    ///
+    /// This can be used to retransmit the fragments that are missing at the
+    /// receiver, see [`Reassembler::is_received`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Fragmenter;
+    ///
+    /// let frag = Fragmenter::new(&[1, 2, 3, 4, 5], 9, 7).unwrap();
+    /// let mut buf: [u8; 7] = [0; 7];
+    ///
+    /// assert_eq!(frag.fragment(1, &mut buf), Ok(Some(6)));
+    /// assert_eq!(&buf[..6], &[9, 1, 1, 3, 4, 5]);
+    /// assert_eq!(frag.fragment(2, &mut buf), Ok(None));
+    /// ```
+    pub fn fragment(&self, index: usize, buf: &mut [u8]) -> Result<Option<usize>, FragError> {
+        if index > usize::from(self.last) {
+            return Ok(None);
+        }
+        let size: usize = usize::from(self.size);
+        let start: usize = index * size;
+        let end: usize = core::cmp::min(start + size, self.data.len());
+        let payload: &[u8] = &self.data[start..end];
+        let len: usize = FRAG_HEADER_LEN + payload.len();
+        if buf.len() < len {
+            return Err(FragError::BufferTooSmall);
+        }
+        buf[0] = self.msg_id;
+        buf[1] = index as u8;
+        buf[2] = self.last;
+        buf[3] = self.size;
+        buf[FRAG_HEADER_LEN..len].copy_from_slice(payload);
+        Ok(Some(len))
+    }
+
+    /// Write the next fragment to `buf`, returns the length of the fragment,
+    /// or `None` when all fragments have been written.
+    pub fn next_into(&mut self, buf: &mut [u8]) -> Result<Option<usize>, FragError> {
+        let ret: Option<usize> = self.fragment(self.next, buf)?;
+        if ret.is_some() {
+            self.next += 1;
+        }
+        Ok(ret)
+    }
+}
+
+/// Reassembles fragments created by a [`Fragmenter`] into a caller-provided
+/// buffer.
+///
+/// Fragments can be received in any order, duplicates are ignored.
+/// A fragment with a new message ID discards the incomplete message.
+/// Fragments of the last completed message are ignored until a fragment of
+/// a new message is received, retransmissions do not deliver the message
+/// twice.
+///
+/// Timeouts use caller defined ticks, see
+/// [protocol layers](crate#protocol-layers), an incomplete message is
+/// discarded when no fragment has been received for `timeout` ticks.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{Fragmenter, Reassembler};
+///
+/// let data: [u8; 300] = [0x5A; 300];
+/// let frag = Fragmenter::new(&data, 1, 128).unwrap();
+///
+/// let mut rx_buf: [u8; 512] = [0; 512];
+/// let mut reasm = Reassembler::new(&mut rx_buf, 1000);
+///
+/// let mut pkt: [u8; 128] = [0; 128];
+/// // fragments received out of order
+/// for &idx in [2, 0, 1].iter() {
+///     let len: usize = frag.fragment(idx, &mut pkt).unwrap().unwrap();
+///     if let Some(msg) = reasm.push(&pkt[..len], 0).unwrap() {
+///         assert_eq!(msg, &data[..]);
+///     }
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Reassembler<'a> {
+    buf: &'a mut [u8],
+    timeout: u32,
+    msg_id: Option<u8>,
+    size: u8,
+    last: u8,
+    len: usize,
+    received: [u32; FRAG_MAX / 32],
+    last_rx: u32,
+    // message ID, last index, and fragment size of the last completed message
+    done: Option<(u8, u8, u8)>,
+}
+
+#[cfg(feature = "defmt")]
+impl<'a> defmt::Format for Reassembler<'a> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Reassembler {{ msg_id: {}, len: {}, last_rx: {} }}",
+            self.msg_id,
+            self.len,
+            self.last_rx
+        )
+    }
+}
+
+impl<'a> Reassembler<'a> {
+    /// Create a new reassembler.
+    pub fn new(buf: &'a mut [u8], timeout: u32) -> Reassembler<'a> {
+        Reassembler {
+            buf,
+            timeout,
+            msg_id: None,
+            size: 0,
+            last: 0,
+            len: 0,
+            received: [0; FRAG_MAX / 32],
+            last_rx: 0,
+            done: None,
+        }
+    }
+
+    /// Discard the message in progress.
+    pub fn reset(&mut self) {
+        self.msg_id = None;
+        self.received = [0; FRAG_MAX / 32];
+        self.len = 0;
+    }
+
+    /// Message ID of the message in progress.
+    pub fn msg_id(&self) -> Option<u8> {
+        self.msg_id
+    }
+
+    /// Number of fragments in the message in progress.
+    pub fn count(&self) -> usize {
+        if self.msg_id.is_some() {
+            usize::from(self.last) + 1
+        } else {
+            0
+        }
+    }
+
+    /// Returns `true` if the fragment at `index` of the message in progress
+    /// has been received.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{Fragmenter, Reassembler};
+    ///
+    /// let frag = Fragmenter::new(&[0; 100], 3, 36).unwrap();
+    /// let mut rx_buf: [u8; 100] = [0; 100];
+    /// let mut reasm = Reassembler::new(&mut rx_buf, 1000);
+    ///
+    /// let mut pkt: [u8; 36] = [0; 36];
+    /// let len: usize = frag.fragment(1, &mut pkt).unwrap().unwrap();
+    /// reasm.push(&pkt[..len], 0).unwrap();
+    ///
+    /// let missing: Vec<usize> = (0..reasm.count()).filter(|&i| !reasm.is_received(i)).collect();
+    /// assert_eq!(missing, [0, 2, 3]);
+    /// ```
+    pub fn is_received(&self, index: usize) -> bool {
+        index < FRAG_MAX && self.received[index / 32] & (1 << (index % 32)) != 0
+    }
+
+    /// Bitmap of the received fragments, bit `n` of byte `n / 8` is set if
+    /// fragment `n` has been received.
+    ///
+    /// This can be sent back to the transmitter to request the missing
+    /// fragments.
+    pub fn received_bitmap(&self) -> [u8; FRAG_MAX / 8] {
+        let mut ret: [u8; FRAG_MAX / 8] = [0; FRAG_MAX / 8];
+        for (bytes, word) in ret.chunks_mut(4).zip(self.received.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        ret
+    }
+
+    /// Returns `true` if the message in progress has timed out at `now`.
+    pub fn is_expired(&self, now: u32) -> bool {
+        self.msg_id.is_some() && now.wrapping_sub(self.last_rx) >= self.timeout
+    }
+
+    /// Add a received fragment.
+    ///
+    /// `now` is the current time in ticks.
+    ///
+    /// Returns the reassembled message when the last missing fragment is
+    /// received.
+    pub fn push(&mut self, frame: &[u8], now: u32) -> Result<Option<&[u8]>, FragError> {
+        if frame.len() < FRAG_HEADER_LEN {
+            return Err(FragError::Header);
+        }
+        let (msg_id, index, last, size) = (frame[0], frame[1], frame[2], frame[3]);
+        let payload: &[u8] = &frame[FRAG_HEADER_LEN..];
+        if size == 0
+            || index > last
+            || (index < last && payload.len() != usize::from(size))
+            || payload.len() > usize::from(size)
+        {
+            return Err(FragError::Header);
+        }
+        if self.done == Some((msg_id, last, size)) {
+            return Ok(None);
+        }
+
+        if self.is_expired(now)
+            || self.msg_id != Some(msg_id)
+            || self.last != last
+            || self.size != size
+        {
+            self.reset();
+            self.msg_id = Some(msg_id);
+            self.last = last;
+            self.size = size;
+            self.done = None;
+        }
+        self.last_rx = now;
+
+        let index: usize = usize::from(index);
+        if self.is_received(index) {
+            return Ok(None);
+        }
+
+        let start: usize = index * usize::from(size);
+        let end: usize = start + payload.len();
+        if end > self.buf.len() {
+            self.reset();
+            return Err(FragError::BufferTooSmall);
+        }
+        self.buf[start..end].copy_from_slice(payload);
+        self.received[index / 32] |= 1 << (index % 32);
+        if index == usize::from(last) {
+            self.len = end;
+        }
+
+        if (0..=usize::from(last)).all(|i| self.is_received(i)) {
+            let len: usize = self.len;
+            self.reset();
+            self.done = Some((msg_id, last, size));
+            Ok(Some(&self.buf[..len]))
+        } else {
+            Ok(None)
+        }
+    }
+}
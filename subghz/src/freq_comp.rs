@@ -0,0 +1,177 @@
+use crate::{HseTrim, RfFreq};
+
+/// Frequency error compensation.
+///
+/// Accumulates the frequency error of received packets, for example from
+/// [`lora_freq_err`], with an exponential moving average.
+/// The average can then be applied to the [`RfFreq`] or to the [`HseTrim`]
+/// to follow the frequency of a reference transmitter such as a gateway.
+///
+/// The weight of each new sample is 1 / 2<sup>`shift`</sup>, the first
+/// sample after a reset is used as-is.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{FreqComp, RfFreq};
+///
+/// let mut comp: FreqComp = FreqComp::new(2);
+/// comp.update(1000);
+/// comp.update(1400);
+/// assert_eq!(comp.offset_hz(), 1100);
+/// assert_eq!(comp.samples(), 2);
+///
+/// let freq: RfFreq = comp.rf_freq(RfFreq::F915);
+/// assert_eq!(freq, RfFreq::from_frequency(915_001_100));
+/// ```
+///
+/// [`lora_freq_err`]: crate::SubGhz::lora_freq_err
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FreqComp {
+    // average in 1/256 Hz
+    avg: i64,
+    samples: u32,
+    shift: u8,
+}
+
+impl FreqComp {
+    /// Create a new frequency error compensator.
+    ///
+    /// `shift` values greater than 16 will be set to 16.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::FreqComp;
+    ///
+    /// const COMP: FreqComp = FreqComp::new(3);
+    /// assert_eq!(COMP.offset_hz(), 0);
+    /// assert_eq!(COMP.samples(), 0);
+    /// ```
+    pub const fn new(shift: u8) -> FreqComp {
+        FreqComp {
+            avg: 0,
+            samples: 0,
+            shift: if shift > 16 { 16 } else { shift },
+        }
+    }
+
+    /// Add a frequency error sample in hertz.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::FreqComp;
+    ///
+    /// let mut comp: FreqComp = FreqComp::new(1);
+    /// comp.update(-200);
+    /// comp.update(-400);
+    /// assert_eq!(comp.offset_hz(), -300);
+    /// ```
+    pub fn update(&mut self, err_hz: i32) {
+        let sample: i64 = i64::from(err_hz) << 8;
+        if self.samples == 0 {
+            self.avg = sample;
+        } else {
+            self.avg += (sample - self.avg) >> self.shift;
+        }
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    /// Average frequency error in hertz.
+    pub const fn offset_hz(&self) -> i32 {
+        (self.avg >> 8) as i32
+    }
+
+    /// Number of samples since the last reset.
+    pub const fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Clear the average.
+    ///
+    /// Call this after the compensation has been applied, the frequency
+    /// error of the following packets is relative to the new setting.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::FreqComp;
+    ///
+    /// let mut comp: FreqComp = FreqComp::new(1);
+    /// comp.update(500);
+    /// comp.reset();
+    /// assert_eq!(comp, FreqComp::new(1));
+    /// ```
+    pub fn reset(&mut self) {
+        self.avg = 0;
+        self.samples = 0;
+    }
+
+    /// Apply the average frequency error to a RF frequency.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{FreqComp, LoRaBandwidth, RfFreq};
+    ///
+    /// let mut freq: RfFreq = RfFreq::F868;
+    /// let mut comp: FreqComp = FreqComp::new(2);
+    ///
+    /// // after each packet from the gateway
+    /// comp.update(sg.lora_freq_err(LoRaBandwidth::Bw125)?);
+    /// if comp.samples() >= 8 {
+    ///     freq = comp.rf_freq(freq);
+    ///     sg.set_rf_frequency(&freq)?;
+    ///     comp.reset();
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn rf_freq(&self, freq: RfFreq) -> RfFreq {
+        let hz: i64 = i64::from(freq.freq()) + i64::from(self.offset_hz());
+        RfFreq::from_frequency(hz.max(0) as u32)
+    }
+
+    /// Apply the average frequency error to the HSE32 load capacitor trim.
+    ///
+    /// `freq` is the RF frequency the error was measured at, and
+    /// `ppb_per_step` is the frequency change of the HSE32 crystal in parts
+    /// per billion for each step of the trim.
+    /// The crystal pulling depends on the crystal and the board, it should be
+    /// measured.
+    ///
+    /// More capacitance lowers the crystal frequency, a positive error
+    /// (received signal above the RF frequency) decreases the trim.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FreqComp, HseTrim, RfFreq};
+    ///
+    /// let mut comp: FreqComp = FreqComp::new(0);
+    /// // +2 ppm at 915 MHz
+    /// comp.update(1830);
+    /// assert_eq!(
+    ///     comp.hse_trim(RfFreq::F915, HseTrim::POR, 500),
+    ///     HseTrim::from_raw(0x12 - 4)
+    /// );
+    /// assert_eq!(comp.hse_trim(RfFreq::F915, HseTrim::MIN, 500), HseTrim::MIN);
+    /// ```
+    pub fn hse_trim(&self, freq: RfFreq, trim: HseTrim, ppb_per_step: u32) -> HseTrim {
+        let hz: i64 = i64::from(freq.freq());
+        if hz == 0 || ppb_per_step == 0 {
+            return trim;
+        }
+        let ppb: i64 = i64::from(self.offset_hz()) * 1_000_000_000 / hz;
+        let step: i64 = i64::from(ppb_per_step);
+        let steps: i64 = if ppb < 0 {
+            (ppb - step / 2) / step
+        } else {
+            (ppb + step / 2) / step
+        };
+        let raw: i64 = i64::from(u8::from(trim)) - steps;
+        HseTrim::from_raw(raw.clamp(0, i64::from(u8::MAX)) as u8)
+    }
+}
@@ -1,9 +1,13 @@
+/// Mask of all the IRQ bits.
+pub(crate) const IRQ_ALL: u16 = 0x03FF;
+
 /// IRQ bit mapping
 ///
 /// See table 37 "IRQ bit mapping and definition" in the reference manual for
 /// more information.
 #[repr(u16)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Irq {
     /// Packet transmission finished.
     ///
@@ -80,6 +84,7 @@ impl Irq {
 /// This is an argument of [`CfgDioIrq::irq_enable`] and
 /// [`CfgDioIrq::irq_disable`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum IrqLine {
     /// Global interrupt.
     Global,
@@ -106,6 +111,7 @@ impl IrqLine {
 ///
 /// [`set_irq_cfg`]: crate::SubGhz::set_irq_cfg
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CfgDioIrq {
     buf: [u8; 9],
 }
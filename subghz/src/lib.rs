@@ -1,41 +1,90 @@
 //! The sub-GHz radio is an ultra-low-power sub-GHz radio operating in the
 //! 150 - 960 MHz ISM band.
+//!
+//! # Protocol layers
+//!
+//! The protocol state of [`Arq`], [`Mesh`], [`Fhss`], and [`Reassembler`]
+//! does not use the radio, it can be driven by any transport, and tested on
+//! a host.
+//! The `SubGhz::poll_*` methods drive it with the sub-GHz radio.
+//!
+//! Time is measured in caller defined ticks, for example a timer or RTC
+//! counter, and may wrap.
+//! Durations derived from the modulation, such as an ACK timeout, are
+//! converted with the tick frequency passed to the constructor.
 #![cfg_attr(not(test), no_std)]
 
+mod arq;
 mod cad_params;
 mod calibrate;
+mod crc;
+mod diagnostics;
+mod dma;
 mod fallback_mode;
+mod fhss;
+mod frag;
+mod freq_comp;
 mod hse_trim;
 mod irq;
+mod link_budget;
+mod lora_symb_timeout;
 mod lora_sync_word;
+mod mesh;
 mod mod_params;
+mod modem_cfg;
 mod ocp;
 mod op_error;
 mod pa_config;
 mod packet_params;
 mod packet_status;
+mod packet_timing;
 mod packet_type;
+mod protocol;
+#[cfg(feature = "radio")]
+mod radio_hal;
+mod radio_rng;
 mod reg_mode;
+mod register;
 mod rf_frequency;
+mod rx_gain;
+mod rx_queue;
 mod rx_timeout_stop;
 mod sleep_cfg;
+mod smps;
 mod standby_clk;
 mod stats;
 mod status;
+mod sun_fsk;
 mod tcxo_mode;
 mod timeout;
 mod tx_params;
+mod tx_queue;
 mod value_error;
+mod whitening;
+mod wmbus;
 
-pub use cad_params::{CadParams, ExitMode, NbCadSymbol};
+pub use arq::{Arq, ArqError, ArqEvent, ARQ_HEADER_LEN};
+pub use cad_params::{CadBias, CadParams, ExitMode, NbCadSymbol};
 pub use calibrate::{Calibrate, CalibrateImage};
+pub use crc::GenericCrc;
+pub use diagnostics::Diagnostics;
+pub use dma::{Dma, DmaCh, DmaTransfer};
 pub use fallback_mode::FallbackMode;
+pub use fhss::Fhss;
+pub use frag::{FragError, Fragmenter, Reassembler, FRAG_HEADER_LEN, FRAG_MAX};
+pub use freq_comp::FreqComp;
 pub use hse_trim::HseTrim;
 pub use irq::{CfgDioIrq, Irq, IrqLine};
+pub use link_budget::LinkBudget;
+pub use lora_symb_timeout::LoRaSymbTimeout;
 pub use lora_sync_word::LoRaSyncWord;
+pub use mesh::{Mesh, MeshError, MeshEvent, MESH_BROADCAST, MESH_HEADER_LEN};
 pub use mod_params::BpskModParams;
 pub use mod_params::{CodingRate, LoRaBandwidth, LoRaModParams, SpreadingFactor};
-pub use mod_params::{FskBandwidth, FskBitrate, FskFdev, FskModParams, FskPulseShape};
+pub use mod_params::{
+    FskBandwidth, FskBitrate, FskFdev, FskModParams, FskModParamsError, FskPulseShape,
+};
+pub use modem_cfg::{ModemCfg, PacketTypeMismatch};
 pub use ocp::Ocp;
 pub use op_error::OpError;
 pub use pa_config::{PaConfig, PaSel};
@@ -44,18 +93,32 @@ pub use packet_params::{
     PreambleDetection,
 };
 pub use packet_status::{FskPacketStatus, LoRaPacketStatus};
+#[cfg(not(feature = "stm32wl5x_cm0p"))]
+pub use packet_timing::dwt_cycle_count;
+pub use packet_timing::PacketTiming;
 pub use packet_type::PacketType;
+#[cfg(feature = "radio")]
+pub use radio_hal::{FskRadio, LoRaRadio, Radio, RadioError, RadioMode, RxInfo};
+pub use radio_rng::{RadioRng, RadioRngError};
 pub use reg_mode::RegMode;
+pub use register::Register;
 pub use rf_frequency::RfFreq;
+pub use rx_gain::RxGain;
+pub use rx_queue::{RxPacket, RxQueue};
 pub use rx_timeout_stop::RxTimeoutStop;
 pub use sleep_cfg::{SleepCfg, Startup};
+pub use smps::{SmpsCfg, SmpsDrv};
 pub use standby_clk::StandbyClk;
 pub use stats::{FskStats, LoRaStats, Stats};
 pub use status::{CmdStatus, Status, StatusMode};
+pub use sun_fsk::{SunFcs, SunFsk, SunFskError, SunPhr, SunSfd, SUN_PHR_LEN, SUN_PSDU_MAX};
 pub use tcxo_mode::{TcxoMode, TcxoTrim};
 pub use timeout::Timeout;
 pub use tx_params::{RampTime, TxParams};
+pub use tx_queue::{TxMeta, TxQueue, TxQueueError, TxStatus};
 pub use value_error::ValueError;
+pub use whitening::Whitening;
+pub use wmbus::{Wmbus, WmbusError, WmbusFormat, WmbusMode};
 
 use core::{
     convert::Infallible,
@@ -63,6 +126,9 @@ use core::{
 };
 
 pub use num_rational;
+#[cfg(feature = "radio")]
+pub use radio;
+pub use rand_core;
 
 use num_rational::Ratio;
 
@@ -89,6 +155,13 @@ pub struct SubGhz {
     spi: pac::SPI3,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for SubGhz {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "SubGhz {{ .. }}")
+    }
+}
+
 impl SubGhz {
     /// Create a new sub-GHz radio driver from a peripheral.
     ///
@@ -164,6 +237,9 @@ impl SubGhz {
     /// You are responsible for ensuring that the radio has exclusive access to
     /// these peripherals.
     ///
+    /// On the STM32WL5x both cores can access the radio, the
+    /// `stm32wl-hal-hsem` crate provides a semaphore to arbitrate access.
+    ///
     /// # Example
     ///
     /// ```
@@ -314,27 +390,86 @@ impl SubGhz {
 // 5.8.2
 /// Register access.
 impl SubGhz {
-    #[allow(dead_code)]
+    /// Read a single register.
+    ///
+    /// # Example
+    ///
+    /// Read the LoRa payload length of the last received packet.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::Register;
+    ///
+    /// let len: u8 = sg.read_register(Register::LPLDLEN)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn read_register(&mut self, register: Register) -> Result<u8, SubGhzError> {
+        let mut buf: [u8; 1] = [0; 1];
+        self.read_registers(register, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Read consecutive registers, starting at `register`.
+    ///
+    /// The number of registers read is equal to the length of `buf`.
+    ///
+    /// # Example
+    ///
+    /// Read the generic synchronization word.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::Register;
+    ///
+    /// let mut sync_word: [u8; 8] = [0; 8];
+    /// sg.read_registers(Register::GSYNC7, &mut sync_word)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
     #[allow(clippy::unnecessary_wraps)]
-    fn read_register(&mut self, register: Register) -> Result<u8, SubGhzError> {
+    pub fn read_registers(
+        &mut self,
+        register: Register,
+        buf: &mut [u8],
+    ) -> Result<(), SubGhzError> {
         let dp = unsafe { pac::Peripherals::steal() };
         let pwr = &dp.PWR;
         self.poll_not_busy();
 
         pwr.subghzspicr.write(|w| w.nss().clear_bit());
-        self.write_byte_raw(0x1D);
-        let addr: u16 = register.address();
-        self.write_byte_raw(((addr >> 8) & 0xFF) as u8);
-        self.write_byte_raw((addr & 0xFF) as u8);
-        let ret: u8 = self.read_byte_raw();
+        self.write_byte_raw(OpCode::ReadRegister as u8);
+        register
+            .address()
+            .to_be_bytes()
+            .iter()
+            .for_each(|&b| self.write_byte_raw(b));
+        // status byte
+        self.read_byte_raw();
+        buf.iter_mut().for_each(|b| *b = self.read_byte_raw());
         pwr.subghzspicr.write(|w| w.nss().set_bit());
 
         self.poll_not_busy();
-        Ok(ret)
+        Ok(())
     }
 
+    /// Write consecutive registers, starting at `register`.
+    ///
+    /// The number of registers written is equal to the length of `data`.
+    ///
+    /// # Example
+    ///
+    /// Set the TX clamp configuration to improve robustness against antenna
+    /// mismatch.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::Register;
+    ///
+    /// let clamp: u8 = sg.read_register(Register::TXCLAMP)?;
+    /// sg.write_register(Register::TXCLAMP, &[clamp | 0x1E])?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
     #[allow(clippy::unnecessary_wraps)]
-    fn write_register(&mut self, register: Register, data: &[u8]) -> Result<(), SubGhzError> {
+    pub fn write_register(&mut self, register: Register, data: &[u8]) -> Result<(), SubGhzError> {
         let dp = unsafe { pac::Peripherals::steal() };
         let pwr = &dp.PWR;
         self.poll_not_busy();
@@ -483,6 +618,233 @@ impl SubGhz {
     pub fn set_hse_out_trim(&mut self, trim: HseTrim) -> Result<(), SubGhzError> {
         self.write_register(Register::HSEOUTTRIM, &[trim.into()])
     }
+
+    /// Get the HSE32 crystal OSC_IN load capacitor trimming.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::HseTrim;
+    ///
+    /// let trim: HseTrim = sg.hse_in_trim()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn hse_in_trim(&mut self) -> Result<HseTrim, SubGhzError> {
+        Ok(HseTrim::from_raw(self.read_register(Register::HSEINTRIM)?))
+    }
+
+    /// Get the HSE32 crystal OSC_OUT load capacitor trimming.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::HseTrim;
+    ///
+    /// let trim: HseTrim = sg.hse_out_trim()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn hse_out_trim(&mut self) -> Result<HseTrim, SubGhzError> {
+        Ok(HseTrim::from_raw(self.read_register(Register::HSEOUTTRIM)?))
+    }
+
+    /// Set the full 9-bit initial value for generic packet whitening.
+    ///
+    /// Unlike [`set_initial_whitening`] this also sets the most significant
+    /// bit, which is `1` after reset.
+    /// Bits above bit 8 are ignored.
+    ///
+    /// # Example
+    ///
+    /// Seed the whitening to match [`Whitening::new(0x1FF)`](crate::Whitening::new).
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// sg.set_whitening_seed(0x1FF)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`set_initial_whitening`]: SubGhz::set_initial_whitening
+    pub fn set_whitening_seed(&mut self, seed: u16) -> Result<(), SubGhzError> {
+        let ctl: u8 = self.read_register(Register::GPKTCTL1A)?;
+        let msb: u8 = ((seed >> 8) & 0b1) as u8;
+        self.write_register(Register::GPKTCTL1A, &[(ctl & !0b1) | msb])?;
+        self.write_register(Register::GWHITEINIRL, &[seed as u8])
+    }
+
+    /// Set the generic packet node address.
+    ///
+    /// This is used when address comparison is enabled with
+    /// [`GenericPacketParams::set_addr_comp`](crate::GenericPacketParams::set_addr_comp).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// sg.set_node_address(0x12)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn set_node_address(&mut self, addr: u8) -> Result<(), SubGhzError> {
+        self.write_register(Register::GNODEADR, &[addr])
+    }
+
+    /// Get the generic packet node address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let addr: u8 = sg.node_address()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn node_address(&mut self) -> Result<u8, SubGhzError> {
+        self.read_register(Register::GNODEADR)
+    }
+
+    /// Set the generic packet broadcast address.
+    ///
+    /// This is used when address comparison is enabled with
+    /// [`AddrComp::Broadcast`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// sg.set_broadcast_address(0xFF)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn set_broadcast_address(&mut self, addr: u8) -> Result<(), SubGhzError> {
+        self.write_register(Register::GBCASTADR, &[addr])
+    }
+
+    /// Get the generic packet broadcast address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let addr: u8 = sg.broadcast_address()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn broadcast_address(&mut self) -> Result<u8, SubGhzError> {
+        self.read_register(Register::GBCASTADR)
+    }
+
+    /// Get the generic packet payload length.
+    ///
+    /// For variable length packets this is the length of the last received
+    /// packet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let len: u8 = sg.generic_payload_len()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn generic_payload_len(&mut self) -> Result<u8, SubGhzError> {
+        self.read_register(Register::GRTXPLDLEN)
+    }
+
+    /// Get the LoRa payload length.
+    ///
+    /// With an explicit header this is the length of the last received
+    /// packet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let len: u8 = sg.lora_payload_len()?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn lora_payload_len(&mut self) -> Result<u8, SubGhzError> {
+        self.read_register(Register::LPLDLEN)
+    }
+
+    /// Add a register to the register retention list.
+    ///
+    /// Registers in the retention list keep their value in sleep mode with a
+    /// warm start.
+    /// The list holds up to 4 registers, this is the same as
+    /// `RadioAddRegisterToRetentionList` in the ST radio driver.
+    ///
+    /// Returns `false` if the list is full and the register is not already
+    /// in the list.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::Register;
+    ///
+    /// let added: bool = sg.add_to_retention_list(Register::TXCLAMP)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn add_to_retention_list(&mut self, register: Register) -> Result<bool, SubGhzError> {
+        const RET_MAX: usize = 4;
+
+        // RETLEN followed by RETLIST
+        let mut buf: [u8; 1 + 2 * RET_MAX] = [0; 1 + 2 * RET_MAX];
+        self.read_registers(Register::RETLEN, &mut buf)?;
+        let len: usize = usize::from(buf[0]).min(RET_MAX);
+        let addr: [u8; 2] = register.address().to_be_bytes();
+
+        if buf[1..1 + 2 * len].chunks_exact(2).any(|a| a == addr) {
+            Ok(true)
+        } else if len == RET_MAX {
+            Ok(false)
+        } else {
+            buf[1 + 2 * len..3 + 2 * len].copy_from_slice(&addr);
+            buf[0] = (len + 1) as u8;
+            self.write_register(Register::RETLEN, &buf)?;
+            Ok(true)
+        }
+    }
+
+    /// Set the RX gain.
+    ///
+    /// The RX gain register is also added to the register retention list
+    /// with [`add_to_retention_list`] so that the setting is retained in
+    /// sleep mode with a warm start.
+    ///
+    /// Returns `false` if the retention list is full, the gain is set but it
+    /// is not retained in sleep mode with a warm start.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::RxGain;
+    ///
+    /// let retained: bool = sg.set_rx_gain(RxGain::Boosted)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`add_to_retention_list`]: Self::add_to_retention_list
+    pub fn set_rx_gain(&mut self, rx_gain: RxGain) -> Result<bool, SubGhzError> {
+        let retained: bool = self.add_to_retention_list(Register::RXGAINC)?;
+        self.write_register(Register::RXGAINC, &[rx_gain.into()])?;
+        Ok(retained)
+    }
+
+    /// Get the RX gain.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::RxGain;
+    ///
+    /// if sg.rx_gain()? == Ok(RxGain::Boosted) {
+    ///     // ...
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn rx_gain(&mut self) -> Result<Result<RxGain, u8>, SubGhzError> {
+        Ok(RxGain::from_raw(self.read_register(Register::RXGAINC)?))
+    }
 }
 
 // 5.8.3
@@ -609,6 +971,36 @@ impl SubGhz {
         ])
     }
 
+    /// Set the sub-GHz radio in RX mode with boosted gain.
+    ///
+    /// This is the same as [`set_rx`], but sets the RX gain to
+    /// [`RxGain::Boosted`] first, see [`set_rx_gain`] for more details.
+    ///
+    /// Returns `false` if the boosted gain is not retained in sleep mode with
+    /// a warm start because the register retention list is full.
+    ///
+    /// # Example
+    ///
+    /// Receive with a 1 second timeout and boosted gain.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::Timeout;
+    ///
+    /// let retained: bool =
+    ///     sg.set_rx_boosted(Timeout::from_duration_sat(Duration::from_secs(1)))?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`set_rx`]: SubGhz::set_rx
+    /// [`set_rx_gain`]: SubGhz::set_rx_gain
+    pub fn set_rx_boosted(&mut self, timeout: Timeout) -> Result<bool, SubGhzError> {
+        let retained: bool = self.set_rx_gain(RxGain::Boosted)?;
+        self.set_rx(timeout)?;
+        Ok(retained)
+    }
+
     /// Allows selection of the receiver event which stops the RX timeout timer.
     ///
     /// # Example
@@ -837,6 +1229,111 @@ impl SubGhz {
         Ok(PacketType::from_raw(pkt_type[1]))
     }
 
+    /// Check that the packet type of the radio matches `expected`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{PacketType, PacketTypeMismatch};
+    ///
+    /// sg.set_packet_type(PacketType::Fsk)?;
+    /// assert_eq!(sg.check_packet_type(PacketType::Fsk)?, Ok(()));
+    /// assert_eq!(
+    ///     sg.check_packet_type(PacketType::LoRa)?,
+    ///     Err(PacketTypeMismatch {
+    ///         expected: PacketType::LoRa,
+    ///         actual: Ok(PacketType::Fsk),
+    ///     })
+    /// );
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn check_packet_type(
+        &self,
+        expected: PacketType,
+    ) -> Result<Result<(), PacketTypeMismatch>, SubGhzError> {
+        let actual: Result<PacketType, u8> = self.packet_type()?;
+        if actual == Ok(expected) {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(PacketTypeMismatch { expected, actual }))
+        }
+    }
+
+    /// Set the packet type, modulation parameters, and packet parameters.
+    ///
+    /// The packet type is read back after it is set, the modulation and
+    /// packet parameters are only written if it matches.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{
+    ///     CrcType, FskBandwidth, FskBitrate, FskFdev, FskModParams, FskPulseShape,
+    ///     GenericPacketParams, HeaderType, ModemCfg,
+    /// };
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::Fsk {
+    ///     mod_params: FskModParams::new()
+    ///         .set_bitrate(FskBitrate::from_bps(20_000))
+    ///         .set_pulse_shape(FskPulseShape::Bt03)
+    ///         .set_bandwidth(FskBandwidth::Bw58)
+    ///         .set_fdev(FskFdev::from_hertz(10_000)),
+    ///     packet_params: GenericPacketParams::new()
+    ///         .set_header_type(HeaderType::Variable)
+    ///         .set_payload_len(64)
+    ///         .set_crc_type(CrcType::Byte2),
+    /// };
+    ///
+    /// sg.set_modem_cfg(&MODEM_CFG)?
+    ///     .expect("radio did not accept the packet type");
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn set_modem_cfg(
+        &mut self,
+        cfg: &ModemCfg,
+    ) -> Result<Result<(), PacketTypeMismatch>, SubGhzError> {
+        self.set_packet_type(cfg.packet_type())?;
+        if let Err(e) = self.check_packet_type(cfg.packet_type())? {
+            return Ok(Err(e));
+        }
+
+        match cfg {
+            ModemCfg::LoRa {
+                mod_params,
+                packet_params,
+            } => {
+                self.set_lora_mod_params(mod_params)?;
+                self.set_lora_packet_params(packet_params)?;
+            }
+            ModemCfg::Fsk {
+                mod_params,
+                packet_params,
+            } => {
+                self.set_fsk_mod_params(mod_params)?;
+                self.set_packet_params(packet_params)?;
+            }
+            ModemCfg::Bpsk {
+                mod_params,
+                packet_params,
+            } => {
+                self.set_bpsk_mod_params(mod_params)?;
+                self.set_bpsk_packet_params(packet_params)?;
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Write the packet parameters of a modem configuration.
+    pub(crate) fn set_modem_packet_params(&mut self, cfg: &ModemCfg) -> Result<(), SubGhzError> {
+        match cfg {
+            ModemCfg::LoRa { packet_params, .. } => self.set_lora_packet_params(packet_params),
+            ModemCfg::Fsk { packet_params, .. } => self.set_packet_params(packet_params),
+            ModemCfg::Bpsk { packet_params, .. } => self.set_bpsk_packet_params(packet_params),
+        }
+    }
+
     /// Set the radio carrier frequency.
     ///
     /// # Example
@@ -1134,6 +1631,43 @@ impl SubGhz {
     pub fn set_lora_symb_timeout(&mut self, n: u8) -> Result<(), SubGhzError> {
         self.write(&[OpCode::SetLoRaSymbTimeout.into(), n])
     }
+
+    /// Set the LoRa symbol timeout with the mantissa and exponent encoding.
+    ///
+    /// This writes the command and the [`LSYMBTMO`] register, the same as the
+    /// ST radio driver, which is required for timeouts of more than 63
+    /// symbols.
+    ///
+    /// # Example
+    ///
+    /// Size the timeout of a LoRaWAN RX window.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{
+    ///     LoRaBandwidth, LoRaModParams, LoRaSymbTimeout, SpreadingFactor, Timeout,
+    /// };
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new()
+    ///     .set_sf(SpreadingFactor::Sf7)
+    ///     .set_bw(LoRaBandwidth::Bw125);
+    /// const WINDOW: Timeout = Timeout::from_duration_sat(Duration::from_millis(100));
+    ///
+    /// let symbols: u32 = WINDOW.as_lora_symbols(&MOD_PARAMS);
+    /// let timeout = LoRaSymbTimeout::from_symbols(symbols.min(255) as u8);
+    /// sg.set_lora_symb_num_timeout(&timeout)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`LSYMBTMO`]: crate::Register::LSYMBTMO
+    pub fn set_lora_symb_num_timeout(&mut self, to: &LoRaSymbTimeout) -> Result<(), SubGhzError> {
+        self.write(&[OpCode::SetLoRaSymbTimeout.into(), to.cmd_bits()])?;
+        if to.symbols() != 0 {
+            self.write_register(Register::LSYMBTMO, &[to.reg_bits()])?;
+        }
+        Ok(())
+    }
 }
 
 // 5.8.5
@@ -1238,6 +1772,34 @@ impl SubGhz {
         ))
     }
 
+    /// Get the frequency error of the last received LoRa packet in hertz.
+    ///
+    /// `bw` must be the bandwidth in the LoRa modulation parameters.
+    ///
+    /// The error is positive when the received signal is above the RF
+    /// frequency.
+    /// Use [`FreqComp`] to accumulate the error over several packets and
+    /// compensate for crystal drift.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::LoRaBandwidth;
+    ///
+    /// let err: i32 = sg.lora_freq_err(LoRaBandwidth::Bw125)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn lora_freq_err(&mut self, bw: LoRaBandwidth) -> Result<i32, SubGhzError> {
+        let mut buf: [u8; 3] = [0; 3];
+        self.read_registers(Register::LFERR, &mut buf)?;
+        // 20-bit two's complement
+        let raw: u32 = u32::from_be_bytes([0, buf[0], buf[1], buf[2]]) & 0x000F_FFFF;
+        let efe: i64 = i64::from(((raw << 12) as i32) >> 12);
+        // 1.55 × efe / (1600 / bandwidth in kHz)
+        Ok((efe * i64::from(bw.hertz()) * 155 / 160_000_000) as i32)
+    }
+
     /// Get the instantaneous signal strength during packet reception.
     ///
     /// The units are in dbm.
@@ -1475,6 +2037,66 @@ impl SubGhz {
         self.write(&[OpCode::SetRegulatorMode as u8, reg_mode as u8])
     }
 
+    /// Set the SMPS clock detection and drive capability.
+    ///
+    /// # Example
+    ///
+    /// Increase the SMPS drive before transmitting at +22 dBm with the HP PA,
+    /// and restore the low power configuration after the transmission.
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{PaConfig, PaSel, RegMode, SmpsCfg, Timeout, TxParams};
+    ///
+    /// const PA_CONFIG: PaConfig = PaConfig::new().set_pa(PaSel::Hp);
+    /// const TX_PARAMS: TxParams = TxParams::new().set_power(0x16);
+    ///
+    /// sg.set_regulator_mode(RegMode::Smps)?;
+    /// sg.set_smps_cfg(&SmpsCfg::for_tx(PA_CONFIG.pa()))?;
+    /// sg.set_pa_config(&PA_CONFIG)?;
+    /// sg.set_tx_params(&TX_PARAMS)?;
+    /// sg.set_tx(Timeout::DISABLED)?;
+    /// // wait for TxDone
+    /// sg.set_smps_cfg(&SmpsCfg::low_power())?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn set_smps_cfg(&mut self, cfg: &SmpsCfg) -> Result<(), SubGhzError> {
+        const CLKDE: u8 = 1 << 6;
+        const DRV_POS: u8 = 1;
+        const DRV_MASK: u8 = 0b11 << DRV_POS;
+
+        let smpsc0: u8 = self.read_register(Register::SMPSC0)?;
+        let smpsc0: u8 = if cfg.clk_det_en() {
+            smpsc0 | CLKDE
+        } else {
+            smpsc0 & !CLKDE
+        };
+        self.write_register(Register::SMPSC0, &[smpsc0])?;
+
+        let smpsc2: u8 = self.read_register(Register::SMPSC2)?;
+        let smpsc2: u8 = (smpsc2 & !DRV_MASK) | ((cfg.drv() as u8) << DRV_POS);
+        self.write_register(Register::SMPSC2, &[smpsc2])
+    }
+
+    /// Get the SMPS clock detection and drive capability.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::SmpsDrv;
+    ///
+    /// let drv: SmpsDrv = sg.smps_cfg()?.drv();
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn smps_cfg(&mut self) -> Result<SmpsCfg, SubGhzError> {
+        let smpsc0: u8 = self.read_register(Register::SMPSC0)?;
+        let smpsc2: u8 = self.read_register(Register::SMPSC2)?;
+        Ok(SmpsCfg::new()
+            .set_clk_det_en(smpsc0 & (1 << 6) != 0)
+            .set_drv(SmpsDrv::from_raw(smpsc2 >> 1)))
+    }
+
     /// Get the radio operational errors.
     ///
     /// # Example
@@ -1541,6 +2163,67 @@ impl SubGhz {
     }
 }
 
+/// Diagnostics.
+impl SubGhz {
+    /// Capture a snapshot of the radio state for debugging.
+    ///
+    /// This reads the status, errors, IRQ status, packet type, RX buffer
+    /// status, packet statistics, key registers, and the `PWR` registers that
+    /// are used to communicate with the radio.
+    /// Nothing is cleared or reset.
+    ///
+    /// This will wake the radio if it is in sleep mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::Diagnostics;
+    ///
+    /// let diag: Diagnostics = sg.diagnostics()?;
+    /// // ... log diag with Display
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn diagnostics(&mut self) -> Result<Diagnostics, SubGhzError> {
+        let dp = unsafe { pac::Peripherals::steal() };
+        // capture PWR state before any command changes it
+        let rfbusys: bool = self.rfbusys();
+        let pwr_sr2: u32 = dp.PWR.sr2.read().bits();
+        let pwr_subghzspicr: u32 = dp.PWR.subghzspicr.read().bits();
+        let pwr_cr1: u32 = dp.PWR.cr1.read().bits();
+
+        let status: u8 = self.status()?.into();
+        let (_, op_error) = self.op_error()?;
+        let (_, irq_status) = self.irq_status()?;
+        let packet_type: u8 = match self.packet_type()? {
+            Ok(packet_type) => packet_type as u8,
+            Err(bits) => bits,
+        };
+        let (_, rx_payload_len, rx_buffer_ptr) = self.rx_buffer_status()?;
+        let stats: [u8; 7] = self.read_n(OpCode::GetStats)?;
+
+        Ok(Diagnostics {
+            status,
+            op_error,
+            irq_status,
+            packet_type,
+            rx_payload_len,
+            rx_buffer_ptr,
+            stats,
+            pa_ocp: self.read_register(Register::PAOCP)?,
+            hse_in_trim: self.read_register(Register::HSEINTRIM)?,
+            hse_out_trim: self.read_register(Register::HSEOUTTRIM)?,
+            rx_gain: self.read_register(Register::RXGAINC)?,
+            smpsc0: self.read_register(Register::SMPSC0)?,
+            smpsc2: self.read_register(Register::SMPSC2)?,
+            rfbusys,
+            pwr_sr2,
+            pwr_subghzspicr,
+            pwr_cr1,
+        })
+    }
+}
+
 /// sub-GHz radio opcodes.
 ///
 /// See Table 41 "Sub-GHz radio SPI commands overview"
@@ -1561,7 +2244,7 @@ pub(crate) enum OpCode {
     GetStats = 0x10,
     GetStatus = 0xC0,
     ReadBuffer = 0x1E,
-    RegRegister = 0x1D,
+    ReadRegister = 0x1D,
     ResetStats = 0x00,
     SetBufferBaseAddress = 0x8F,
     SetCad = 0xC5,
@@ -1594,33 +2277,3 @@ impl From<OpCode> for u8 {
         opcode as u8
     }
 }
-
-#[repr(u16)]
-#[allow(dead_code)]
-#[allow(clippy::upper_case_acronyms)]
-pub(crate) enum Register {
-    /// Generic CRC initial.
-    GCRCINIRH = 0x06BC,
-    /// Generic CRC polynomial.
-    GCRCPOLRH = 0x06BE,
-    /// Generic whitening.
-    GWHITEINIRL = 0x06B9,
-    /// PA over current protection.
-    PAOCP = 0x08E7,
-    /// LoRa synchronization word MSB.
-    LSYNCH = 0x0740,
-    /// LoRa synchronization word LSB.
-    LSYNCL = 0x0741,
-    /// Generic synchronization word 7.
-    GSYNC7 = 0x06C0,
-    /// HSE32 OSC_IN capacitor trim.
-    HSEINTRIM = 0x0911,
-    /// HSE32 OSC_OUT capacitor trim.
-    HSEOUTTRIM = 0x0912,
-}
-
-impl Register {
-    pub const fn address(self) -> u16 {
-        self as u16
-    }
-}
@@ -0,0 +1,295 @@
+use crate::{
+    CodingRate, FskBandwidth, FskBitrate, LoRaBandwidth, PaConfig, PaSel, SpreadingFactor, TxParams,
+};
+use num_rational::Ratio;
+
+/// Thermal noise floor at 290 K in tenths of dBm/Hz.
+const NOISE_FLOOR: i16 = -1740;
+
+/// E<sub>b</sub>/N<sub>0</sub> in tenths of dB for a bit error rate of 0.1%
+/// with non-coherent (G)FSK demodulation.
+const FSK_EB_N0: i16 = 110;
+
+/// 10 × log<sub>10</sub>(`x`) in tenths of dB, rounded to the nearest tenth.
+///
+/// `x` must be non-zero.
+const fn db_tenths(x: u32) -> i16 {
+    // integer part of log2
+    let msb: u32 = 31 - x.leading_zeros();
+    // mantissa in [1, 2) as Q30
+    let mut y: u64 = ((x as u64) << 30) >> msb;
+    // fractional part of log2 as Q16, one bit per squaring
+    let mut frac: u64 = 0;
+    let mut bit: u32 = 0;
+    while bit < 16 {
+        y = (y * y) >> 30;
+        frac <<= 1;
+        if y >= 2 << 30 {
+            y >>= 1;
+            frac |= 1;
+        }
+        bit += 1;
+    }
+    let log2_q16: u64 = ((msb as u64) << 16) | frac;
+    // 100 × log10(2) = 30.103
+    ((log2_q16 * 30_103 + 500 * 65_536) / (1000 * 65_536)) as i16
+}
+
+/// Link budget and sensitivity estimate.
+///
+/// The sensitivity is estimated from the receiver noise bandwidth, the
+/// noise figure, and the SNR required by the modulation:
+///
+/// sensitivity = -174 dBm/Hz + 10 × log<sub>10</sub>(bandwidth) + NF + SNR
+///
+/// The link budget is the TX output power minus the sensitivity, this is the
+/// maximum path loss between two radios with the same settings.
+///
+/// All calculations are `const` and do not require a radio.
+///
+/// # Example
+///
+/// Compare spreading factors.
+///
+/// ```
+/// use stm32wl_hal_subghz::{
+///     CodingRate, LinkBudget, LoRaBandwidth, PaConfig, PaSel, SpreadingFactor, TxParams,
+/// };
+///
+/// const PA_CONFIG: PaConfig = PaConfig::new().set_pa(PaSel::Hp);
+/// const TX_PARAMS: TxParams = TxParams::new().set_power(0x16);
+///
+/// const SF7: LinkBudget =
+///     LinkBudget::lora(SpreadingFactor::Sf7, LoRaBandwidth::Bw125, CodingRate::Cr45)
+///         .set_tx_power(&PA_CONFIG, &TX_PARAMS);
+/// const SF12: LinkBudget =
+///     LinkBudget::lora(SpreadingFactor::Sf12, LoRaBandwidth::Bw125, CodingRate::Cr45)
+///         .set_tx_power(&PA_CONFIG, &TX_PARAMS);
+///
+/// assert_eq!(SF7.sensitivity_tenths(), -1245);
+/// assert_eq!(SF12.sensitivity_tenths(), -1370);
+/// assert_eq!(SF12.link_budget_tenths() - SF7.link_budget_tenths(), 125);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkBudget {
+    bandwidth: u32,
+    bitrate: u32,
+    snr_limit: i16,
+    noise_figure: i16,
+    tx_power: i8,
+}
+
+impl LinkBudget {
+    /// Default receiver noise figure, 6 dB.
+    pub const DEFAULT_NOISE_FIGURE: i16 = 60;
+
+    /// Create a new estimate for LoRa modulation.
+    ///
+    /// The SNR limit is the demodulator limit for the spreading factor, from
+    /// -2.5 dB for SF5 to -20 dB for SF12.
+    ///
+    /// The noise figure is [`DEFAULT_NOISE_FIGURE`], and the TX power is
+    /// 0 dBm.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CodingRate, LinkBudget, LoRaBandwidth, SpreadingFactor};
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf9, LoRaBandwidth::Bw125, CodingRate::Cr45);
+    /// assert_eq!(LB.snr_limit_tenths(), -125);
+    /// assert_eq!(LB.bitrate(), 1757);
+    /// ```
+    ///
+    /// [`DEFAULT_NOISE_FIGURE`]: Self::DEFAULT_NOISE_FIGURE
+    pub const fn lora(sf: SpreadingFactor, bw: LoRaBandwidth, cr: CodingRate) -> LinkBudget {
+        let sf: u32 = sf.factor();
+        let bandwidth: u32 = bw.hertz();
+        // bitrate = SF × BW / 2^SF × 4 / (4 + CR)
+        let bitrate: u32 =
+            ((sf as u64) * (bandwidth as u64) * 4 / ((1 << sf) * (4 + cr as u64))) as u32;
+
+        LinkBudget {
+            bandwidth,
+            bitrate,
+            snr_limit: -25 * (sf as i16 - 4),
+            noise_figure: Self::DEFAULT_NOISE_FIGURE,
+            tx_power: 0,
+        }
+    }
+
+    /// Create a new estimate for (G)FSK modulation.
+    ///
+    /// The SNR limit is derived from an E<sub>b</sub>/N<sub>0</sub> of 11 dB,
+    /// a bit error rate of 0.1%, scaled by the ratio of the bitrate to the
+    /// bandwidth.
+    ///
+    /// The noise figure is [`DEFAULT_NOISE_FIGURE`], and the TX power is
+    /// 0 dBm.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBandwidth, FskBitrate, LinkBudget};
+    ///
+    /// const LB: LinkBudget = LinkBudget::fsk(FskBitrate::from_bps(4_800), FskBandwidth::Bw19);
+    /// assert_eq!(LB.snr_limit_tenths(), 49);
+    /// assert_eq!(LB.sensitivity_tenths(), -1202);
+    /// ```
+    ///
+    /// [`DEFAULT_NOISE_FIGURE`]: Self::DEFAULT_NOISE_FIGURE
+    pub const fn fsk(bitrate: FskBitrate, bw: FskBandwidth) -> LinkBudget {
+        let bandwidth: u32 = bw.hertz();
+        let bitrate: u32 = bitrate.as_bps();
+        let snr_limit: i16 = if bitrate == 0 {
+            FSK_EB_N0
+        } else {
+            FSK_EB_N0 + db_tenths(bitrate) - db_tenths(bandwidth)
+        };
+
+        LinkBudget {
+            bandwidth,
+            bitrate,
+            snr_limit,
+            noise_figure: Self::DEFAULT_NOISE_FIGURE,
+            tx_power: 0,
+        }
+    }
+
+    /// Set the receiver noise figure in tenths of dB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CodingRate, LinkBudget, LoRaBandwidth, SpreadingFactor};
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf7, LoRaBandwidth::Bw125, CodingRate::Cr45);
+    /// assert_eq!(
+    ///     LB.set_noise_figure(70).sensitivity_tenths(),
+    ///     LB.sensitivity_tenths() + 10
+    /// );
+    /// ```
+    #[must_use = "set_noise_figure returns a modified LinkBudget"]
+    pub const fn set_noise_figure(mut self, nf: i16) -> LinkBudget {
+        self.noise_figure = nf;
+        self
+    }
+
+    /// Set the TX output power from the PA and TX parameters.
+    ///
+    /// The output power is the power from [`TxParams::set_power`], clamped
+    /// to the range of the PA selected by [`PaConfig::set_pa`].
+    /// This assumes the PA configuration is the optimal configuration for the
+    /// output power, see table 35 "PA optimal setting and operating modes" in
+    /// the reference manual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{
+    ///     CodingRate, LinkBudget, LoRaBandwidth, PaConfig, PaSel, SpreadingFactor, TxParams,
+    /// };
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf7, LoRaBandwidth::Bw125, CodingRate::Cr45)
+    ///         .set_tx_power(&PaConfig::new().set_pa(PaSel::Lp), &TxParams::new().set_power(0x0E));
+    /// assert_eq!(LB.tx_power(), 14);
+    /// ```
+    #[must_use = "set_tx_power returns a modified LinkBudget"]
+    pub const fn set_tx_power(mut self, pa_config: &PaConfig, tx_params: &TxParams) -> LinkBudget {
+        let (min, max): (i8, i8) = match pa_config.pa() {
+            PaSel::Lp => (-17, 14),
+            PaSel::Hp => (-9, 22),
+        };
+        let power: i8 = tx_params.power() as i8;
+        self.tx_power = if power < min {
+            min
+        } else if power > max {
+            max
+        } else {
+            power
+        };
+        self
+    }
+
+    /// TX output power in dBm.
+    pub const fn tx_power(&self) -> i8 {
+        self.tx_power
+    }
+
+    /// Receiver noise bandwidth in hertz.
+    pub const fn bandwidth(&self) -> u32 {
+        self.bandwidth
+    }
+
+    /// Bitrate in bits per second, including the LoRa coding overhead.
+    pub const fn bitrate(&self) -> u32 {
+        self.bitrate
+    }
+
+    /// SNR limit in tenths of dB.
+    pub const fn snr_limit_tenths(&self) -> i16 {
+        self.snr_limit
+    }
+
+    /// Sensitivity in tenths of dBm.
+    pub const fn sensitivity_tenths(&self) -> i16 {
+        NOISE_FLOOR + db_tenths(self.bandwidth) + self.noise_figure + self.snr_limit
+    }
+
+    /// Link budget in tenths of dB.
+    pub const fn link_budget_tenths(&self) -> i16 {
+        (self.tx_power as i16) * 10 - self.sensitivity_tenths()
+    }
+
+    /// SNR limit in dB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CodingRate, LinkBudget, LoRaBandwidth, SpreadingFactor};
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf7, LoRaBandwidth::Bw125, CodingRate::Cr45);
+    /// assert_eq!(LB.snr_limit().to_integer(), -7);
+    /// ```
+    pub fn snr_limit(&self) -> Ratio<i16> {
+        Ratio::new(self.snr_limit_tenths(), 10)
+    }
+
+    /// Sensitivity in dBm.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{CodingRate, LinkBudget, LoRaBandwidth, SpreadingFactor};
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf12, LoRaBandwidth::Bw125, CodingRate::Cr45);
+    /// assert_eq!(LB.sensitivity().to_integer(), -137);
+    /// ```
+    pub fn sensitivity(&self) -> Ratio<i16> {
+        Ratio::new(self.sensitivity_tenths(), 10)
+    }
+
+    /// Link budget in dB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{
+    ///     CodingRate, LinkBudget, LoRaBandwidth, PaConfig, PaSel, SpreadingFactor, TxParams,
+    /// };
+    ///
+    /// const LB: LinkBudget =
+    ///     LinkBudget::lora(SpreadingFactor::Sf12, LoRaBandwidth::Bw125, CodingRate::Cr45)
+    ///         .set_tx_power(&PaConfig::new().set_pa(PaSel::Hp), &TxParams::new().set_power(0x16));
+    /// assert_eq!(LB.link_budget().to_integer(), 159);
+    /// ```
+    pub fn link_budget(&self) -> Ratio<i16> {
+        Ratio::new(self.link_budget_tenths(), 10)
+    }
+}
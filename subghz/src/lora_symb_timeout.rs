@@ -0,0 +1,104 @@
+/// LoRa symbol timeout.
+///
+/// Argument of [`set_lora_symb_num_timeout`].
+///
+/// The radio stores symbol timeouts as a mantissa and exponent,
+/// mantissa × 2<sup>2 × exponent + 1</sup> symbols, with a 5-bit mantissa.
+/// Odd numbers of symbols, and timeouts of more than 62 symbols, can not be
+/// represented exactly, they are rounded up to the next representable value.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::LoRaSymbTimeout;
+///
+/// assert_eq!(LoRaSymbTimeout::from_symbols(0).symbols(), 0);
+/// assert_eq!(LoRaSymbTimeout::from_symbols(5).symbols(), 6);
+/// assert_eq!(LoRaSymbTimeout::from_symbols(62).symbols(), 62);
+/// assert_eq!(LoRaSymbTimeout::from_symbols(100).symbols(), 104);
+/// assert_eq!(LoRaSymbTimeout::from_symbols(248).symbols(), 248);
+/// assert_eq!(LoRaSymbTimeout::from_symbols(255), LoRaSymbTimeout::MAX);
+/// ```
+///
+/// [`set_lora_symb_num_timeout`]: crate::SubGhz::set_lora_symb_num_timeout
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LoRaSymbTimeout {
+    // exponent first for the derived ordering
+    exp: u8,
+    mant: u8,
+}
+
+impl LoRaSymbTimeout {
+    /// No symbol timeout, reception starts after the first symbol.
+    pub const DISABLED: LoRaSymbTimeout = LoRaSymbTimeout { mant: 0, exp: 0 };
+
+    /// Maximum symbol timeout, 248 symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::LoRaSymbTimeout;
+    ///
+    /// assert_eq!(LoRaSymbTimeout::MAX.symbols(), 248);
+    /// ```
+    pub const MAX: LoRaSymbTimeout = LoRaSymbTimeout::from_symbols(248);
+
+    /// Create a new symbol timeout from a number of symbols.
+    ///
+    /// This uses the same rounding as the ST radio driver, values are rounded
+    /// up to the next representable value, and saturate at [`MAX`].
+    ///
+    /// [`MAX`]: LoRaSymbTimeout::MAX
+    pub const fn from_symbols(n: u8) -> LoRaSymbTimeout {
+        let n: u8 = if n > 248 { 248 } else { n };
+        let mut mant: u8 = (n + 1) >> 1;
+        let mut exp: u8 = 0;
+        while mant > 31 {
+            mant = (mant + 3) >> 2;
+            exp += 1;
+        }
+        LoRaSymbTimeout { mant, exp }
+    }
+
+    /// Number of symbols in the timeout.
+    pub const fn symbols(&self) -> u16 {
+        (self.mant as u16) << (2 * self.exp + 1)
+    }
+
+    /// Value for the set LoRa symbol timeout command.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::LoRaSymbTimeout;
+    ///
+    /// assert_eq!(LoRaSymbTimeout::from_symbols(10).cmd_bits(), 10);
+    /// assert_eq!(LoRaSymbTimeout::from_symbols(100).cmd_bits(), 13 << 3);
+    /// ```
+    pub const fn cmd_bits(&self) -> u8 {
+        self.mant << (2 * self.exp + 1)
+    }
+
+    /// Value for the [`LSYMBTMO`] register.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::LoRaSymbTimeout;
+    ///
+    /// assert_eq!(LoRaSymbTimeout::from_symbols(10).reg_bits(), 5 << 3);
+    /// assert_eq!(LoRaSymbTimeout::from_symbols(100).reg_bits(), (13 << 3) | 1);
+    /// ```
+    ///
+    /// [`LSYMBTMO`]: crate::Register::LSYMBTMO
+    pub const fn reg_bits(&self) -> u8 {
+        (self.mant << 3) | self.exp
+    }
+}
+
+impl Default for LoRaSymbTimeout {
+    fn default() -> Self {
+        LoRaSymbTimeout::DISABLED
+    }
+}
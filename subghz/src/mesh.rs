@@ -0,0 +1,687 @@
+use crate::{
+    irq::IRQ_ALL,
+    protocol::{mtu_len, RadioState},
+    Irq, LoRaPacketParams, StandbyClk, SubGhz, SubGhzError, Timeout,
+};
+use rand_core::RngCore;
+
+/// Length of the mesh header in bytes.
+///
+/// The header is:
+///
+/// | Byte | Field                                         |
+/// |------|-----------------------------------------------|
+/// |  0-1 | Source address, little-endian                 |
+/// |  2-3 | Destination address, little-endian            |
+/// |    4 | Sequence number, per source                   |
+/// |    5 | Remaining hops                                |
+pub const MESH_HEADER_LEN: usize = 6;
+
+/// Destination address of frames for all nodes.
+pub const MESH_BROADCAST: u16 = 0xFFFF;
+
+/// Mesh errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MeshError {
+    /// A frame is already waiting to be transmitted.
+    Busy,
+    /// The payload does not fit in a frame of `MTU` bytes.
+    PayloadLen,
+    /// The received frame is invalid.
+    Frame,
+    /// The received payload does not fit in the message buffer.
+    BufferTooSmall,
+}
+
+impl From<SubGhzError> for MeshError {
+    fn from(e: SubGhzError) -> Self {
+        match e {}
+    }
+}
+
+/// Mesh events.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MeshEvent {
+    /// A new payload for this node, or for all nodes, was received.
+    ///
+    /// Copies of a frame that was already received are not reported again.
+    Received {
+        /// Address of the originating node.
+        src: u16,
+        /// Destination address, this node or [`MESH_BROADCAST`].
+        dst: u16,
+        /// Payload length.
+        len: usize,
+    },
+    /// The frame with the inner sequence number was not sent because the
+    /// channel was busy for every CAD attempt.
+    ChannelBusy(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct Pending {
+    len: u8,
+    /// Sequence number of frames originated by this node, `None` for relays.
+    own_seq: Option<u8>,
+    not_before: u32,
+    cad_attempts: u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct CacheEntry {
+    src: u16,
+    seq: u8,
+    at: u32,
+}
+
+/// Returns `true` if `now` is at or after `t`, allowing the ticks to wrap.
+const fn reached(now: u32, t: u32) -> bool {
+    now.wrapping_sub(t) < (1 << 31)
+}
+
+/// Managed flooding mesh network layer.
+///
+/// Every node rebroadcasts frames that are not addressed only to itself,
+/// until the hop limit set by the originating node is reached.
+/// Each node remembers the source and sequence number of the last `CACHE`
+/// frames, copies of a frame that are received again from other relays are
+/// dropped.
+///
+/// Rebroadcasts are delayed by a random number of ticks, up to the maximum
+/// delay, to reduce collisions between neighbours relaying the same frame.
+/// Before each transmission channel activity detection (CAD) is used to
+/// check that the channel is free, if it is busy the transmission is delayed
+/// again.
+///
+/// `MTU` is the maximum frame length including the [`MESH_HEADER_LEN`] byte
+/// header, and must not exceed 255.
+/// One frame is queued at a time, relays received while a frame is queued
+/// are dropped.
+///
+/// Timing uses caller defined ticks, see
+/// [protocol layers](crate#protocol-layers).
+/// The random delays use any [`RngCore`] source, for example the RNG
+/// peripheral driver.
+///
+/// [`send`], [`receive`], [`is_tx_ready`], [`channel_busy`], and [`take_tx`]
+/// are the transport interface, [`SubGhz::poll_mesh`] drives them with the
+/// sub-GHz radio LoRa modem.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh, MeshEvent, MESH_BROADCAST};
+/// # struct Rng(u32);
+/// # impl stm32wl_hal_subghz::rand_core::RngCore for Rng {
+/// #     fn next_u32(&mut self) -> u32 { self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223); self.0 }
+/// #     fn next_u64(&mut self) -> u64 { u64::from(self.next_u32()) }
+/// #     fn fill_bytes(&mut self, dest: &mut [u8]) { stm32wl_hal_subghz::rand_core::impls::fill_bytes_via_next(self, dest) }
+/// #     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), stm32wl_hal_subghz::rand_core::Error> { Ok(self.fill_bytes(dest)) }
+/// # }
+/// # let mut rng = Rng(1);
+///
+/// // nodes 1 and 3 are out of range, node 2 relays
+/// let mut node1: Mesh<64, 16> = Mesh::new(1, LoRaPacketParams::new(), 1000);
+/// let mut node2: Mesh<64, 16> = Mesh::new(2, LoRaPacketParams::new(), 1000);
+/// let mut node3: Mesh<64, 16> = Mesh::new(3, LoRaPacketParams::new(), 1000);
+///
+/// let mut frame: [u8; 64] = [0; 64];
+/// let mut msg: [u8; 64] = [0; 64];
+///
+/// node1.send(3, b"hello", 0).unwrap();
+/// assert!(node1.is_tx_ready(0));
+/// // ... CAD found the channel free
+/// let len: usize = node1.take_tx(&mut frame).unwrap();
+///
+/// // not for node 2, rebroadcast after a random delay
+/// assert_eq!(node2.receive(&frame[..len], 0, &mut rng, &mut msg), Ok(None));
+/// assert!(node2.is_busy());
+/// assert!(node2.is_tx_ready(node2.max_delay()));
+/// let len: usize = node2.take_tx(&mut frame).unwrap();
+///
+/// assert_eq!(
+///     node3.receive(&frame[..len], 100, &mut rng, &mut msg),
+///     Ok(Some(MeshEvent::Received { src: 1, dst: 3, len: 5 }))
+/// );
+/// assert_eq!(&msg[..5], b"hello");
+///
+/// // node 1 hears the rebroadcast of its own frame
+/// assert_eq!(node1.receive(&frame[..len], 100, &mut rng, &mut msg), Ok(None));
+/// assert!(!node1.is_busy());
+/// ```
+///
+/// [`send`]: Mesh::send
+/// [`receive`]: Mesh::receive
+/// [`is_tx_ready`]: Mesh::is_tx_ready
+/// [`channel_busy`]: Mesh::channel_busy
+/// [`take_tx`]: Mesh::take_tx
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Mesh<const MTU: usize, const CACHE: usize> {
+    addr: u16,
+    packet_params: LoRaPacketParams,
+    hop_limit: u8,
+    max_delay: u32,
+    cache_timeout: u32,
+    cad_retries: u8,
+    buf: [u8; MTU],
+    pending: Option<Pending>,
+    next_seq: u8,
+    cache: [Option<CacheEntry>; CACHE],
+    cache_next: usize,
+    radio: RadioState,
+    relayed: u32,
+    duplicates: u32,
+    dropped: u32,
+}
+
+#[cfg(feature = "defmt")]
+impl<const MTU: usize, const CACHE: usize> defmt::Format for Mesh<MTU, CACHE> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Mesh {{ addr: {}, pending: {}, relayed: {}, duplicates: {}, dropped: {} }}",
+            self.addr,
+            self.pending.is_some(),
+            self.relayed,
+            self.duplicates,
+            self.dropped
+        )
+    }
+}
+
+impl<const MTU: usize, const CACHE: usize> Mesh<MTU, CACHE> {
+    /// Create a new mesh node.
+    ///
+    /// `addr` is the address of this node, it must be unique in the network
+    /// and must not be [`MESH_BROADCAST`].
+    ///
+    /// `packet_params` are used by [`SubGhz::poll_mesh`], the payload length
+    /// is replaced with the length of each frame.
+    ///
+    /// `tick_hz` is the tick frequency.
+    ///
+    /// The default is a hop limit of 3, a maximum rebroadcast delay of
+    /// 100 ms, a cache timeout of 60 s, and 5 CAD retries.
+    pub const fn new(addr: u16, packet_params: LoRaPacketParams, tick_hz: u32) -> Mesh<MTU, CACHE> {
+        Mesh {
+            addr,
+            packet_params,
+            hop_limit: 3,
+            max_delay: tick_hz / 10,
+            cache_timeout: tick_hz.saturating_mul(60),
+            cad_retries: 5,
+            buf: [0; MTU],
+            pending: None,
+            next_seq: 0,
+            cache: [None; CACHE],
+            cache_next: 0,
+            radio: RadioState::Idle,
+            relayed: 0,
+            duplicates: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Set the hop limit of frames originated by this node.
+    ///
+    /// This is the number of times a frame is rebroadcast, a hop limit of 0
+    /// only reaches the direct neighbours.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh};
+    ///
+    /// const MESH: Mesh<64, 16> = Mesh::new(1, LoRaPacketParams::new(), 1000).set_hop_limit(5);
+    /// # assert_eq!(MESH.hop_limit(), 5);
+    /// ```
+    #[must_use = "set_hop_limit returns a modified Mesh"]
+    pub const fn set_hop_limit(mut self, hop_limit: u8) -> Mesh<MTU, CACHE> {
+        self.hop_limit = hop_limit;
+        self
+    }
+
+    /// Hop limit of frames originated by this node.
+    pub const fn hop_limit(&self) -> u8 {
+        self.hop_limit
+    }
+
+    /// Set the maximum rebroadcast delay in ticks.
+    ///
+    /// This should be a few times the time on air of a `MTU` byte frame, the
+    /// delay is also used to back off when CAD finds the channel busy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh};
+    ///
+    /// // 1 kHz tick, up to 500 ms
+    /// const MESH: Mesh<64, 16> = Mesh::new(1, LoRaPacketParams::new(), 1000).set_max_delay(500);
+    /// # assert_eq!(MESH.max_delay(), 500);
+    /// ```
+    #[must_use = "set_max_delay returns a modified Mesh"]
+    pub const fn set_max_delay(mut self, ticks: u32) -> Mesh<MTU, CACHE> {
+        self.max_delay = ticks;
+        self
+    }
+
+    /// Maximum rebroadcast delay in ticks.
+    pub const fn max_delay(&self) -> u32 {
+        self.max_delay
+    }
+
+    /// Set the time in ticks a received frame is remembered for duplicate
+    /// suppression.
+    ///
+    /// This must be longer than a frame takes to cross the network, and
+    /// shorter than a node takes to send 256 frames, after which the sequence
+    /// numbers repeat.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh};
+    ///
+    /// const MESH: Mesh<64, 16> =
+    ///     Mesh::new(1, LoRaPacketParams::new(), 1000).set_cache_timeout(10_000);
+    /// # assert_eq!(MESH.cache_timeout(), 10_000);
+    /// ```
+    #[must_use = "set_cache_timeout returns a modified Mesh"]
+    pub const fn set_cache_timeout(mut self, ticks: u32) -> Mesh<MTU, CACHE> {
+        self.cache_timeout = ticks;
+        self
+    }
+
+    /// Duplicate suppression timeout in ticks.
+    pub const fn cache_timeout(&self) -> u32 {
+        self.cache_timeout
+    }
+
+    /// Set the number of times a transmission is delayed when CAD finds the
+    /// channel busy before the frame is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh};
+    ///
+    /// const MESH: Mesh<64, 16> = Mesh::new(1, LoRaPacketParams::new(), 1000).set_cad_retries(10);
+    /// # assert_eq!(MESH.cad_retries(), 10);
+    /// ```
+    #[must_use = "set_cad_retries returns a modified Mesh"]
+    pub const fn set_cad_retries(mut self, retries: u8) -> Mesh<MTU, CACHE> {
+        self.cad_retries = retries;
+        self
+    }
+
+    /// Number of CAD retries.
+    pub const fn cad_retries(&self) -> u8 {
+        self.cad_retries
+    }
+
+    /// Address of this node.
+    pub const fn addr(&self) -> u16 {
+        self.addr
+    }
+
+    /// Returns `true` if a frame is waiting to be transmitted.
+    pub const fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Number of frames from other nodes that were rebroadcast.
+    pub const fn relayed(&self) -> u32 {
+        self.relayed
+    }
+
+    /// Number of received copies of frames that were already received.
+    pub const fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    /// Number of rebroadcasts that were dropped because a frame was already
+    /// queued, or the channel was busy.
+    pub const fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Queue a payload for `dst`, or for all nodes with [`MESH_BROADCAST`].
+    ///
+    /// The frame is transmitted as soon as the channel is free.
+    /// `now` is the current time in ticks.
+    ///
+    /// Returns the sequence number of the frame.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaPacketParams, Mesh, MeshError, MESH_BROADCAST};
+    ///
+    /// let mut mesh: Mesh<16, 8> = Mesh::new(1, LoRaPacketParams::new(), 1000);
+    ///
+    /// assert_eq!(mesh.send(MESH_BROADCAST, &[0; 11], 0), Err(MeshError::PayloadLen));
+    /// assert_eq!(mesh.send(MESH_BROADCAST, &[0; 10], 0), Ok(0));
+    /// assert_eq!(mesh.send(MESH_BROADCAST, &[0; 10], 0), Err(MeshError::Busy));
+    /// ```
+    pub fn send(&mut self, dst: u16, payload: &[u8], now: u32) -> Result<u8, MeshError> {
+        if self.pending.is_some() {
+            return Err(MeshError::Busy);
+        }
+        let len: usize = MESH_HEADER_LEN + payload.len();
+        if len > usize::from(mtu_len(MTU)) {
+            return Err(MeshError::PayloadLen);
+        }
+        let seq: u8 = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        self.buf[0..2].copy_from_slice(&self.addr.to_le_bytes());
+        self.buf[2..4].copy_from_slice(&dst.to_le_bytes());
+        self.buf[4] = seq;
+        self.buf[5] = self.hop_limit;
+        self.buf[MESH_HEADER_LEN..len].copy_from_slice(payload);
+        self.cache_insert(self.addr, seq, now);
+        self.pending = Some(Pending {
+            len: len as u8,
+            own_seq: Some(seq),
+            not_before: now,
+            cad_attempts: 0,
+        });
+        Ok(seq)
+    }
+
+    /// Process a received frame.
+    ///
+    /// `now` is the current time in ticks, `rng` is used for the rebroadcast
+    /// delay.
+    ///
+    /// Payloads for this node, or for all nodes, are copied into `msg`.
+    /// New frames that are not only for this node, and have hops remaining,
+    /// are queued for rebroadcast.
+    pub fn receive<R: RngCore>(
+        &mut self,
+        frame: &[u8],
+        now: u32,
+        rng: &mut R,
+        msg: &mut [u8],
+    ) -> Result<Option<MeshEvent>, MeshError> {
+        if frame.len() < MESH_HEADER_LEN || frame.len() > MTU {
+            return Err(MeshError::Frame);
+        }
+        let src: u16 = u16::from_le_bytes([frame[0], frame[1]]);
+        let dst: u16 = u16::from_le_bytes([frame[2], frame[3]]);
+        let seq: u8 = frame[4];
+        let hops: u8 = frame[5];
+        if src == MESH_BROADCAST {
+            return Err(MeshError::Frame);
+        }
+
+        if src == self.addr || self.cache_contains(src, seq, now) {
+            self.duplicates = self.duplicates.saturating_add(1);
+            return Ok(None);
+        }
+        self.cache_insert(src, seq, now);
+
+        if dst != self.addr && hops > 0 {
+            if self.pending.is_some() {
+                self.dropped = self.dropped.saturating_add(1);
+            } else {
+                self.buf[..frame.len()].copy_from_slice(frame);
+                self.buf[5] = hops - 1;
+                self.pending = Some(Pending {
+                    len: frame.len() as u8,
+                    own_seq: None,
+                    not_before: now.wrapping_add(self.random_delay(rng)),
+                    cad_attempts: 0,
+                });
+            }
+        }
+
+        if dst == self.addr || dst == MESH_BROADCAST {
+            let payload: &[u8] = &frame[MESH_HEADER_LEN..];
+            let len: usize = payload.len();
+            if msg.len() < len {
+                return Err(MeshError::BufferTooSmall);
+            }
+            msg[..len].copy_from_slice(payload);
+            Ok(Some(MeshEvent::Received { src, dst, len }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns `true` if a frame is waiting to be transmitted, and its delay
+    /// has elapsed at `now`.
+    ///
+    /// CAD should be performed next, followed by [`take_tx`] if the channel is
+    /// free, or [`channel_busy`] if it is not.
+    ///
+    /// [`take_tx`]: Mesh::take_tx
+    /// [`channel_busy`]: Mesh::channel_busy
+    pub fn is_tx_ready(&self, now: u32) -> bool {
+        match self.pending {
+            Some(pending) => reached(now, pending.not_before),
+            None => false,
+        }
+    }
+
+    /// CAD found the channel busy.
+    ///
+    /// The frame is delayed by a random number of ticks, or dropped after the
+    /// CAD retries are exhausted.
+    /// Returns [`MeshEvent::ChannelBusy`] if a frame originated by this node
+    /// was dropped.
+    pub fn channel_busy<R: RngCore>(&mut self, now: u32, rng: &mut R) -> Option<MeshEvent> {
+        let mut pending: Pending = self.pending?;
+        if pending.cad_attempts >= self.cad_retries {
+            self.pending = None;
+            match pending.own_seq {
+                Some(seq) => Some(MeshEvent::ChannelBusy(seq)),
+                None => {
+                    self.dropped = self.dropped.saturating_add(1);
+                    None
+                }
+            }
+        } else {
+            pending.cad_attempts += 1;
+            pending.not_before = now.wrapping_add(1).wrapping_add(self.random_delay(rng));
+            self.pending = Some(pending);
+            None
+        }
+    }
+
+    /// Write the queued frame to `buf` for transmission, returns the frame
+    /// length, or `None` if there is no frame queued.
+    pub fn take_tx(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let pending: Pending = self.pending?;
+        let len: usize = usize::from(pending.len);
+        if buf.len() < len {
+            return None;
+        }
+        buf[..len].copy_from_slice(&self.buf[..len]);
+        if pending.own_seq.is_none() {
+            self.relayed = self.relayed.saturating_add(1);
+        }
+        self.pending = None;
+        Some(len)
+    }
+
+    fn random_delay<R: RngCore>(&self, rng: &mut R) -> u32 {
+        if self.max_delay == 0 {
+            0
+        } else {
+            rng.next_u32() % self.max_delay
+        }
+    }
+
+    fn cache_contains(&self, src: u16, seq: u8, now: u32) -> bool {
+        self.cache.iter().flatten().any(|entry| {
+            entry.src == src && entry.seq == seq && now.wrapping_sub(entry.at) < self.cache_timeout
+        })
+    }
+
+    fn cache_insert(&mut self, src: u16, seq: u8, at: u32) {
+        if CACHE == 0 {
+            return;
+        }
+        self.cache[self.cache_next] = Some(CacheEntry { src, seq, at });
+        self.cache_next = (self.cache_next + 1) % CACHE;
+    }
+}
+
+/// Mesh network.
+impl SubGhz {
+    /// Drive a [`Mesh`] node with the radio.
+    ///
+    /// The LoRa packet type, modulation parameters, and CAD parameters with
+    /// [`ExitMode::Standby`] must be configured before calling this function,
+    /// the packet parameters are written for each frame.
+    ///
+    /// The [`Irq::TxDone`], [`Irq::RxDone`], [`Irq::Timeout`],
+    /// [`Irq::HeaderValid`], [`Irq::CadDone`], and [`Irq::CadDetected`]
+    /// interrupts must be enabled.
+    /// Call this when an interrupt is asserted, and periodically to handle
+    /// the rebroadcast delays.
+    /// `now` is the current time in ticks, `rng` is used for the random
+    /// delays.
+    ///
+    /// The radio is in RX mode with [`Timeout::MAX`] when it is not
+    /// performing CAD or transmitting, received payloads are copied into
+    /// `msg`.
+    /// A queued frame is not started while a frame is being received.
+    ///
+    /// # Errors
+    ///
+    /// * [`MeshError::BufferTooSmall`] the received payload does not fit in
+    ///   `msg`, the frame is still relayed, and reception restarts on the
+    ///   next call.
+    ///
+    /// Invalid frames are ignored, they are noise or from another network.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// # fn ticks() -> u32 { 0 }
+    /// # struct Rng;
+    /// # impl stm32wl_hal_subghz::rand_core::RngCore for Rng {
+    /// #     fn next_u32(&mut self) -> u32 { 0 }
+    /// #     fn next_u64(&mut self) -> u64 { 0 }
+    /// #     fn fill_bytes(&mut self, _: &mut [u8]) {}
+    /// #     fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), stm32wl_hal_subghz::rand_core::Error> { Ok(()) }
+    /// # }
+    /// # let mut rng = Rng;
+    /// use stm32wl_hal_subghz::{
+    ///     CadParams, CodingRate, ExitMode, HeaderType, LoRaBandwidth, LoRaModParams,
+    ///     LoRaPacketParams, Mesh, MeshEvent, ModemCfg, NbCadSymbol, SpreadingFactor,
+    ///     MESH_BROADCAST,
+    /// };
+    ///
+    /// const PACKET_PARAMS: LoRaPacketParams = LoRaPacketParams::new()
+    ///     .set_preamble_len(8)
+    ///     .set_header_type(HeaderType::Variable)
+    ///     .set_crc_en(true);
+    /// const MODEM_CFG: ModemCfg = ModemCfg::LoRa {
+    ///     mod_params: LoRaModParams::new()
+    ///         .set_sf(SpreadingFactor::Sf7)
+    ///         .set_bw(LoRaBandwidth::Bw125)
+    ///         .set_cr(CodingRate::Cr45),
+    ///     packet_params: PACKET_PARAMS,
+    /// };
+    /// const CAD_PARAMS: CadParams = CadParams::new()
+    ///     .set_num_symbol(NbCadSymbol::S2)
+    ///     .set_det_peak(0x18)
+    ///     .set_det_min(0x10)
+    ///     .set_exit_mode(ExitMode::Standby);
+    ///
+    /// sg.set_modem_cfg(&MODEM_CFG)?.unwrap();
+    /// sg.set_cad_params(&CAD_PARAMS)?;
+    /// let mut node: Mesh<128, 32> = Mesh::new(0x0042, PACKET_PARAMS, 1000);
+    /// let mut msg: [u8; 128] = [0; 128];
+    ///
+    /// node.send(MESH_BROADCAST, b"reading: 21.5C", ticks()).unwrap();
+    /// loop {
+    ///     match sg.poll_mesh(&mut node, ticks(), &mut rng, &mut msg)? {
+    ///         Some(MeshEvent::Received { src, dst, len }) => { /* ... use msg[..len] */ }
+    ///         Some(MeshEvent::ChannelBusy(seq)) => { /* not sent */ }
+    ///         None => (),
+    ///     }
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::MeshError>(())
+    /// ```
+    ///
+    /// [`ExitMode::Standby`]: crate::ExitMode::Standby
+    pub fn poll_mesh<R: RngCore, const MTU: usize, const CACHE: usize>(
+        &mut self,
+        mesh: &mut Mesh<MTU, CACHE>,
+        now: u32,
+        rng: &mut R,
+        msg: &mut [u8],
+    ) -> Result<Option<MeshEvent>, MeshError> {
+        const IRQ_RX_DONE: u16 = Irq::RxDone.mask() | Irq::Timeout.mask();
+        const IRQ_TX_DONE: u16 = Irq::TxDone.mask() | Irq::Timeout.mask();
+
+        let mut frame: [u8; 255] = [0; 255];
+        let mut event: Option<MeshEvent> = None;
+
+        let (_, irq) = self.irq_status()?;
+        match mesh.radio {
+            RadioState::Rx if irq & IRQ_RX_DONE != 0 => {
+                let mut result: Result<Option<MeshEvent>, MeshError> = Ok(None);
+                if irq & Irq::RxDone.mask() != 0
+                    && irq & (Irq::Err.mask() | Irq::HeaderErr.mask()) == 0
+                {
+                    let (_, len, ptr) = self.rx_buffer_status()?;
+                    let frame: &mut [u8] = &mut frame[..usize::from(len)];
+                    self.read_buffer(ptr, frame)?;
+                    result = mesh.receive(frame, now, rng, msg);
+                }
+                self.clear_irq_status(IRQ_ALL)?;
+                mesh.radio = RadioState::Idle;
+                event = match result {
+                    Ok(event) => event,
+                    Err(MeshError::Frame) => None,
+                    Err(e) => return Err(e),
+                };
+            }
+            RadioState::Cad if irq & Irq::CadDone.mask() != 0 => {
+                self.clear_irq_status(IRQ_ALL)?;
+                mesh.radio = RadioState::Idle;
+                if irq & Irq::CadDetected.mask() != 0 {
+                    event = mesh.channel_busy(now, rng);
+                } else if let Some(len) = mesh.take_tx(&mut frame) {
+                    self.set_buffer_base_address(0, 0)?;
+                    self.write_buffer(0, &frame[..len])?;
+                    self.set_lora_packet_params(&mesh.packet_params.set_payload_len(len as u8))?;
+                    self.set_tx(Timeout::DISABLED)?;
+                    mesh.radio = RadioState::Tx;
+                }
+            }
+            RadioState::Tx if irq & IRQ_TX_DONE != 0 => {
+                self.clear_irq_status(IRQ_ALL)?;
+                mesh.radio = RadioState::Idle;
+            }
+            _ => (),
+        }
+
+        let receiving: bool = mesh.radio == RadioState::Rx && irq & Irq::HeaderValid.mask() != 0;
+        if matches!(mesh.radio, RadioState::Idle | RadioState::Rx)
+            && !receiving
+            && mesh.is_tx_ready(now)
+        {
+            self.set_standby(StandbyClk::Rc)?;
+            self.clear_irq_status(IRQ_ALL)?;
+            self.set_cad()?;
+            mesh.radio = RadioState::Cad;
+        } else if mesh.radio == RadioState::Idle {
+            self.set_buffer_base_address(0, 0)?;
+            self.set_lora_packet_params(&mesh.packet_params.set_payload_len(mtu_len(MTU)))?;
+            self.set_rx(Timeout::MAX)?;
+            mesh.radio = RadioState::Rx;
+        }
+
+        Ok(event)
+    }
+}
@@ -1,5 +1,8 @@
+use crate::ValueError;
+
 /// Bandwidth options for [`FskModParams`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FskBandwidth {
     /// 4.8 kHz DSB
     Bw4 = 0x1F,
@@ -75,7 +78,7 @@ impl FskBandwidth {
     /// assert_eq!(FskBandwidth::Bw373.hertz(), 373_600);
     /// assert_eq!(FskBandwidth::Bw467.hertz(), 467_000);
     /// ```
-    pub fn hertz(&self) -> u32 {
+    pub const fn hertz(&self) -> u32 {
         match self {
             FskBandwidth::Bw4 => 4_800,
             FskBandwidth::Bw5 => 5_800,
@@ -100,6 +103,80 @@ impl FskBandwidth {
             FskBandwidth::Bw467 => 467_000,
         }
     }
+
+    /// Convert from a raw bit value.
+    ///
+    /// Invalid values will be returned in the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::FskBandwidth;
+    ///
+    /// assert_eq!(FskBandwidth::from_raw(0x1F), Ok(FskBandwidth::Bw4));
+    /// assert_eq!(FskBandwidth::from_raw(0x09), Ok(FskBandwidth::Bw467));
+    /// assert_eq!(FskBandwidth::from_raw(0x00), Err(0x00));
+    /// ```
+    pub const fn from_raw(bits: u8) -> Result<FskBandwidth, u8> {
+        let mut idx: usize = 0;
+        while idx < Self::ASCENDING.len() {
+            if Self::ASCENDING[idx] as u8 == bits {
+                return Ok(Self::ASCENDING[idx]);
+            }
+            idx += 1;
+        }
+        Err(bits)
+    }
+
+    /// Get the smallest bandwidth that is greater than or equal to `hz`.
+    ///
+    /// Returns `None` if `hz` is greater than the widest bandwidth.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::FskBandwidth;
+    ///
+    /// assert_eq!(FskBandwidth::smallest_for(0), Some(FskBandwidth::Bw4));
+    /// assert_eq!(FskBandwidth::smallest_for(50_000), Some(FskBandwidth::Bw58));
+    /// assert_eq!(FskBandwidth::smallest_for(58_600), Some(FskBandwidth::Bw58));
+    /// assert_eq!(FskBandwidth::smallest_for(58_601), Some(FskBandwidth::Bw78));
+    /// assert_eq!(FskBandwidth::smallest_for(467_001), None);
+    /// ```
+    pub const fn smallest_for(hz: u32) -> Option<FskBandwidth> {
+        let mut idx: usize = 0;
+        while idx < Self::ASCENDING.len() {
+            if Self::ASCENDING[idx].hertz() >= hz {
+                return Some(Self::ASCENDING[idx]);
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    const ASCENDING: [FskBandwidth; 21] = [
+        FskBandwidth::Bw4,
+        FskBandwidth::Bw5,
+        FskBandwidth::Bw7,
+        FskBandwidth::Bw9,
+        FskBandwidth::Bw11,
+        FskBandwidth::Bw14,
+        FskBandwidth::Bw19,
+        FskBandwidth::Bw23,
+        FskBandwidth::Bw29,
+        FskBandwidth::Bw39,
+        FskBandwidth::Bw46,
+        FskBandwidth::Bw58,
+        FskBandwidth::Bw78,
+        FskBandwidth::Bw93,
+        FskBandwidth::Bw117,
+        FskBandwidth::Bw156,
+        FskBandwidth::Bw187,
+        FskBandwidth::Bw234,
+        FskBandwidth::Bw312,
+        FskBandwidth::Bw373,
+        FskBandwidth::Bw467,
+    ];
 }
 
 impl Ord for FskBandwidth {
@@ -116,6 +193,7 @@ impl PartialOrd for FskBandwidth {
 
 /// Pulse shaping options for [`FskModParams`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FskPulseShape {
     /// No filtering applied.
     None = 0b00,
@@ -132,6 +210,7 @@ pub enum FskPulseShape {
 /// Bitrate argument for [`FskModParams::set_bitrate`] and
 /// [`BpskModParams::set_bitrate`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FskBitrate {
     bits: u32,
 }
@@ -223,6 +302,7 @@ impl PartialOrd for FskBitrate {
 
 /// Frequency deviation argument for [`FskModParams::set_fdev`]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FskFdev {
     bits: u32,
 }
@@ -286,8 +366,31 @@ impl FskFdev {
     }
 }
 
+/// Error for an invalid (G)FSK modulation configuration.
+///
+/// Returned by [`FskModParams::validate`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FskModParamsError {
+    /// Bitrate in bits per second is outside of the radio limits.
+    Bitrate(ValueError<u32>),
+    /// Frequency deviation in hertz is outside of the radio limits.
+    Fdev(ValueError<u32>),
+    /// Bandwidth is narrower than the bandwidth required by Carson's rule.
+    Bandwidth {
+        /// Required bandwidth in hertz, 2 × fdev + bitrate.
+        required: u32,
+        /// Smallest bandwidth that satisfies Carson's rule, `None` if the
+        /// required bandwidth exceeds the widest bandwidth.
+        suggested: Option<FskBandwidth>,
+    },
+    /// Bandwidth bits do not match a bandwidth.
+    UnknownBandwidth(u8),
+}
+
 /// (G)FSK modulation paramters.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FskModParams {
     buf: [u8; 9],
 }
@@ -402,6 +505,170 @@ impl FskModParams {
         self
     }
 
+    /// Get the bitrate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBitrate, FskModParams};
+    ///
+    /// const BITRATE: FskBitrate = FskBitrate::from_bps(32_000);
+    /// const MOD_PARAMS: FskModParams = FskModParams::new().set_bitrate(BITRATE);
+    /// assert_eq!(MOD_PARAMS.bitrate(), BITRATE);
+    /// ```
+    pub const fn bitrate(&self) -> FskBitrate {
+        FskBitrate::from_raw(
+            (self.buf[1] as u32) << 16 | (self.buf[2] as u32) << 8 | (self.buf[3] as u32),
+        )
+    }
+
+    /// Get the bandwidth.
+    ///
+    /// Returns the raw bits in the error if they do not match a bandwidth.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBandwidth, FskModParams};
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new().set_bandwidth(FskBandwidth::Bw9);
+    /// assert_eq!(MOD_PARAMS.bandwidth(), Ok(FskBandwidth::Bw9));
+    /// ```
+    pub const fn bandwidth(&self) -> Result<FskBandwidth, u8> {
+        FskBandwidth::from_raw(self.buf[5])
+    }
+
+    /// Get the frequency deviation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskFdev, FskModParams};
+    ///
+    /// const FDEV: FskFdev = FskFdev::from_hertz(31_250);
+    /// const MOD_PARAMS: FskModParams = FskModParams::new().set_fdev(FDEV);
+    /// assert_eq!(MOD_PARAMS.fdev(), FDEV);
+    /// ```
+    pub const fn fdev(&self) -> FskFdev {
+        FskFdev::from_raw(
+            (self.buf[6] as u32) << 16 | (self.buf[7] as u32) << 8 | (self.buf[8] as u32),
+        )
+    }
+
+    /// Minimum bandwidth in hertz required by Carson's rule,
+    /// 2 × fdev + bitrate.
+    ///
+    /// This does not include margin for crystal frequency error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBitrate, FskFdev, FskModParams};
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new()
+    ///     .set_bitrate(FskBitrate::from_bps(20_000))
+    ///     .set_fdev(FskFdev::from_hertz(31_250));
+    /// assert_eq!(MOD_PARAMS.carson_bandwidth(), 82_500);
+    /// ```
+    pub const fn carson_bandwidth(&self) -> u32 {
+        2 * self.fdev().as_hertz() + self.bitrate().as_bps()
+    }
+
+    /// Smallest bandwidth that satisfies Carson's rule.
+    ///
+    /// Returns `None` if the required bandwidth exceeds the widest bandwidth.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBandwidth, FskBitrate, FskFdev, FskModParams};
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new()
+    ///     .set_bitrate(FskBitrate::from_bps(20_000))
+    ///     .set_fdev(FskFdev::from_hertz(31_250));
+    /// assert_eq!(MOD_PARAMS.suggested_bandwidth(), Some(FskBandwidth::Bw93));
+    /// ```
+    pub const fn suggested_bandwidth(&self) -> Option<FskBandwidth> {
+        FskBandwidth::smallest_for(self.carson_bandwidth())
+    }
+
+    /// Validate the modulation parameters.
+    ///
+    /// This checks:
+    ///
+    /// * The bitrate is between 600 b/s and 300 kb/s.
+    /// * The frequency deviation is between 600 Hz and 200 kHz.
+    /// * The bandwidth is greater than or equal to 2 × fdev + bitrate
+    ///   (Carson's rule).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{
+    ///     FskBandwidth, FskBitrate, FskFdev, FskModParams, FskModParamsError,
+    /// };
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new()
+    ///     .set_bitrate(FskBitrate::from_bps(20_000))
+    ///     .set_fdev(FskFdev::from_hertz(31_250))
+    ///     .set_bandwidth(FskBandwidth::Bw78);
+    ///
+    /// assert_eq!(
+    ///     MOD_PARAMS.validate(),
+    ///     Err(FskModParamsError::Bandwidth {
+    ///         required: 82_500,
+    ///         suggested: Some(FskBandwidth::Bw93),
+    ///     })
+    /// );
+    /// assert_eq!(
+    ///     MOD_PARAMS.set_bandwidth(FskBandwidth::Bw93).validate(),
+    ///     Ok(())
+    /// );
+    /// ```
+    pub const fn validate(&self) -> Result<(), FskModParamsError> {
+        const BITRATE_MIN: u32 = 600;
+        const BITRATE_MAX: u32 = 300_000;
+        const FDEV_MIN: u32 = 600;
+        const FDEV_MAX: u32 = 200_000;
+
+        let bitrate: u32 = self.bitrate().as_bps();
+        if bitrate < BITRATE_MIN {
+            return Err(FskModParamsError::Bitrate(ValueError::too_low(
+                bitrate,
+                BITRATE_MIN,
+            )));
+        } else if bitrate > BITRATE_MAX {
+            return Err(FskModParamsError::Bitrate(ValueError::too_high(
+                bitrate,
+                BITRATE_MAX,
+            )));
+        }
+
+        let fdev: u32 = self.fdev().as_hertz();
+        if fdev < FDEV_MIN {
+            return Err(FskModParamsError::Fdev(ValueError::too_low(fdev, FDEV_MIN)));
+        } else if fdev > FDEV_MAX {
+            return Err(FskModParamsError::Fdev(ValueError::too_high(
+                fdev, FDEV_MAX,
+            )));
+        }
+
+        let bw: FskBandwidth = match self.bandwidth() {
+            Ok(bw) => bw,
+            Err(bits) => return Err(FskModParamsError::UnknownBandwidth(bits)),
+        };
+
+        let required: u32 = self.carson_bandwidth();
+        if bw.hertz() < required {
+            Err(FskModParamsError::Bandwidth {
+                required,
+                suggested: FskBandwidth::smallest_for(required),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
@@ -440,6 +707,7 @@ impl Default for FskModParams {
 ///
 /// Argument of [`LoRaModParams::set_sf`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum SpreadingFactor {
     /// Spreading factor 5.
@@ -460,6 +728,46 @@ pub enum SpreadingFactor {
     Sf12 = 0xC0,
 }
 
+impl SpreadingFactor {
+    /// Create a new `SpreadingFactor` from bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SpreadingFactor;
+    ///
+    /// assert_eq!(SpreadingFactor::from_raw(0x07), Ok(SpreadingFactor::Sf7));
+    /// assert_eq!(SpreadingFactor::from_raw(0x04), Err(0x04));
+    /// ```
+    pub const fn from_raw(bits: u8) -> Result<SpreadingFactor, u8> {
+        match bits {
+            0x05 => Ok(SpreadingFactor::Sf5),
+            0x06 => Ok(SpreadingFactor::Sf6),
+            0x07 => Ok(SpreadingFactor::Sf7),
+            0x08 => Ok(SpreadingFactor::Sf8),
+            0x09 => Ok(SpreadingFactor::Sf9),
+            0xA0 => Ok(SpreadingFactor::Sf10),
+            0xB0 => Ok(SpreadingFactor::Sf11),
+            0xC0 => Ok(SpreadingFactor::Sf12),
+            x => Err(x),
+        }
+    }
+
+    /// Spreading factor as a number, a LoRa symbol has 2<sup>SF</sup> chips.
+    pub(crate) const fn factor(self) -> u32 {
+        match self {
+            SpreadingFactor::Sf5 => 5,
+            SpreadingFactor::Sf6 => 6,
+            SpreadingFactor::Sf7 => 7,
+            SpreadingFactor::Sf8 => 8,
+            SpreadingFactor::Sf9 => 9,
+            SpreadingFactor::Sf10 => 10,
+            SpreadingFactor::Sf11 => 11,
+            SpreadingFactor::Sf12 => 12,
+        }
+    }
+}
+
 impl From<SpreadingFactor> for u8 {
     fn from(sf: SpreadingFactor) -> Self {
         sf as u8
@@ -470,6 +778,7 @@ impl From<SpreadingFactor> for u8 {
 ///
 /// Argument of [`LoRaModParams::set_bw`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum LoRaBandwidth {
     /// 7.81 kHz
@@ -495,6 +804,32 @@ pub enum LoRaBandwidth {
 }
 
 impl LoRaBandwidth {
+    /// Create a new `LoRaBandwidth` from bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::LoRaBandwidth;
+    ///
+    /// assert_eq!(LoRaBandwidth::from_raw(0x04), Ok(LoRaBandwidth::Bw125));
+    /// assert_eq!(LoRaBandwidth::from_raw(0x07), Err(0x07));
+    /// ```
+    pub const fn from_raw(bits: u8) -> Result<LoRaBandwidth, u8> {
+        match bits {
+            0x00 => Ok(LoRaBandwidth::Bw7),
+            0x08 => Ok(LoRaBandwidth::Bw10),
+            0x01 => Ok(LoRaBandwidth::Bw15),
+            0x09 => Ok(LoRaBandwidth::Bw20),
+            0x02 => Ok(LoRaBandwidth::Bw31),
+            0x0A => Ok(LoRaBandwidth::Bw41),
+            0x03 => Ok(LoRaBandwidth::Bw62),
+            0x04 => Ok(LoRaBandwidth::Bw125),
+            0x05 => Ok(LoRaBandwidth::Bw250),
+            0x06 => Ok(LoRaBandwidth::Bw500),
+            x => Err(x),
+        }
+    }
+
     /// Get the bandwidth in hertz.
     ///
     /// # Example
@@ -545,6 +880,7 @@ impl PartialOrd for LoRaBandwidth {
 ///
 /// Argument of [`LoRaModParams::set_cr`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum CodingRate {
     /// No forward error correction coding rate 4/4
@@ -561,6 +897,7 @@ pub enum CodingRate {
 
 /// LoRa modulation paramters.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LoRaModParams {
     buf: [u8; 5],
 }
@@ -606,6 +943,20 @@ impl LoRaModParams {
         self
     }
 
+    /// Get the spreading factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaModParams, SpreadingFactor};
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new().set_sf(SpreadingFactor::Sf11);
+    /// assert_eq!(MOD_PARAMS.sf(), Ok(SpreadingFactor::Sf11));
+    /// ```
+    pub const fn sf(&self) -> Result<SpreadingFactor, u8> {
+        SpreadingFactor::from_raw(self.buf[1])
+    }
+
     /// Set the bandwidth.
     ///
     /// # Example
@@ -622,6 +973,20 @@ impl LoRaModParams {
         self
     }
 
+    /// Get the bandwidth.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaBandwidth, LoRaModParams};
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new().set_bw(LoRaBandwidth::Bw250);
+    /// assert_eq!(MOD_PARAMS.bw(), Ok(LoRaBandwidth::Bw250));
+    /// ```
+    pub const fn bw(&self) -> Result<LoRaBandwidth, u8> {
+        LoRaBandwidth::from_raw(self.buf[2])
+    }
+
     /// Set the forward error correction coding rate.
     ///
     /// # Example
@@ -654,6 +1019,16 @@ impl LoRaModParams {
         self
     }
 
+    /// Forward error correction coding rate as the `x` in 4/`x`.
+    pub(crate) const fn cr_denominator(&self) -> u32 {
+        4 + (self.buf[3] as u32)
+    }
+
+    /// Low data rate optimization enable.
+    pub(crate) const fn ldro_en(&self) -> bool {
+        self.buf[4] != 0
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
@@ -685,6 +1060,7 @@ impl Default for LoRaModParams {
 /// **Note:** There is no method to set the pulse shape because there is only
 /// one valid pulse shape (Gaussian BT 0.5).
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct BpskModParams {
     buf: [u8; 5],
 }
@@ -733,6 +1109,13 @@ impl BpskModParams {
         self
     }
 
+    /// Bitrate.
+    pub(crate) const fn bitrate(&self) -> FskBitrate {
+        FskBitrate::from_raw(
+            ((self.buf[1] as u32) << 16) | ((self.buf[2] as u32) << 8) | (self.buf[3] as u32),
+        )
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
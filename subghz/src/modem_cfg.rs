@@ -0,0 +1,271 @@
+use crate::{
+    BpskModParams, BpskPacketParams, FskModParams, GenericPacketParams, LoRaModParams,
+    LoRaPacketParams, PacketType,
+};
+use core::time::Duration;
+
+/// Modem configuration.
+///
+/// Bundles the modulation and packet parameters with the matching packet
+/// type so they can not be applied to the wrong modem.
+///
+/// Argument of [`set_modem_cfg`].
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{
+///     CodingRate, HeaderType, LoRaBandwidth, LoRaModParams, LoRaPacketParams, ModemCfg,
+///     PacketType, SpreadingFactor,
+/// };
+///
+/// const MODEM_CFG: ModemCfg = ModemCfg::LoRa {
+///     mod_params: LoRaModParams::new()
+///         .set_sf(SpreadingFactor::Sf7)
+///         .set_bw(LoRaBandwidth::Bw125)
+///         .set_cr(CodingRate::Cr45),
+///     packet_params: LoRaPacketParams::new()
+///         .set_preamble_len(8)
+///         .set_header_type(HeaderType::Variable)
+///         .set_payload_len(64)
+///         .set_crc_en(true),
+/// };
+///
+/// assert_eq!(MODEM_CFG.packet_type(), PacketType::LoRa);
+/// ```
+///
+/// [`set_modem_cfg`]: crate::SubGhz::set_modem_cfg
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ModemCfg {
+    /// LoRa modem.
+    LoRa {
+        /// Modulation parameters.
+        mod_params: LoRaModParams,
+        /// Packet parameters.
+        packet_params: LoRaPacketParams,
+    },
+    /// (G)FSK modem.
+    Fsk {
+        /// Modulation parameters.
+        mod_params: FskModParams,
+        /// Packet parameters.
+        packet_params: GenericPacketParams,
+    },
+    /// BPSK modem.
+    Bpsk {
+        /// Modulation parameters.
+        mod_params: BpskModParams,
+        /// Packet parameters.
+        packet_params: BpskPacketParams,
+    },
+}
+
+impl ModemCfg {
+    /// Packet type of the modem configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{BpskModParams, BpskPacketParams, ModemCfg, PacketType};
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::Bpsk {
+    ///     mod_params: BpskModParams::new(),
+    ///     packet_params: BpskPacketParams::new(),
+    /// };
+    ///
+    /// assert_eq!(MODEM_CFG.packet_type(), PacketType::Bpsk);
+    /// ```
+    pub const fn packet_type(&self) -> PacketType {
+        match self {
+            ModemCfg::LoRa { .. } => PacketType::LoRa,
+            ModemCfg::Fsk { .. } => PacketType::Fsk,
+            ModemCfg::Bpsk { .. } => PacketType::Bpsk,
+        }
+    }
+
+    /// Set the payload length in the packet parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{BpskModParams, BpskPacketParams, ModemCfg};
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::Bpsk {
+    ///     mod_params: BpskModParams::new(),
+    ///     packet_params: BpskPacketParams::new(),
+    /// }
+    /// .set_payload_len(12);
+    ///
+    /// # assert_eq!(
+    /// #     MODEM_CFG,
+    /// #     ModemCfg::Bpsk {
+    /// #         mod_params: BpskModParams::new(),
+    /// #         packet_params: BpskPacketParams::new().set_payload_len(12),
+    /// #     }
+    /// # );
+    /// ```
+    #[must_use = "set_payload_len returns a modified ModemCfg"]
+    pub const fn set_payload_len(self, len: u8) -> ModemCfg {
+        match self {
+            ModemCfg::LoRa {
+                mod_params,
+                packet_params,
+            } => ModemCfg::LoRa {
+                mod_params,
+                packet_params: packet_params.set_payload_len(len),
+            },
+            ModemCfg::Fsk {
+                mod_params,
+                packet_params,
+            } => ModemCfg::Fsk {
+                mod_params,
+                packet_params: packet_params.set_payload_len(len),
+            },
+            ModemCfg::Bpsk {
+                mod_params,
+                packet_params,
+            } => ModemCfg::Bpsk {
+                mod_params,
+                packet_params: packet_params.set_payload_len(len),
+            },
+        }
+    }
+
+    /// Time on air of a packet with a `payload_len` byte payload, rounded up
+    /// to the nearest nanosecond.
+    ///
+    /// This includes the preamble, sync word, header, and CRC added by the
+    /// radio, the payload length in the packet parameters is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{
+    ///     CodingRate, CrcType, FskBitrate, FskModParams, GenericPacketParams, HeaderType,
+    ///     LoRaBandwidth, LoRaModParams, LoRaPacketParams, ModemCfg, SpreadingFactor,
+    /// };
+    ///
+    /// const LORA: ModemCfg = ModemCfg::LoRa {
+    ///     mod_params: LoRaModParams::new()
+    ///         .set_sf(SpreadingFactor::Sf7)
+    ///         .set_bw(LoRaBandwidth::Bw125)
+    ///         .set_cr(CodingRate::Cr45),
+    ///     packet_params: LoRaPacketParams::new()
+    ///         .set_preamble_len(8)
+    ///         .set_header_type(HeaderType::Variable)
+    ///         .set_crc_en(true),
+    /// };
+    /// // 8 + 4.25 + 28 symbols of 1.024 ms
+    /// assert_eq!(LORA.time_on_air(10), Duration::from_micros(41_216));
+    ///
+    /// const FSK: ModemCfg = ModemCfg::Fsk {
+    ///     mod_params: FskModParams::new().set_bitrate(FskBitrate::from_bps(50_000)),
+    ///     packet_params: GenericPacketParams::new()
+    ///         .set_preamble_len(32)
+    ///         .set_sync_word_len(32)
+    ///         .set_header_type(HeaderType::Variable)
+    ///         .set_crc_type(CrcType::Byte2),
+    /// };
+    /// // 32 + 32 + 8 × (1 + 10 + 2) bits of 20 µs
+    /// assert_eq!(FSK.time_on_air(10), Duration::from_micros(3360));
+    /// ```
+    pub const fn time_on_air(&self, payload_len: u8) -> Duration {
+        // FSK bitrate registers are in units of 1 / (32 × 32 MHz) seconds
+        const FSK_BITRATE_HZ: u128 = 32 * 32_000_000;
+
+        let (num, den): (u128, u128) = match self {
+            ModemCfg::LoRa {
+                mod_params,
+                packet_params,
+            } => {
+                // only valid values can be set with the builder methods
+                let (sf, bw) = match (mod_params.sf(), mod_params.bw()) {
+                    (Ok(sf), Ok(bw)) => (sf.factor(), bw.hertz()),
+                    _ => return Duration::from_nanos(0),
+                };
+                let small_sf: bool = sf < 7;
+
+                let mut bits: i32 = 8 * (payload_len as i32) - 4 * (sf as i32);
+                if packet_params.crc_en() {
+                    bits += 16;
+                }
+                if packet_params.explicit_header() {
+                    bits += 20;
+                }
+                if !small_sf {
+                    bits += 8;
+                }
+                let bits: u32 = if bits < 0 { 0 } else { bits as u32 };
+
+                let bits_per_block: u32 = if !small_sf && mod_params.ldro_en() {
+                    4 * (sf - 2)
+                } else {
+                    4 * sf
+                };
+                let mut blocks: u32 = bits / bits_per_block;
+                if blocks * bits_per_block < bits {
+                    blocks += 1;
+                }
+
+                // preamble, sync word, and start frame delimiter
+                let sync_quarters: u32 = if small_sf { 25 } else { 17 };
+                let quarters: u32 = 4 * (packet_params.preamble_len() as u32)
+                    + sync_quarters
+                    + 4 * (8 + blocks * mod_params.cr_denominator());
+
+                ((quarters as u128) << sf, 4 * (bw as u128))
+            }
+            ModemCfg::Fsk {
+                mod_params,
+                packet_params,
+            } => {
+                let bits: u32 = (packet_params.preamble_len() as u32)
+                    + (packet_params.sync_word_len() as u32)
+                    + 8 * (packet_params.overhead_len() + payload_len as u32);
+                (
+                    (bits as u128) * (mod_params.bitrate().into_bits() as u128),
+                    FSK_BITRATE_HZ,
+                )
+            }
+            ModemCfg::Bpsk { mod_params, .. } => (
+                8 * (payload_len as u128) * (mod_params.bitrate().into_bits() as u128),
+                FSK_BITRATE_HZ,
+            ),
+        };
+
+        let num: u128 = num * 1_000_000_000;
+        let mut nanos: u128 = num / den;
+        if nanos * den < num {
+            nanos += 1;
+        }
+        Duration::from_nanos(nanos as u64)
+    }
+}
+
+/// Packet type read back from the radio does not match the expected packet
+/// type.
+///
+/// Returned by [`set_modem_cfg`] and [`check_packet_type`].
+///
+/// [`set_modem_cfg`]: crate::SubGhz::set_modem_cfg
+/// [`check_packet_type`]: crate::SubGhz::check_packet_type
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacketTypeMismatch {
+    /// Expected packet type.
+    pub expected: PacketType,
+    /// Packet type read from the radio, or the raw value if it is reserved.
+    pub actual: Result<PacketType, u8>,
+}
+
+impl core::fmt::Display for PacketTypeMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Packet type mismatch, expected {:?} actual {:?}",
+            self.expected, self.actual
+        )
+    }
+}
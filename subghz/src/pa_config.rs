@@ -4,6 +4,7 @@
 ///
 /// [`set_pa_config`]: crate::SubGhz::set_pa_config
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct PaConfig {
     buf: [u8; 5],
 }
@@ -89,6 +90,24 @@ impl PaConfig {
         self
     }
 
+    /// Get the power amplifier selection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{PaConfig, PaSel};
+    ///
+    /// const PA_CONFIG: PaConfig = PaConfig::new().set_pa(PaSel::Hp);
+    /// assert_eq!(PA_CONFIG.pa(), PaSel::Hp);
+    /// ```
+    pub const fn pa(&self) -> PaSel {
+        if self.buf[3] & 0b1 == 0b1 {
+            PaSel::Lp
+        } else {
+            PaSel::Hp
+        }
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
@@ -119,6 +138,7 @@ impl Default for PaConfig {
 /// This is an argument of [`PaConfig::set_pa`].
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PaSel {
     /// High power amplifier.
     Hp = 0b0,
@@ -1,6 +1,7 @@
 /// Preamble detection length for [`GenericPacketParams`].
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PreambleDetection {
     /// Preamble detection disabled.
     Disabled = 0x0,
@@ -17,6 +18,7 @@ pub enum PreambleDetection {
 /// Address comparison/filtering for [`GenericPacketParams`].
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AddrComp {
     /// Address comparison/filtering disabled.
     Disabled = 0x0,
@@ -31,6 +33,7 @@ pub enum AddrComp {
 /// Argument of [`GenericPacketParams::set_header_type`] and
 /// [`LoRaPacketParams::set_header_type`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum HeaderType {
     /// Fixed; payload length and header field not added to packet.
     Fixed,
@@ -57,6 +60,7 @@ impl HeaderType {
 /// CRC type definition for [`GenericPacketParams`].
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CrcType {
     /// 1-byte CRC.
     Byte1 = 0x0,
@@ -74,6 +78,7 @@ pub enum CrcType {
 ///
 /// [`set_packet_params`]: crate::SubGhz::set_packet_params
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GenericPacketParams {
     buf: [u8; 10],
 }
@@ -130,6 +135,20 @@ impl GenericPacketParams {
         self
     }
 
+    /// Get the preamble length in number of symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::GenericPacketParams;
+    ///
+    /// const PKT_PARAMS: GenericPacketParams = GenericPacketParams::new().set_preamble_len(12);
+    /// assert_eq!(PKT_PARAMS.preamble_len(), 12);
+    /// ```
+    pub const fn preamble_len(&self) -> u16 {
+        ((self.buf[1] as u16) << 8) | (self.buf[2] as u16)
+    }
+
     /// Preabmle detection length in number of bit symbols.
     ///
     /// # Example
@@ -176,6 +195,20 @@ impl GenericPacketParams {
         self
     }
 
+    /// Get the sync word length in bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::GenericPacketParams;
+    ///
+    /// const PKT_PARAMS: GenericPacketParams = GenericPacketParams::new().set_sync_word_len(16);
+    /// assert_eq!(PKT_PARAMS.sync_word_len(), 16);
+    /// ```
+    pub const fn sync_word_len(&self) -> u8 {
+        self.buf[4]
+    }
+
     /// Address comparison/filtering.
     ///
     /// # Example
@@ -269,6 +302,23 @@ impl GenericPacketParams {
         self
     }
 
+    /// Number of bytes added to the payload by the radio, the length field,
+    /// address, and CRC.
+    pub(crate) const fn overhead_len(&self) -> u32 {
+        let header: u32 = self.buf[6] as u32;
+        let addr: u32 = if self.buf[5] == AddrComp::Disabled as u8 {
+            0
+        } else {
+            1
+        };
+        let crc: u32 = match self.buf[8] {
+            0x1 => 0,
+            0x0 | 0x4 => 1,
+            _ => 2,
+        };
+        header + addr + crc
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
@@ -308,6 +358,7 @@ impl Default for GenericPacketParams {
 ///
 /// [`set_lora_packet_params`]: crate::SubGhz::set_lora_packet_params
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LoRaPacketParams {
     buf: [u8; 7],
 }
@@ -363,6 +414,20 @@ impl LoRaPacketParams {
         self
     }
 
+    /// Get the preamble length in number of symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::LoRaPacketParams;
+    ///
+    /// const PKT_PARAMS: LoRaPacketParams = LoRaPacketParams::new().set_preamble_len(12);
+    /// assert_eq!(PKT_PARAMS.preamble_len(), 12);
+    /// ```
+    pub const fn preamble_len(&self) -> u16 {
+        ((self.buf[1] as u16) << 8) | (self.buf[2] as u16)
+    }
+
     /// Header type (fixed or variable).
     ///
     /// # Example
@@ -433,6 +498,16 @@ impl LoRaPacketParams {
         self
     }
 
+    /// Returns `true` if the header is explicit (variable length).
+    pub(crate) const fn explicit_header(&self) -> bool {
+        self.buf[3] == HeaderType::Variable.to_bits_lora()
+    }
+
+    /// CRC enable.
+    pub(crate) const fn crc_en(&self) -> bool {
+        self.buf[5] != 0
+    }
+
     /// Extracts a slice containing the packet.
     ///
     /// # Example
@@ -467,6 +542,7 @@ impl Default for LoRaPacketParams {
 ///
 /// [`set_lora_packet_params`]: crate::SubGhz::set_lora_packet_params
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct BpskPacketParams {
     buf: [u8; 2],
 }
@@ -111,6 +111,29 @@ impl FskPacketStatus {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for FskPacketStatus {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "FskPacketStatus {{ status: {}, preamble_error: {}, sync_err: {}, adrs_err: {}, crc_err: {}, length_err: {}, abort_err: {}, pkt_received: {}, pkt_sent: {}, rssi_sync: {}/{}, rssi_avg: {}/{} }}",
+            self.status(),
+            self.preamble_error(),
+            self.sync_err(),
+            self.adrs_err(),
+            self.crc_err(),
+            self.length_err(),
+            self.abort_err(),
+            self.pkt_received(),
+            self.pkt_sent(),
+            self.rssi_sync().numer(),
+            self.rssi_sync().denom(),
+            self.rssi_avg().numer(),
+            self.rssi_avg().denom(),
+        )
+    }
+}
+
 /// (G)FSK packet status.
 ///
 /// Returned by [`lora_packet_status`].
@@ -196,3 +219,20 @@ impl LoRaPacketStatus {
         Ratio::new(i16::from(self.buf[3]), -2)
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for LoRaPacketStatus {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "LoRaPacketStatus {{ status: {}, rssi_pkt: {}/{}, snr_pkt: {}/{}, signal_rssi_pkt: {}/{} }}",
+            self.status(),
+            self.rssi_pkt().numer(),
+            self.rssi_pkt().denom(),
+            self.snr_pkt().numer(),
+            self.snr_pkt().denom(),
+            self.signal_rssi_pkt().numer(),
+            self.signal_rssi_pkt().denom(),
+        )
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{FskBitrate, GenericPacketParams, LoRaBandwidth, LoRaPacketParams, SpreadingFactor};
+use core::time::Duration;
+
+/// Number of LoRa symbols between the preamble and the header, in quarter
+/// symbols.
+///
+/// 2 sync word symbols, and 2.25 start frame delimiter symbols.
+const LORA_SYNC_QUARTERS: u64 = 4 * 2 + 9;
+
+/// [`LORA_SYNC_QUARTERS`] for SF5 and SF6, which have a 4.25 symbol start
+/// frame delimiter.
+const LORA_SYNC_QUARTERS_SF5_SF6: u64 = 4 * 2 + 17;
+
+/// Number of LoRa explicit header symbols, in quarter symbols.
+const LORA_HEADER_QUARTERS: u64 = 4 * 8;
+
+/// Fixed packet timing derived from the modulation and packet parameters.
+///
+/// This is used to correct timestamps captured on the radio interrupts to the
+/// start of the packet.
+///
+/// * For (G)FSK the timestamp should be captured on [`Irq::SyncDetected`],
+///   which is asserted at the end of the preamble and sync word.
+/// * For LoRa the timestamp should be captured on [`Irq::HeaderValid`], which
+///   is asserted at the end of the explicit header.
+///   There is no interrupt before [`Irq::RxDone`] with an implicit header.
+///
+/// The offset does not include the demodulator latency, which is fixed for a
+/// given set of modulation parameters and should be calibrated if more
+/// precision is required.
+///
+/// All calculations are `const` and do not require a radio.
+///
+/// # Example
+///
+/// ```
+/// use core::time::Duration;
+/// use stm32wl_hal_subghz::{FskBitrate, GenericPacketParams, PacketTiming};
+///
+/// const PKT_PARAMS: GenericPacketParams = GenericPacketParams::new()
+///     .set_preamble_len(32)
+///     .set_sync_word_len(32);
+/// const TIMING: PacketTiming = PacketTiming::fsk(FskBitrate::from_bps(50_000), &PKT_PARAMS);
+///
+/// assert_eq!(TIMING.symbol_time(), Duration::from_micros(20));
+/// assert_eq!(TIMING.sync_offset(), Duration::from_micros(1280));
+/// ```
+///
+/// [`Irq::SyncDetected`]: crate::Irq::SyncDetected
+/// [`Irq::HeaderValid`]: crate::Irq::HeaderValid
+/// [`Irq::RxDone`]: crate::Irq::RxDone
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacketTiming {
+    // symbol time is num / den seconds
+    num: u32,
+    den: u32,
+    // offset in quarter symbols
+    quarters: u64,
+}
+
+impl PacketTiming {
+    /// Create the packet timing for a LoRa modulation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{LoRaBandwidth, LoRaPacketParams, PacketTiming, SpreadingFactor};
+    ///
+    /// const PKT_PARAMS: LoRaPacketParams = LoRaPacketParams::new().set_preamble_len(8);
+    /// const TIMING: PacketTiming =
+    ///     PacketTiming::lora(SpreadingFactor::Sf7, LoRaBandwidth::Bw125, &PKT_PARAMS);
+    ///
+    /// assert_eq!(TIMING.symbol_time(), Duration::from_micros(1024));
+    /// // 8 + 4.25 + 8 symbols
+    /// assert_eq!(TIMING.sync_offset(), Duration::from_micros(20_736));
+    ///
+    /// // SF5 and SF6 have a longer start frame delimiter, 8 + 6.25 + 8 symbols
+    /// const TIMING_SF5: PacketTiming =
+    ///     PacketTiming::lora(SpreadingFactor::Sf5, LoRaBandwidth::Bw125, &PKT_PARAMS);
+    /// assert_eq!(TIMING_SF5.sync_offset(), Duration::from_micros(5_696));
+    /// ```
+    pub const fn lora(
+        sf: SpreadingFactor,
+        bw: LoRaBandwidth,
+        params: &LoRaPacketParams,
+    ) -> PacketTiming {
+        let sync: u64 = match sf {
+            SpreadingFactor::Sf5 | SpreadingFactor::Sf6 => LORA_SYNC_QUARTERS_SF5_SF6,
+            _ => LORA_SYNC_QUARTERS,
+        };
+        PacketTiming {
+            num: 1 << sf.factor(),
+            den: bw.hertz(),
+            quarters: 4 * (params.preamble_len() as u64) + sync + LORA_HEADER_QUARTERS,
+        }
+    }
+
+    /// Create the packet timing for a (G)FSK modulation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{FskBitrate, GenericPacketParams, PacketTiming};
+    ///
+    /// const PKT_PARAMS: GenericPacketParams = GenericPacketParams::new()
+    ///     .set_preamble_len(16)
+    ///     .set_sync_word_len(16);
+    /// const TIMING: PacketTiming = PacketTiming::fsk(FskBitrate::from_bps(100_000), &PKT_PARAMS);
+    ///
+    /// assert_eq!(TIMING.sync_offset(), Duration::from_micros(320));
+    /// ```
+    pub const fn fsk(bitrate: FskBitrate, params: &GenericPacketParams) -> PacketTiming {
+        PacketTiming {
+            num: 1,
+            den: bitrate.as_bps(),
+            quarters: 4 * (params.preamble_len() as u64 + params.sync_word_len() as u64),
+        }
+    }
+
+    /// Duration of one symbol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{LoRaBandwidth, LoRaPacketParams, PacketTiming, SpreadingFactor};
+    ///
+    /// const TIMING: PacketTiming = PacketTiming::lora(
+    ///     SpreadingFactor::Sf12,
+    ///     LoRaBandwidth::Bw125,
+    ///     &LoRaPacketParams::new(),
+    /// );
+    /// assert_eq!(TIMING.symbol_time(), Duration::from_micros(32_768));
+    /// ```
+    pub const fn symbol_time(&self) -> Duration {
+        self.quarters_to_duration(4)
+    }
+
+    /// Time from the start of the packet to the interrupt used to capture the
+    /// timestamp.
+    ///
+    /// Subtract this from the captured timestamp to get the start of the
+    /// packet.
+    ///
+    /// # Example
+    ///
+    /// See [`PacketTiming`].
+    pub const fn sync_offset(&self) -> Duration {
+        self.quarters_to_duration(self.quarters)
+    }
+
+    /// Convert [`sync_offset`](Self::sync_offset) to timer ticks, rounded to
+    /// the nearest tick.
+    ///
+    /// # Example
+    ///
+    /// Convert to cycles of a 48 MHz DWT cycle counter.
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBitrate, GenericPacketParams, PacketTiming};
+    ///
+    /// const PKT_PARAMS: GenericPacketParams = GenericPacketParams::new()
+    ///     .set_preamble_len(32)
+    ///     .set_sync_word_len(32);
+    /// const TIMING: PacketTiming = PacketTiming::fsk(FskBitrate::from_bps(50_000), &PKT_PARAMS);
+    ///
+    /// assert_eq!(TIMING.sync_offset_ticks(48_000_000), 61_440);
+    /// ```
+    pub const fn sync_offset_ticks(&self, tick_hz: u32) -> u64 {
+        let num: u128 = (self.quarters as u128) * (self.num as u128) * (tick_hz as u128);
+        let den: u128 = 4 * (self.den as u128);
+        ((num + den / 2) / den) as u64
+    }
+
+    const fn quarters_to_duration(&self, quarters: u64) -> Duration {
+        let num: u128 = (quarters as u128) * (self.num as u128) * 1_000_000_000;
+        let den: u128 = 4 * (self.den as u128);
+        Duration::from_nanos(((num + den / 2) / den) as u64)
+    }
+}
+
+/// Read the DWT cycle counter.
+///
+/// This is a timestamp source for [`RxQueue::capture_timestamp`] and
+/// [`TxQueue::capture_timestamp`], it should be read first in the radio
+/// interrupt handler.
+///
+/// The cycle counter must be enabled with `DCB::enable_trace` and
+/// `DWT::enable_cycle_counter` from the `cortex-m` crate, it wraps every
+/// 2<sup>32</sup> core clock cycles.
+///
+/// The Cortex-M0+ core does not have a cycle counter, use a timer input
+/// capture instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+/// use stm32wl_hal_subghz::{dwt_cycle_count, RxQueue};
+///
+/// let mut queue: RxQueue<8, 64> = RxQueue::new();
+///
+/// // in the radio interrupt handler
+/// sg.capture_rx_timestamp(&mut queue, dwt_cycle_count())?;
+/// sg.poll_continuous_rx(&mut queue)?;
+/// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+/// ```
+///
+/// [`RxQueue::capture_timestamp`]: crate::RxQueue::capture_timestamp
+/// [`TxQueue::capture_timestamp`]: crate::TxQueue::capture_timestamp
+#[cfg(not(feature = "stm32wl5x_cm0p"))]
+pub fn dwt_cycle_count() -> u32 {
+    unsafe { (*crate::pac::DWT::PTR).cyccnt.read() }
+}
@@ -5,6 +5,7 @@
 /// [`set_packet_type`]: crate::SubGhz::set_packet_type
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PacketType {
     /// FSK (frequency shift keying) generic packet type.
     Fsk = 0,
@@ -0,0 +1,26 @@
+/// State of the radio driven by a protocol layer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) enum RadioState {
+    Idle,
+    Rx,
+    Cad,
+    Tx,
+}
+
+/// Frame length for a maximum transmission unit of `mtu` bytes, limited to
+/// the 255 byte radio buffer.
+pub(crate) const fn mtu_len(mtu: usize) -> u8 {
+    if mtu > u8::MAX as usize {
+        u8::MAX
+    } else {
+        mtu as u8
+    }
+}
+
+/// Next state of a xorshift32 pseudorandom number generator.
+pub(crate) const fn xorshift32(mut state: u32) -> u32 {
+    state ^= state << 13;
+    state ^= state >> 17;
+    state ^= state << 5;
+    state
+}
@@ -0,0 +1,385 @@
+use core::convert::TryFrom;
+
+use crate::{
+    irq::IRQ_ALL, GenericPacketParams, Irq, LoRaPacketParams, RfFreq, SleepCfg, StandbyClk,
+    StatusMode, SubGhz, SubGhzError, Timeout,
+};
+
+mod sealed {
+    use crate::{SubGhz, SubGhzError};
+
+    pub trait PacketParams: Copy {
+        fn write(&self, sg: &mut SubGhz, payload_len: u8) -> Result<(), SubGhzError>;
+        fn info(sg: &SubGhz) -> Result<super::RxInfo, SubGhzError>;
+    }
+}
+
+impl sealed::PacketParams for LoRaPacketParams {
+    fn write(&self, sg: &mut SubGhz, payload_len: u8) -> Result<(), SubGhzError> {
+        sg.set_lora_packet_params(&self.set_payload_len(payload_len))
+    }
+
+    fn info(sg: &SubGhz) -> Result<RxInfo, SubGhzError> {
+        let status = sg.lora_packet_status()?;
+        Ok(RxInfo {
+            rssi: status.rssi_pkt().to_integer(),
+            snr: Some(status.snr_pkt().to_integer()),
+        })
+    }
+}
+
+impl sealed::PacketParams for GenericPacketParams {
+    fn write(&self, sg: &mut SubGhz, payload_len: u8) -> Result<(), SubGhzError> {
+        sg.set_packet_params(&self.set_payload_len(payload_len))
+    }
+
+    fn info(sg: &SubGhz) -> Result<RxInfo, SubGhzError> {
+        let status = sg.fsk_packet_status()?;
+        Ok(RxInfo {
+            rssi: status.rssi_avg().to_integer(),
+            snr: None,
+        })
+    }
+}
+
+/// Errors returned by the [`radio`] trait implementations.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RadioError {
+    /// Payload does not fit in the radio buffer, or in the receive buffer.
+    PayloadLen,
+    /// Error on a received packet.
+    ///
+    /// For LoRa this is a CRC error, for (G)FSK this is a preamble, sync word,
+    /// address, CRC, or length error.
+    Rx,
+    /// LoRa header error on a received packet.
+    Header,
+    /// TX or RX timeout.
+    Timeout,
+    /// The radio returned an unknown mode in the status.
+    UnknownMode(u8),
+}
+
+impl From<SubGhzError> for RadioError {
+    fn from(e: SubGhzError) -> Self {
+        match e {}
+    }
+}
+
+/// Operating modes of the radio.
+///
+/// Associated type of the [`radio::State`] implementation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RadioMode {
+    /// Sleep mode, entered with a warm start.
+    Sleep,
+    /// Standby mode with the RC 13MHz clock.
+    StandbyRc,
+    /// Standby mode with the HSE32 clock.
+    StandbyHse,
+    /// Frequency synthesis mode.
+    Fs,
+    /// Receive mode.
+    Rx,
+    /// Transmit mode.
+    Tx,
+}
+
+impl radio::RadioState for RadioMode {
+    fn idle() -> Self {
+        RadioMode::StandbyRc
+    }
+
+    fn sleep() -> Self {
+        RadioMode::Sleep
+    }
+}
+
+/// Information on a received packet.
+///
+/// Associated type of the [`radio::Receive`] implementation.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxInfo {
+    /// Signal strength of the packet in dBm.
+    ///
+    /// This is the average RSSI for (G)FSK packets.
+    pub rssi: i16,
+    /// Signal to noise ratio of the packet in dB.
+    ///
+    /// This is `None` for (G)FSK packets.
+    pub snr: Option<i16>,
+}
+
+impl radio::ReceiveInfo for RxInfo {
+    fn rssi(&self) -> i16 {
+        self.rssi
+    }
+}
+
+/// Sub-GHz radio with a packet configuration, implements the [`radio`]
+/// traits.
+///
+/// Use the [`LoRaRadio`] and [`FskRadio`] aliases.
+///
+/// The modulation parameters, frequency, PA, and TX parameters are
+/// configured on the [`SubGhz`] before it is wrapped.
+/// The radio buffer base addresses are set to `0` by [`new`](Self::new).
+pub struct Radio<P> {
+    sg: SubGhz,
+    params: P,
+    tx_timeout: Timeout,
+    rx_timeout: Timeout,
+}
+
+#[cfg(feature = "defmt")]
+impl<P: defmt::Format> defmt::Format for Radio<P> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Radio {{ params: {}, tx_timeout: {}, rx_timeout: {} }}",
+            self.params,
+            self.tx_timeout,
+            self.rx_timeout
+        )
+    }
+}
+
+/// Sub-GHz radio configured for LoRa packets.
+///
+/// # Example
+///
+/// ```no_run
+/// use radio::{Receive, Transmit};
+/// use stm32wl_hal_subghz::{
+///     CodingRate, HeaderType, LoRaBandwidth, LoRaModParams, LoRaPacketParams, LoRaRadio,
+///     PacketType, RfFreq, SpreadingFactor, SubGhz,
+/// };
+///
+/// let mut sg = unsafe { SubGhz::steal() };
+/// sg.set_packet_type(PacketType::LoRa)?;
+/// sg.set_rf_frequency(&RfFreq::F915)?;
+/// sg.set_lora_mod_params(
+///     &LoRaModParams::new()
+///         .set_sf(SpreadingFactor::Sf7)
+///         .set_bw(LoRaBandwidth::Bw125)
+///         .set_cr(CodingRate::Cr45),
+/// )?;
+/// // ... PA and TX configuration
+///
+/// const PARAMS: LoRaPacketParams = LoRaPacketParams::new()
+///     .set_preamble_len(8)
+///     .set_header_type(HeaderType::Variable)
+///     .set_crc_en(true);
+///
+/// let mut radio: LoRaRadio = LoRaRadio::new(sg, PARAMS)?;
+/// radio.start_transmit(b"Hello")?;
+/// while !radio.check_transmit()? {}
+/// # Ok::<(), stm32wl_hal_subghz::RadioError>(())
+/// ```
+pub type LoRaRadio = Radio<LoRaPacketParams>;
+
+/// Sub-GHz radio configured for (G)FSK packets.
+///
+/// # Example
+///
+/// ```no_run
+/// use radio::Receive;
+/// use stm32wl_hal_subghz::{
+///     CrcType, FskRadio, GenericPacketParams, HeaderType, PacketType, SubGhz,
+/// };
+///
+/// let mut sg = unsafe { SubGhz::steal() };
+/// sg.set_packet_type(PacketType::Fsk)?;
+/// // ... modulation, frequency, and sync word configuration
+///
+/// const PARAMS: GenericPacketParams = GenericPacketParams::new()
+///     .set_header_type(HeaderType::Variable)
+///     .set_crc_type(CrcType::Byte2);
+///
+/// let mut radio: FskRadio = FskRadio::new(sg, PARAMS)?;
+/// radio.start_receive()?;
+/// while !radio.check_receive(true)? {}
+///
+/// let mut buf: [u8; 255] = [0; 255];
+/// let (len, info) = radio.get_received(&mut buf)?;
+/// # Ok::<(), stm32wl_hal_subghz::RadioError>(())
+/// ```
+pub type FskRadio = Radio<GenericPacketParams>;
+
+impl<P: sealed::PacketParams> Radio<P> {
+    /// Wrap a configured [`SubGhz`].
+    ///
+    /// The TX and RX timeouts default to [`Timeout::DISABLED`].
+    pub fn new(mut sg: SubGhz, params: P) -> Result<Self, RadioError> {
+        sg.set_buffer_base_address(0, 0)?;
+        Ok(Radio {
+            sg,
+            params,
+            tx_timeout: Timeout::DISABLED,
+            rx_timeout: Timeout::DISABLED,
+        })
+    }
+
+    /// Free the [`SubGhz`] from the wrapper.
+    pub fn free(self) -> SubGhz {
+        self.sg
+    }
+
+    /// Access the wrapped [`SubGhz`].
+    pub fn subghz(&mut self) -> &mut SubGhz {
+        &mut self.sg
+    }
+
+    /// Set the timeout used by [`radio::Transmit::start_transmit`].
+    pub fn set_tx_timeout(&mut self, timeout: Timeout) {
+        self.tx_timeout = timeout
+    }
+
+    /// Set the timeout used by [`radio::Receive::start_receive`].
+    pub fn set_rx_timeout(&mut self, timeout: Timeout) {
+        self.rx_timeout = timeout
+    }
+
+    fn irq_status(&mut self) -> Result<u16, RadioError> {
+        let (_, irq) = self.sg.irq_status()?;
+        Ok(irq)
+    }
+}
+
+impl<P: sealed::PacketParams> radio::Transmit for Radio<P> {
+    type Error = RadioError;
+
+    fn start_transmit(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let len: u8 = u8::try_from(data.len()).map_err(|_| RadioError::PayloadLen)?;
+        self.sg.set_standby(StandbyClk::Rc)?;
+        self.sg.clear_irq_status(IRQ_ALL)?;
+        self.sg.write_buffer(0, data)?;
+        self.params.write(&mut self.sg, len)?;
+        self.sg.set_tx(self.tx_timeout)?;
+        Ok(())
+    }
+
+    fn check_transmit(&mut self) -> Result<bool, Self::Error> {
+        let irq: u16 = self.irq_status()?;
+        if irq & Irq::TxDone.mask() != 0 {
+            self.sg.clear_irq_status(IRQ_ALL)?;
+            Ok(true)
+        } else if irq & Irq::Timeout.mask() != 0 {
+            self.sg.clear_irq_status(IRQ_ALL)?;
+            Err(RadioError::Timeout)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+impl<P: sealed::PacketParams> radio::Receive for Radio<P> {
+    type Error = RadioError;
+    type Info = RxInfo;
+
+    fn start_receive(&mut self) -> Result<(), Self::Error> {
+        self.sg.set_standby(StandbyClk::Rc)?;
+        self.sg.clear_irq_status(IRQ_ALL)?;
+        self.params.write(&mut self.sg, u8::MAX)?;
+        self.sg.set_rx(self.rx_timeout)?;
+        Ok(())
+    }
+
+    fn check_receive(&mut self, restart: bool) -> Result<bool, Self::Error> {
+        let irq: u16 = self.irq_status()?;
+        let err: Option<RadioError> = if irq & Irq::Err.mask() != 0 {
+            Some(RadioError::Rx)
+        } else if irq & Irq::HeaderErr.mask() != 0 {
+            Some(RadioError::Header)
+        } else if irq & Irq::Timeout.mask() != 0 {
+            Some(RadioError::Timeout)
+        } else {
+            None
+        };
+
+        match err {
+            Some(e) => {
+                if restart {
+                    self.start_receive()?;
+                    Ok(false)
+                } else {
+                    self.sg.clear_irq_status(IRQ_ALL)?;
+                    Err(e)
+                }
+            }
+            None => Ok(irq & Irq::RxDone.mask() != 0),
+        }
+    }
+
+    fn get_received(&mut self, buff: &mut [u8]) -> Result<(usize, Self::Info), Self::Error> {
+        let (_, len, ptr) = self.sg.rx_buffer_status()?;
+        let len: usize = usize::from(len);
+        let data: &mut [u8] = buff.get_mut(..len).ok_or(RadioError::PayloadLen)?;
+        self.sg.read_buffer(ptr, data)?;
+        let info: RxInfo = P::info(&self.sg)?;
+        self.sg.clear_irq_status(IRQ_ALL)?;
+        Ok((len, info))
+    }
+}
+
+impl<P: sealed::PacketParams> radio::Channel for Radio<P> {
+    type Channel = RfFreq;
+    type Error = RadioError;
+
+    fn set_channel(&mut self, channel: &Self::Channel) -> Result<(), Self::Error> {
+        self.sg.set_rf_frequency(channel)?;
+        Ok(())
+    }
+}
+
+impl<P: sealed::PacketParams> radio::Rssi for Radio<P> {
+    type Error = RadioError;
+
+    fn poll_rssi(&mut self) -> Result<i16, Self::Error> {
+        let (_, rssi) = self.sg.rssi_inst()?;
+        Ok(rssi.to_integer())
+    }
+}
+
+impl<P: sealed::PacketParams> radio::State for Radio<P> {
+    type State = RadioMode;
+    type Error = RadioError;
+
+    fn set_state(&mut self, state: Self::State) -> Result<(), Self::Error> {
+        match state {
+            RadioMode::Sleep => self.sg.set_sleep(SleepCfg::default())?,
+            RadioMode::StandbyRc => self.sg.set_standby(StandbyClk::Rc)?,
+            RadioMode::StandbyHse => self.sg.set_standby(StandbyClk::Hse32)?,
+            RadioMode::Fs => self.sg.set_fs()?,
+            RadioMode::Rx => self.sg.set_rx(self.rx_timeout)?,
+            RadioMode::Tx => self.sg.set_tx(self.tx_timeout)?,
+        }
+        Ok(())
+    }
+
+    fn get_state(&mut self) -> Result<Self::State, Self::Error> {
+        match self.sg.status()?.mode() {
+            Ok(StatusMode::StandbyRc) => Ok(RadioMode::StandbyRc),
+            Ok(StatusMode::StandbyHse) => Ok(RadioMode::StandbyHse),
+            Ok(StatusMode::Fs) => Ok(RadioMode::Fs),
+            Ok(StatusMode::Rx) => Ok(RadioMode::Rx),
+            Ok(StatusMode::Tx) => Ok(RadioMode::Tx),
+            Err(bits) => Err(RadioError::UnknownMode(bits)),
+        }
+    }
+}
+
+impl<P: sealed::PacketParams> radio::Busy for Radio<P> {
+    type Error = RadioError;
+
+    /// Returns `true` if a packet is being received.
+    fn is_busy(&mut self) -> Result<bool, Self::Error> {
+        const RECEIVING: u16 =
+            Irq::PreambleDetected.mask() | Irq::SyncDetected.mask() | Irq::HeaderValid.mask();
+        let irq: u16 = self.irq_status()?;
+        Ok(irq & RECEIVING != 0 && irq & Irq::RxDone.mask() == 0)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Register, StandbyClk, StatusMode, SubGhz, SubGhzError, Timeout};
+use core::num::NonZeroU32;
+
+/// LNA enable bit of [`Register::ANALNA`].
+const ANALNA_EN: u8 = 1 << 0;
+/// Mixer enable bit of [`Register::ANAMIXER`].
+const ANAMIXER_EN: u8 = 1 << 7;
+
+/// Number of status reads to wait for the radio to enter receive mode.
+const RX_POLL_MAX: u32 = 1000;
+
+/// Radio random number generator errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RadioRngError {
+    /// The radio did not enter receive mode.
+    ///
+    /// Check that the RF frequency is set and the radio is not in use.
+    Rx,
+}
+
+impl From<SubGhzError> for RadioRngError {
+    fn from(e: SubGhzError) -> Self {
+        match e {}
+    }
+}
+
+impl From<RadioRngError> for rand_core::Error {
+    fn from(e: RadioRngError) -> Self {
+        match e {
+            RadioRngError::Rx => NonZeroU32::new(1).unwrap().into(),
+        }
+    }
+}
+
+/// Random number generator using the sub-GHz radio receiver noise.
+///
+/// Created by [`radio_rng`], the LNA and mixer are disabled while this
+/// exists, and are restored when it is dropped.
+///
+/// This is an entropy source independent of the RNG peripheral, it is
+/// intended to be mixed with other sources.
+/// The output is not conditioned and has not been validated, it does not
+/// implement [`rand_core::CryptoRng`].
+///
+/// # Example
+///
+/// ```no_run
+/// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+/// let mut rng = sg.radio_rng()?;
+/// let mut seed: [u8; 32] = [0; 32];
+/// rng.try_fill(&mut seed)?;
+/// drop(rng);
+/// # Ok::<(), stm32wl_hal_subghz::RadioRngError>(())
+/// ```
+///
+/// [`radio_rng`]: crate::SubGhz::radio_rng
+pub struct RadioRng<'a> {
+    sg: &'a mut SubGhz,
+    ana_lna: u8,
+    ana_mixer: u8,
+}
+
+impl<'a> RadioRng<'a> {
+    /// Read a random word from the radio.
+    ///
+    /// The radio is put in receive mode, the random number registers are
+    /// read, and the radio is returned to standby with the RC 13 MHz clock.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let mut rng = sg.radio_rng()?;
+    /// let word: u32 = rng.try_next_u32()?;
+    /// # Ok::<(), stm32wl_hal_subghz::RadioRngError>(())
+    /// ```
+    pub fn try_next_u32(&mut self) -> Result<u32, RadioRngError> {
+        self.sg.set_rx(Timeout::MAX)?;
+        let mut polls: u32 = 0;
+        while self.sg.status()?.mode() != Ok(StatusMode::Rx) {
+            polls += 1;
+            if polls >= RX_POLL_MAX {
+                self.sg.set_standby(StandbyClk::Rc)?;
+                return Err(RadioRngError::Rx);
+            }
+        }
+
+        let mut buf: [u8; 4] = [0; 4];
+        self.sg.read_registers(Register::RNG3, &mut buf)?;
+        self.sg.set_standby(StandbyClk::Rc)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// Fill a slice with random bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let mut rng = sg.radio_rng()?;
+    /// let mut nonce: [u8; 12] = [0; 12];
+    /// rng.try_fill(&mut nonce)?;
+    /// # Ok::<(), stm32wl_hal_subghz::RadioRngError>(())
+    /// ```
+    pub fn try_fill(&mut self, dest: &mut [u8]) -> Result<(), RadioRngError> {
+        for chunk in dest.chunks_mut(4) {
+            let word: [u8; 4] = self.try_next_u32()?.to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<'a> defmt::Format for RadioRng<'a> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "RadioRng {{ .. }}")
+    }
+}
+
+impl<'a> Drop for RadioRng<'a> {
+    fn drop(&mut self) {
+        let _ = self.sg.set_standby(StandbyClk::Rc);
+        let _ = self.sg.write_register(Register::ANALNA, &[self.ana_lna]);
+        let _ = self
+            .sg
+            .write_register(Register::ANAMIXER, &[self.ana_mixer]);
+    }
+}
+
+impl<'a> rand_core::RngCore for RadioRng<'a> {
+    /// Not recommended for use, panics upon errors.
+    fn next_u32(&mut self) -> u32 {
+        self.try_next_u32().unwrap()
+    }
+
+    /// Not recommended for use, panics upon errors.
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_u32(self)
+    }
+
+    /// Not recommended for use, panics upon errors.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill(dest).unwrap()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        Ok(self.try_fill(dest)?)
+    }
+}
+
+/// Radio random number generator.
+impl SubGhz {
+    /// Start the radio random number generator.
+    ///
+    /// The LNA and mixer are disabled so the random numbers are sampled from
+    /// the internal receiver noise, and not from signals on air, this is the
+    /// same procedure as `SUBGRF_GetRandom` in STM32CubeWL.
+    /// The LNA and mixer settings are restored and the radio is returned to
+    /// standby when the [`RadioRng`] is dropped.
+    ///
+    /// The RF frequency must be set before calling this function, the packet
+    /// type and modulation parameters do not matter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// let word: u32 = sg.radio_rng()?.try_next_u32()?;
+    /// # Ok::<(), stm32wl_hal_subghz::RadioRngError>(())
+    /// ```
+    pub fn radio_rng(&mut self) -> Result<RadioRng<'_>, SubGhzError> {
+        self.set_standby(StandbyClk::Rc)?;
+        let ana_lna: u8 = self.read_register(Register::ANALNA)?;
+        let ana_mixer: u8 = self.read_register(Register::ANAMIXER)?;
+        self.write_register(Register::ANALNA, &[ana_lna & !ANALNA_EN])?;
+        self.write_register(Register::ANAMIXER, &[ana_mixer & !ANAMIXER_EN])?;
+        Ok(RadioRng {
+            sg: self,
+            ana_lna,
+            ana_mixer,
+        })
+    }
+}
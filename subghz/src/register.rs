@@ -0,0 +1,154 @@
+/// Sub-GHz radio registers.
+///
+/// Argument of [`read_register`], [`read_registers`], and [`write_register`].
+///
+/// Most registers are documented in the reference manual with a `SUBGHZ_`
+/// prefix and an `R` suffix, for example `SUBGHZ_PAOCPR` is [`PAOCP`].
+/// The remaining registers are not in the reference manual, they are used by
+/// the ST radio driver in STM32CubeWL.
+///
+/// Multi-byte registers are listed by the address of the most significant
+/// byte, read or write all bytes in one transfer with [`read_registers`] and
+/// [`write_register`].
+///
+/// The PA trim registers are not listed, the PA output is configured with
+/// [`set_pa_config`] and [`set_tx_params`], and clamped with [`TXCLAMP`].
+///
+/// [`read_register`]: crate::SubGhz::read_register
+/// [`read_registers`]: crate::SubGhz::read_registers
+/// [`write_register`]: crate::SubGhz::write_register
+/// [`PAOCP`]: Register::PAOCP
+/// [`set_pa_config`]: crate::SubGhz::set_pa_config
+/// [`set_tx_params`]: crate::SubGhz::set_tx_params
+/// [`TXCLAMP`]: Register::TXCLAMP
+#[repr(u16)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Register {
+    /// Number of registers in the retention list.
+    ///
+    /// Not in the reference manual.
+    RETLEN = 0x029F,
+    /// Retention list, register addresses retained in sleep mode with a warm
+    /// start, MSB first.
+    ///
+    /// Not in the reference manual.
+    RETLIST = 0x02A0,
+    /// Generic bit synchronization.
+    GBSYNC = 0x06AC,
+    /// Generic packet control 1A.
+    ///
+    /// Bit 0 is the MSB of the whitening initial value.
+    GPKTCTL1A = 0x06B8,
+    /// Generic whitening initial value LSB.
+    GWHITEINIRL = 0x06B9,
+    /// Generic TX and RX payload length.
+    GRTXPLDLEN = 0x06BB,
+    /// Generic CRC initial value MSB.
+    GCRCINIRH = 0x06BC,
+    /// Generic CRC initial value LSB.
+    GCRCINIRL = 0x06BD,
+    /// Generic CRC polynomial MSB.
+    GCRCPOLRH = 0x06BE,
+    /// Generic CRC polynomial LSB.
+    GCRCPOLRL = 0x06BF,
+    /// Generic synchronization word 7.
+    GSYNC7 = 0x06C0,
+    /// Generic synchronization word 6.
+    GSYNC6 = 0x06C1,
+    /// Generic synchronization word 5.
+    GSYNC5 = 0x06C2,
+    /// Generic synchronization word 4.
+    GSYNC4 = 0x06C3,
+    /// Generic synchronization word 3.
+    GSYNC3 = 0x06C4,
+    /// Generic synchronization word 2.
+    GSYNC2 = 0x06C5,
+    /// Generic synchronization word 1.
+    GSYNC1 = 0x06C6,
+    /// Generic synchronization word 0.
+    GSYNC0 = 0x06C7,
+    /// Generic node address.
+    GNODEADR = 0x06CD,
+    /// Generic broadcast address.
+    GBCASTADR = 0x06CE,
+    /// LoRa payload length.
+    LPLDLEN = 0x0702,
+    /// LoRa symbol timeout.
+    ///
+    /// Not in the reference manual.
+    LSYMBTMO = 0x0706,
+    /// LoRa IQ polarity.
+    ///
+    /// Not in the reference manual.
+    LIQPOL = 0x0736,
+    /// LoRa synchronization word MSB.
+    LSYNCH = 0x0740,
+    /// LoRa synchronization word LSB.
+    LSYNCL = 0x0741,
+    /// LoRa frequency error estimate, 3 bytes.
+    ///
+    /// Not in the reference manual.
+    LFERR = 0x076B,
+    /// Random number generator byte 3 (MSB).
+    RNG3 = 0x0819,
+    /// Random number generator byte 2.
+    RNG2 = 0x081A,
+    /// Random number generator byte 1.
+    RNG1 = 0x081B,
+    /// Random number generator byte 0 (LSB).
+    RNG0 = 0x081C,
+    /// TX modulation.
+    ///
+    /// Not in the reference manual.
+    TXMOD = 0x0889,
+    /// RX gain control.
+    RXGAINC = 0x08AC,
+    /// TX clamp configuration.
+    ///
+    /// Not in the reference manual.
+    TXCLAMP = 0x08D8,
+    /// Analog LNA control.
+    ///
+    /// Not in the reference manual.
+    ANALNA = 0x08E2,
+    /// Analog mixer control.
+    ///
+    /// Not in the reference manual.
+    ANAMIXER = 0x08E5,
+    /// PA over current protection.
+    PAOCP = 0x08E7,
+    /// HSE32 OSC_IN capacitor trim.
+    HSEINTRIM = 0x0911,
+    /// HSE32 OSC_OUT capacitor trim.
+    HSEOUTTRIM = 0x0912,
+    /// SMPS control 0.
+    SMPSC0 = 0x0916,
+    /// Power control, `SUBGHZ_PCR` in the reference manual.
+    PC = 0x091A,
+    /// SMPS control 2.
+    SMPSC2 = 0x0923,
+}
+
+impl Register {
+    /// Get the address of the register.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Register;
+    ///
+    /// assert_eq!(Register::PAOCP.address(), 0x08E7);
+    /// assert_eq!(Register::GSYNC7.address(), 0x06C0);
+    /// ```
+    pub const fn address(self) -> u16 {
+        self as u16
+    }
+}
+
+impl From<Register> for u16 {
+    fn from(register: Register) -> Self {
+        register.address()
+    }
+}
@@ -4,6 +4,7 @@
 ///
 /// [`set_rf_frequency`]: crate::SubGhz::set_rf_frequency
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RfFreq {
     buf: [u8; 5],
 }
@@ -0,0 +1,42 @@
+/// RX gain mode.
+///
+/// Argument of [`set_rx_gain`].
+///
+/// [`set_rx_gain`]: crate::SubGhz::set_rx_gain
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum RxGain {
+    /// Power saving gain, this is the reset value.
+    PowerSaving = 0x94,
+    /// Boosted gain, improves sensitivity by around 2 dB at the cost of
+    /// higher current consumption.
+    Boosted = 0x96,
+}
+
+impl RxGain {
+    /// Create a new `RxGain` from bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::RxGain;
+    ///
+    /// assert_eq!(RxGain::from_raw(0x94), Ok(RxGain::PowerSaving));
+    /// assert_eq!(RxGain::from_raw(0x96), Ok(RxGain::Boosted));
+    /// assert_eq!(RxGain::from_raw(0x00), Err(0x00));
+    /// ```
+    pub const fn from_raw(bits: u8) -> Result<RxGain, u8> {
+        match bits {
+            0x94 => Ok(RxGain::PowerSaving),
+            0x96 => Ok(RxGain::Boosted),
+            _ => Err(bits),
+        }
+    }
+}
+
+impl From<RxGain> for u8 {
+    fn from(rx_gain: RxGain) -> Self {
+        rx_gain as u8
+    }
+}
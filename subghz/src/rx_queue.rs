@@ -0,0 +1,389 @@
+use crate::{
+    irq::IRQ_ALL, FskPacketStatus, Irq, LoRaPacketStatus, OpCode, StandbyClk, SubGhz, SubGhzError,
+    Timeout,
+};
+
+/// Received packet with metadata.
+///
+/// Element of [`RxQueue`], `MTU` is the maximum payload length stored.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct RxPacket<const MTU: usize> {
+    buf: [u8; MTU],
+    len: u8,
+    status: [u8; 4],
+    seq: u32,
+    timestamp: Option<u32>,
+}
+
+impl<const MTU: usize> RxPacket<MTU> {
+    const fn new() -> RxPacket<MTU> {
+        RxPacket {
+            buf: [0; MTU],
+            len: 0,
+            status: [0; 4],
+            seq: 0,
+            timestamp: None,
+        }
+    }
+
+    /// Packet payload.
+    pub fn data(&self) -> &[u8] {
+        &self.buf[..usize::from(self.len)]
+    }
+
+    /// Sequence number of the packet.
+    ///
+    /// This is the number of packets received by the radio since
+    /// [`start_continuous_rx`] was called, including packets that were
+    /// dropped or overwritten.
+    ///
+    /// [`start_continuous_rx`]: crate::SubGhz::start_continuous_rx
+    pub const fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// Timestamp of the packet, in the units of the caller's timer.
+    ///
+    /// This is the value passed to [`RxQueue::capture_timestamp`] while the
+    /// packet was being received, or `None` if no timestamp was captured.
+    ///
+    /// Subtract [`PacketTiming::sync_offset_ticks`] to get the start of the
+    /// packet.
+    ///
+    /// [`PacketTiming::sync_offset_ticks`]: crate::PacketTiming::sync_offset_ticks
+    pub const fn timestamp(&self) -> Option<u32> {
+        self.timestamp
+    }
+
+    /// Packet status, decoded for (G)FSK.
+    pub fn fsk_status(&self) -> FskPacketStatus {
+        FskPacketStatus::from(self.status)
+    }
+
+    /// Packet status, decoded for LoRa.
+    pub fn lora_status(&self) -> LoRaPacketStatus {
+        LoRaPacketStatus::from(self.status)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const MTU: usize> defmt::Format for RxPacket<MTU> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "RxPacket {{ seq: {}, timestamp: {}, status: {=[u8]:X}, data: {=[u8]:X} }}",
+            self.seq,
+            self.timestamp,
+            &self.status[..],
+            self.data()
+        )
+    }
+}
+
+/// Fixed-capacity queue of received packets.
+///
+/// Filled by [`poll_continuous_rx`], `SLOTS` is the number of packets
+/// stored and `MTU` is the maximum payload length of each packet.
+///
+/// When the queue is full new packets are dropped, packets that are longer
+/// than `MTU` are also dropped.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::RxQueue;
+///
+/// let queue: RxQueue<4, 64> = RxQueue::new();
+/// assert!(queue.is_empty());
+/// assert_eq!(queue.capacity(), 4);
+/// ```
+///
+/// [`poll_continuous_rx`]: crate::SubGhz::poll_continuous_rx
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RxQueue<const SLOTS: usize, const MTU: usize> {
+    slots: [RxPacket<MTU>; SLOTS],
+    head: usize,
+    len: usize,
+    seq: u32,
+    pkt_rx: u16,
+    dropped: u32,
+    overwritten: u32,
+    errors: u32,
+    capture: Option<u32>,
+}
+
+#[cfg(feature = "defmt")]
+impl<const SLOTS: usize, const MTU: usize> defmt::Format for RxQueue<SLOTS, MTU> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "RxQueue {{ len: {}, dropped: {}, overwritten: {}, errors: {} }}",
+            self.len,
+            self.dropped,
+            self.overwritten,
+            self.errors
+        )
+    }
+}
+
+impl<const SLOTS: usize, const MTU: usize> RxQueue<SLOTS, MTU> {
+    /// Create a new, empty, queue.
+    pub const fn new() -> RxQueue<SLOTS, MTU> {
+        RxQueue {
+            slots: [RxPacket::new(); SLOTS],
+            head: 0,
+            len: 0,
+            seq: 0,
+            pkt_rx: 0,
+            dropped: 0,
+            overwritten: 0,
+            errors: 0,
+            capture: None,
+        }
+    }
+
+    /// Maximum number of packets in the queue.
+    pub const fn capacity(&self) -> usize {
+        SLOTS
+    }
+
+    /// Number of packets in the queue.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    pub const fn is_full(&self) -> bool {
+        self.len == SLOTS
+    }
+
+    /// Remove the oldest packet from the queue.
+    pub fn pop(&mut self) -> Option<RxPacket<MTU>> {
+        if self.is_empty() {
+            None
+        } else {
+            let pkt: RxPacket<MTU> = self.slots[self.head];
+            self.head = (self.head + 1) % SLOTS;
+            self.len -= 1;
+            Some(pkt)
+        }
+    }
+
+    /// Number of packets dropped because the queue was full, or because the
+    /// packet was longer than `MTU`.
+    pub const fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Number of packets overwritten in the radio buffer before they were
+    /// read.
+    ///
+    /// This is derived from the radio packet statistics, a packet is
+    /// overwritten when more than one packet is received between calls to
+    /// [`poll_continuous_rx`].
+    ///
+    /// [`poll_continuous_rx`]: crate::SubGhz::poll_continuous_rx
+    pub const fn overwritten(&self) -> u32 {
+        self.overwritten
+    }
+
+    /// Number of packets received with a CRC, header, or other packet error.
+    pub const fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Reset the dropped, overwritten, and error counters to zero.
+    pub fn reset_counters(&mut self) {
+        self.dropped = 0;
+        self.overwritten = 0;
+        self.errors = 0;
+    }
+
+    /// Capture a timestamp for the packet being received.
+    ///
+    /// Call this from the radio interrupt handler when [`Irq::SyncDetected`]
+    /// ((G)FSK) or [`Irq::HeaderValid`] (LoRa) is asserted, with the value of
+    /// a free-running timer such as the DWT cycle counter, or a timer input
+    /// capture.
+    /// [`capture_rx_timestamp`] does this for the radio interrupts.
+    /// The timestamp is attached to the next packet added to the queue by
+    /// [`poll_continuous_rx`].
+    ///
+    /// See [`PacketTiming`] for the offset from the start of the packet to
+    /// the interrupt.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// # fn cycle_count() -> u32 { 0 }
+    /// use stm32wl_hal_subghz::{Irq, RxQueue};
+    ///
+    /// let mut queue: RxQueue<8, 64> = RxQueue::new();
+    ///
+    /// // in the radio interrupt handler
+    /// let timestamp: u32 = cycle_count();
+    /// let (_, irq) = sg.irq_status()?;
+    /// if irq & Irq::SyncDetected.mask() != 0 {
+    ///     queue.capture_timestamp(timestamp);
+    ///     sg.clear_irq_status(Irq::SyncDetected.mask())?;
+    /// }
+    /// sg.poll_continuous_rx(&mut queue)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`Irq::SyncDetected`]: crate::Irq::SyncDetected
+    /// [`Irq::HeaderValid`]: crate::Irq::HeaderValid
+    /// [`PacketTiming`]: crate::PacketTiming
+    /// [`poll_continuous_rx`]: crate::SubGhz::poll_continuous_rx
+    /// [`capture_rx_timestamp`]: crate::SubGhz::capture_rx_timestamp
+    pub fn capture_timestamp(&mut self, timestamp: u32) {
+        self.capture = Some(timestamp);
+    }
+
+    /// Get the next free slot, the caller must check the queue is not full.
+    fn push(&mut self) -> &mut RxPacket<MTU> {
+        let idx: usize = (self.head + self.len) % SLOTS;
+        self.len += 1;
+        &mut self.slots[idx]
+    }
+}
+
+impl<const SLOTS: usize, const MTU: usize> Default for RxQueue<SLOTS, MTU> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Continuous receive.
+impl SubGhz {
+    /// Start continuous reception into a [`RxQueue`].
+    ///
+    /// This resets the radio packet statistics and the sequence number of
+    /// the queue, then sets the radio in continuous RX mode with
+    /// [`Timeout::MAX`].
+    ///
+    /// The packet type, modulation parameters, and packet parameters must be
+    /// configured before calling this function.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::RxQueue;
+    ///
+    /// let mut queue: RxQueue<8, 64> = RxQueue::new();
+    ///
+    /// sg.start_continuous_rx(&mut queue)?;
+    /// loop {
+    ///     sg.poll_continuous_rx(&mut queue)?;
+    ///     while let Some(pkt) = queue.pop() {
+    ///         // ... do things with pkt.data()
+    ///     }
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn start_continuous_rx<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut RxQueue<SLOTS, MTU>,
+    ) -> Result<(), SubGhzError> {
+        queue.seq = 0;
+        queue.pkt_rx = 0;
+        queue.capture = None;
+        self.set_standby(StandbyClk::Rc)?;
+        self.reset_stats()?;
+        self.clear_irq_status(IRQ_ALL)?;
+        self.set_rx(Timeout::MAX)
+    }
+
+    /// Capture a timestamp for the packet being received from the radio
+    /// interrupt handler.
+    ///
+    /// If [`Irq::SyncDetected`] or [`Irq::HeaderValid`] are asserted this
+    /// passes `timestamp` to [`RxQueue::capture_timestamp`] and clears both
+    /// interrupts.
+    ///
+    /// `timestamp` should be read on entry to the interrupt handler, before
+    /// any radio commands, for example with [`dwt_cycle_count`].
+    ///
+    /// Returns `true` if a timestamp was captured.
+    ///
+    /// # Example
+    ///
+    /// See [`dwt_cycle_count`].
+    ///
+    /// [`dwt_cycle_count`]: crate::dwt_cycle_count
+    pub fn capture_rx_timestamp<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut RxQueue<SLOTS, MTU>,
+        timestamp: u32,
+    ) -> Result<bool, SubGhzError> {
+        const SYNC_IRQ: u16 = Irq::SyncDetected.mask() | Irq::HeaderValid.mask();
+        let (_, irq) = self.irq_status()?;
+        if irq & SYNC_IRQ == 0 {
+            return Ok(false);
+        }
+        queue.capture_timestamp(timestamp);
+        self.clear_irq_status(SYNC_IRQ)?;
+        Ok(true)
+    }
+
+    /// Copy a received packet into the queue, and re-arm reception.
+    ///
+    /// Call this when the [`Irq::RxDone`] interrupt is asserted, or
+    /// periodically.
+    ///
+    /// Returns `true` if a packet was added to the queue.
+    ///
+    /// # Example
+    ///
+    /// See [`start_continuous_rx`](Self::start_continuous_rx).
+    pub fn poll_continuous_rx<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut RxQueue<SLOTS, MTU>,
+    ) -> Result<bool, SubGhzError> {
+        let (_, irq) = self.irq_status()?;
+        if irq & Irq::RxDone.mask() == 0 {
+            return Ok(false);
+        }
+
+        // the packet counter is at the same offset for (G)FSK and LoRa
+        let pkt_rx: u16 = self.fsk_stats()?.pkt_rx();
+        let delta: u16 = pkt_rx.wrapping_sub(queue.pkt_rx);
+        queue.pkt_rx = pkt_rx;
+        queue.seq = queue.seq.wrapping_add(u32::from(delta));
+        queue.overwritten = queue
+            .overwritten
+            .saturating_add(u32::from(delta.saturating_sub(1)));
+
+        let timestamp: Option<u32> = queue.capture.take();
+        let mut added: bool = false;
+        if irq & (Irq::Err.mask() | Irq::HeaderErr.mask()) != 0 {
+            queue.errors = queue.errors.saturating_add(1);
+        } else {
+            let (_, len, ptr) = self.rx_buffer_status()?;
+            if usize::from(len) > MTU || queue.is_full() {
+                queue.dropped = queue.dropped.saturating_add(1);
+            } else {
+                let status: [u8; 4] = self.read_n(OpCode::GetPacketStatus)?;
+                let seq: u32 = queue.seq;
+                let slot: &mut RxPacket<MTU> = queue.push();
+                self.read_buffer(ptr, &mut slot.buf[..usize::from(len)])?;
+                slot.len = len;
+                slot.status = status;
+                slot.seq = seq;
+                slot.timestamp = timestamp;
+                added = true;
+            }
+        }
+
+        self.clear_irq_status(IRQ_ALL)?;
+        self.set_rx(Timeout::MAX)?;
+        Ok(added)
+    }
+}
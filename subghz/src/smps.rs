@@ -0,0 +1,196 @@
+use crate::PaSel;
+
+/// SMPS maximum drive capability.
+///
+/// Argument of [`SmpsCfg::set_drv`].
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum SmpsDrv {
+    /// 20 mA.
+    ///
+    /// Not used by the ST radio driver.
+    Milli20 = 0b00,
+    /// 40 mA.
+    Milli40 = 0b01,
+    /// 60 mA.
+    Milli60 = 0b10,
+    /// 100 mA.
+    ///
+    /// Not used by the ST radio driver.
+    Milli100 = 0b11,
+}
+
+impl SmpsDrv {
+    /// Create a new `SmpsDrv` from bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SmpsDrv;
+    ///
+    /// assert_eq!(SmpsDrv::from_raw(0b10), SmpsDrv::Milli60);
+    /// assert_eq!(SmpsDrv::from_raw(0b111), SmpsDrv::Milli100);
+    /// ```
+    pub const fn from_raw(bits: u8) -> SmpsDrv {
+        match bits & 0b11 {
+            0b00 => SmpsDrv::Milli20,
+            0b01 => SmpsDrv::Milli40,
+            0b10 => SmpsDrv::Milli60,
+            _ => SmpsDrv::Milli100,
+        }
+    }
+
+    /// Drive for a transmission with the given PA.
+    ///
+    /// These are the values used by the ST radio driver in STM32CubeWL
+    /// (`SMPS_DRIVE_SETTING_DEFAULT` and `SMPS_DRIVE_SETTING_MAX` in
+    /// `radio_driver.c`), independent of the output power:
+    ///
+    /// * LP PA: 40 mA.
+    /// * HP PA: 60 mA, up to +22 dBm.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{PaSel, SmpsDrv};
+    ///
+    /// assert_eq!(SmpsDrv::for_tx(PaSel::Lp), SmpsDrv::Milli40);
+    /// assert_eq!(SmpsDrv::for_tx(PaSel::Hp), SmpsDrv::Milli60);
+    /// ```
+    pub const fn for_tx(pa: PaSel) -> SmpsDrv {
+        match pa {
+            PaSel::Lp => SmpsDrv::Milli40,
+            PaSel::Hp => SmpsDrv::Milli60,
+        }
+    }
+}
+
+/// SMPS configuration.
+///
+/// Argument of [`set_smps_cfg`].
+///
+/// This only has an effect when the SMPS is selected with
+/// [`set_regulator_mode`].
+///
+/// [`set_smps_cfg`]: crate::SubGhz::set_smps_cfg
+/// [`set_regulator_mode`]: crate::SubGhz::set_regulator_mode
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SmpsCfg {
+    clk_det: bool,
+    drv: SmpsDrv,
+}
+
+impl SmpsCfg {
+    /// Create a new `SmpsCfg` struct.
+    ///
+    /// This is the same as `default`, but in a `const` function.
+    ///
+    /// The default is the [`low_power`] configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SmpsCfg;
+    ///
+    /// const SMPS_CFG: SmpsCfg = SmpsCfg::new();
+    /// assert_eq!(SMPS_CFG, SmpsCfg::default());
+    /// assert_eq!(SMPS_CFG, SmpsCfg::low_power());
+    /// ```
+    ///
+    /// [`low_power`]: SmpsCfg::low_power
+    pub const fn new() -> SmpsCfg {
+        SmpsCfg::low_power()
+    }
+
+    /// Low power SMPS configuration.
+    ///
+    /// Clock detection enabled with a 40 mA drive, the ST radio driver
+    /// restores this configuration after a transmission, and uses it for RX,
+    /// LP PA TX, and before entering sleep.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{SmpsCfg, SmpsDrv};
+    ///
+    /// const SMPS_CFG: SmpsCfg = SmpsCfg::low_power();
+    /// assert_eq!(SMPS_CFG.drv(), SmpsDrv::Milli40);
+    /// assert!(SMPS_CFG.clk_det_en());
+    /// ```
+    pub const fn low_power() -> SmpsCfg {
+        SmpsCfg {
+            clk_det: true,
+            drv: SmpsDrv::Milli40,
+        }
+    }
+
+    /// SMPS configuration for a transmission with the given PA.
+    ///
+    /// Clock detection enabled with the drive from [`SmpsDrv::for_tx`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{PaSel, SmpsCfg, SmpsDrv};
+    ///
+    /// const SMPS_CFG: SmpsCfg = SmpsCfg::for_tx(PaSel::Hp);
+    /// assert_eq!(SMPS_CFG.drv(), SmpsDrv::Milli60);
+    /// assert_eq!(SmpsCfg::for_tx(PaSel::Lp), SmpsCfg::low_power());
+    /// ```
+    pub const fn for_tx(pa: PaSel) -> SmpsCfg {
+        SmpsCfg::low_power().set_drv(SmpsDrv::for_tx(pa))
+    }
+
+    /// Enable or disable the SMPS clock detection.
+    ///
+    /// Clock detection protects the SMPS by switching to the LDO if the SMPS
+    /// clock fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SmpsCfg;
+    ///
+    /// const SMPS_CFG: SmpsCfg = SmpsCfg::new().set_clk_det_en(false);
+    /// # assert!(!SMPS_CFG.clk_det_en());
+    /// ```
+    #[must_use = "set_clk_det_en returns a modified SmpsCfg"]
+    pub const fn set_clk_det_en(mut self, en: bool) -> SmpsCfg {
+        self.clk_det = en;
+        self
+    }
+
+    /// Returns `true` if clock detection is enabled.
+    pub const fn clk_det_en(&self) -> bool {
+        self.clk_det
+    }
+
+    /// Set the SMPS maximum drive capability.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{SmpsCfg, SmpsDrv};
+    ///
+    /// const SMPS_CFG: SmpsCfg = SmpsCfg::new().set_drv(SmpsDrv::Milli60);
+    /// # assert_eq!(SMPS_CFG.drv(), SmpsDrv::Milli60);
+    /// ```
+    #[must_use = "set_drv returns a modified SmpsCfg"]
+    pub const fn set_drv(mut self, drv: SmpsDrv) -> SmpsCfg {
+        self.drv = drv;
+        self
+    }
+
+    /// SMPS maximum drive capability.
+    pub const fn drv(&self) -> SmpsDrv {
+        self.drv
+    }
+}
+
+impl Default for SmpsCfg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,10 +3,12 @@ use crate::Status;
 /// [Typestate] for LoRa stats.
 ///
 /// [Typestate]: https://docs.rust-embedded.org/book/static-guarantees/typestate-programming.html
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LoRaStats;
 /// [Typestate] for FSK stats.
 ///
 /// [Typestate]: https://docs.rust-embedded.org/book/static-guarantees/typestate-programming.html
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FskStats;
 
 /// Packet statistics.
@@ -154,3 +156,31 @@ impl core::fmt::Display for Stats<FskStats> {
             .finish()
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Stats<FskStats> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Stats {{ status: {}, pkt_rx: {}, pkt_crc: {}, pkt_len_err: {} }}",
+            self.status(),
+            self.pkt_rx(),
+            self.pkt_crc(),
+            self.pkt_len_err(),
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Stats<LoRaStats> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Stats {{ status: {}, pkt_rx: {}, pkt_crc: {}, pkt_hdr_err: {} }}",
+            self.status(),
+            self.pkt_rx(),
+            self.pkt_crc(),
+            self.pkt_hdr_err(),
+        )
+    }
+}
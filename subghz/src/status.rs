@@ -6,6 +6,7 @@
 /// This is returned by [`Status::mode`].
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum StatusMode {
     /// Standby mode with RC 13MHz.
     StandbyRc = 0x2,
@@ -55,6 +56,7 @@ impl StatusMode {
 /// This is returned by [`Status::cmd`].
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CmdStatus {
     /// Data available to host.
     ///
@@ -186,3 +188,15 @@ impl core::fmt::Display for Status {
             .finish()
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Status {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "Status {{ mode: {}, cmd: {} }}",
+            self.mode(),
+            self.cmd(),
+        )
+    }
+}
@@ -0,0 +1,531 @@
+use crate::{CrcType, GenericCrc, GenericPacketParams, HeaderType, PreambleDetection, Whitening};
+
+/// Length of the SUN-FSK PHY header (PHR) in bytes.
+pub const SUN_PHR_LEN: usize = 2;
+
+/// Maximum SUN-FSK PSDU length in bytes, including the FCS.
+pub const SUN_PSDU_MAX: usize = 2047;
+
+/// PN9 seed for SUN-FSK data whitening.
+const SUN_PN9_SEED: u16 = 0x1FF;
+
+/// Whiten or de-whiten a PSDU in transmission order.
+///
+/// The PN9 sequence from [`Whitening`] is LSB first, the PSDU bytes are sent
+/// MSB first.
+fn whiten(psdu: &mut [u8]) {
+    let mut pn9: Whitening = Whitening::new(SUN_PN9_SEED);
+    psdu.iter_mut()
+        .for_each(|air| *air ^= pn9.next_byte().reverse_bits())
+}
+
+/// 2-octet FCS, ITU-T CRC-16 computed over the PSDU in transmission order.
+const SUN_CRC16: GenericCrc = GenericCrc::new(CrcType::Byte2)
+    .set_polynomial(0x1021)
+    .set_initial(0x0000);
+
+/// Frame check sequence type.
+///
+/// Argument of [`SunFsk::set_fcs`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SunFcs {
+    /// 4-octet FCS, IEEE 802.3 CRC-32.
+    Crc32,
+    /// 2-octet FCS, ITU-T CRC-16.
+    Crc16,
+}
+
+impl SunFcs {
+    /// Length of the FCS in bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SunFcs;
+    ///
+    /// assert_eq!(SunFcs::Crc32.fcs_len(), 4);
+    /// assert_eq!(SunFcs::Crc16.fcs_len(), 2);
+    /// ```
+    pub const fn fcs_len(&self) -> usize {
+        match self {
+            SunFcs::Crc32 => 4,
+            SunFcs::Crc16 => 2,
+        }
+    }
+
+    /// Compute the FCS of a PSDU in transmission order.
+    ///
+    /// The FCS is transmitted MSB first.
+    const fn checksum(&self, air: &[u8]) -> u32 {
+        match self {
+            SunFcs::Crc32 => crc32(air),
+            SunFcs::Crc16 => SUN_CRC16.checksum(air) as u32,
+        }
+    }
+}
+
+/// IEEE 802.3 CRC-32 computed MSB first, as the bits are transmitted.
+const fn crc32(data: &[u8]) -> u32 {
+    const POLY: u32 = 0x04C1_1DB7;
+    let mut crc: u32 = 0xFFFF_FFFF;
+    let mut idx: usize = 0;
+    while idx < data.len() {
+        crc ^= (data[idx] as u32) << 24;
+        let mut bit: u8 = 0;
+        while bit < 8 {
+            if crc & (1 << 31) != 0 {
+                crc = (crc << 1) ^ POLY;
+            } else {
+                crc <<= 1;
+            }
+            bit += 1;
+        }
+        idx += 1;
+    }
+    !crc
+}
+
+/// SUN-FSK start of frame delimiter (SFD).
+///
+/// Argument of [`SunFsk::set_sfd`], selected by the `phyMRFSKSFD` PIB
+/// attribute.
+///
+/// Only the SFDs for uncoded (no FEC) frames are supported.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SunSfd {
+    /// `phyMRFSKSFD` = 0, `0x904E`.
+    Sfd0,
+    /// `phyMRFSKSFD` = 1, `0x7A0E`.
+    Sfd1,
+}
+
+impl SunSfd {
+    /// SFD bits in transmission order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SunSfd;
+    ///
+    /// assert_eq!(SunSfd::Sfd0.bits(), 0x904E);
+    /// assert_eq!(SunSfd::Sfd1.bits(), 0x7A0E);
+    /// ```
+    pub const fn bits(&self) -> u16 {
+        match self {
+            SunSfd::Sfd0 => 0x904E,
+            SunSfd::Sfd1 => 0x7A0E,
+        }
+    }
+}
+
+/// SUN-FSK errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SunFskError {
+    /// The PSDU is longer than [`SUN_PSDU_MAX`].
+    PsduLen,
+    /// The output buffer is too small, or the received frame is shorter than
+    /// the length in the PHR.
+    BufferTooSmall,
+    /// The PHR is invalid, or has the mode switch bit set.
+    Phr,
+    /// FCS mismatch.
+    Fcs,
+}
+
+/// SUN-FSK PHY header (PHR).
+///
+/// The PHR is transmitted MSB first:
+///
+/// | Bits  | Field                              |
+/// |-------|------------------------------------|
+/// |    15 | Mode switch                        |
+/// | 14:13 | Reserved                           |
+/// |    12 | FCS type, 0 = 4-octet, 1 = 2-octet |
+/// |    11 | Data whitening                     |
+/// |  10:0 | PSDU length, including the FCS     |
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{SunFcs, SunPhr};
+///
+/// const PHR: SunPhr = SunPhr::new(SunFcs::Crc16, true, 20);
+/// assert_eq!(PHR.to_bytes(), [0x18, 0x14]);
+/// assert_eq!(SunPhr::from_bytes([0x18, 0x14]), Ok(PHR));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SunPhr {
+    fcs: SunFcs,
+    whitening: bool,
+    psdu_len: u16,
+}
+
+impl SunPhr {
+    const MS: u16 = 1 << 15;
+    const FCS: u16 = 1 << 12;
+    const DW: u16 = 1 << 11;
+    const LEN_MASK: u16 = 0x07FF;
+
+    /// Create a new PHR.
+    ///
+    /// `psdu_len` is the length of the PSDU in bytes, including the FCS,
+    /// lengths above [`SUN_PSDU_MAX`] are truncated to 11 bits.
+    pub const fn new(fcs: SunFcs, whitening: bool, psdu_len: u16) -> SunPhr {
+        SunPhr {
+            fcs,
+            whitening,
+            psdu_len: psdu_len & Self::LEN_MASK,
+        }
+    }
+
+    /// Parse a received PHR.
+    ///
+    /// Returns an error if the mode switch bit is set, or if the PSDU length
+    /// is shorter than the FCS.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{SunFskError, SunPhr};
+    ///
+    /// // mode switch
+    /// assert_eq!(SunPhr::from_bytes([0x80, 0x10]), Err(SunFskError::Phr));
+    /// // 3 byte PSDU with a 4 byte FCS
+    /// assert_eq!(SunPhr::from_bytes([0x00, 0x03]), Err(SunFskError::Phr));
+    /// ```
+    pub const fn from_bytes(bytes: [u8; SUN_PHR_LEN]) -> Result<SunPhr, SunFskError> {
+        let bits: u16 = u16::from_be_bytes(bytes);
+        if bits & Self::MS != 0 {
+            return Err(SunFskError::Phr);
+        }
+        let fcs: SunFcs = if bits & Self::FCS != 0 {
+            SunFcs::Crc16
+        } else {
+            SunFcs::Crc32
+        };
+        let psdu_len: u16 = bits & Self::LEN_MASK;
+        if (psdu_len as usize) < fcs.fcs_len() {
+            return Err(SunFskError::Phr);
+        }
+        Ok(SunPhr {
+            fcs,
+            whitening: bits & Self::DW != 0,
+            psdu_len,
+        })
+    }
+
+    /// PHR bytes in transmission order.
+    pub const fn to_bytes(&self) -> [u8; SUN_PHR_LEN] {
+        let mut bits: u16 = self.psdu_len;
+        if let SunFcs::Crc16 = self.fcs {
+            bits |= Self::FCS;
+        }
+        if self.whitening {
+            bits |= Self::DW;
+        }
+        bits.to_be_bytes()
+    }
+
+    /// FCS type.
+    pub const fn fcs(&self) -> SunFcs {
+        self.fcs
+    }
+
+    /// Returns `true` if data whitening is enabled.
+    pub const fn whitening_en(&self) -> bool {
+        self.whitening
+    }
+
+    /// Length of the PSDU in bytes, including the FCS.
+    pub const fn psdu_len(&self) -> u16 {
+        self.psdu_len
+    }
+
+    /// Length of the frame in bytes, PHR and PSDU.
+    pub const fn frame_len(&self) -> usize {
+        SUN_PHR_LEN + (self.psdu_len as usize)
+    }
+}
+
+/// IEEE 802.15.4g SUN-FSK PHY framing.
+///
+/// The SUN-FSK PHY sends the PHR, then the PSDU (payload and FCS) optionally
+/// whitened with PN9.
+/// The generic packet engine can not skip the PHR, the hardware CRC and
+/// whitening would include it, so the frame is built in software and sent
+/// as a fixed length payload with the hardware CRC and whitening disabled:
+///
+/// * [`packet_params`] for [`set_packet_params`].
+/// * [`sync_word`] for [`set_sync_word`], the SFD.
+/// * [`encode`] and [`decode`] for the frame, using the [`Whitening`] and
+///   [`GenericCrc`] models of the radio engines.
+///
+/// SUN-FSK sends PSDU octets LSB first, the radio sends MSB first, the PSDU
+/// is bit reversed by [`encode`] and [`decode`].
+///
+/// The radio buffer limits frames to 255 bytes including the PHR.
+/// Mode switch and FEC are not supported.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{SunFcs, SunFsk};
+///
+/// const SUN: SunFsk = SunFsk::new().set_fcs(SunFcs::Crc16);
+///
+/// let mut frame: [u8; 32] = [0; 32];
+/// let len: usize = SUN.encode(b"hello", &mut frame).unwrap();
+/// assert_eq!(len, 2 + 5 + 2);
+///
+/// // ... sg.write_buffer(0, &frame[..len])
+///
+/// assert_eq!(SunFsk::decode(&mut frame[..len]), Ok(&b"hello"[..]));
+/// ```
+///
+/// [`packet_params`]: SunFsk::packet_params
+/// [`sync_word`]: SunFsk::sync_word
+/// [`encode`]: SunFsk::encode
+/// [`decode`]: SunFsk::decode
+/// [`set_packet_params`]: crate::SubGhz::set_packet_params
+/// [`set_sync_word`]: crate::SubGhz::set_sync_word
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SunFsk {
+    fcs: SunFcs,
+    whitening: bool,
+    sfd: SunSfd,
+    preamble_len: u16,
+}
+
+impl SunFsk {
+    /// Create a new `SunFsk` struct.
+    ///
+    /// This is the same as `default`, but in a `const` function.
+    ///
+    /// The default is a 4-octet FCS, data whitening enabled, [`SunSfd::Sfd0`],
+    /// and a 4 octet preamble.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::SunFsk;
+    ///
+    /// const SUN: SunFsk = SunFsk::new();
+    /// assert_eq!(SUN, SunFsk::default());
+    /// ```
+    pub const fn new() -> SunFsk {
+        SunFsk {
+            fcs: SunFcs::Crc32,
+            whitening: true,
+            sfd: SunSfd::Sfd0,
+            preamble_len: 4,
+        }
+    }
+
+    /// Set the FCS type.
+    #[must_use = "set_fcs returns a modified SunFsk"]
+    pub const fn set_fcs(mut self, fcs: SunFcs) -> SunFsk {
+        self.fcs = fcs;
+        self
+    }
+
+    /// FCS type.
+    pub const fn fcs(&self) -> SunFcs {
+        self.fcs
+    }
+
+    /// Enable or disable data whitening of transmitted frames.
+    #[must_use = "set_whitening_en returns a modified SunFsk"]
+    pub const fn set_whitening_en(mut self, en: bool) -> SunFsk {
+        self.whitening = en;
+        self
+    }
+
+    /// Returns `true` if data whitening of transmitted frames is enabled.
+    pub const fn whitening_en(&self) -> bool {
+        self.whitening
+    }
+
+    /// Set the start of frame delimiter.
+    #[must_use = "set_sfd returns a modified SunFsk"]
+    pub const fn set_sfd(mut self, sfd: SunSfd) -> SunFsk {
+        self.sfd = sfd;
+        self
+    }
+
+    /// Start of frame delimiter.
+    pub const fn sfd(&self) -> SunSfd {
+        self.sfd
+    }
+
+    /// Set the preamble length in octets (`phyFSKPreambleLength`).
+    ///
+    /// Values above 8191 saturate.
+    #[must_use = "set_preamble_len returns a modified SunFsk"]
+    pub const fn set_preamble_len(mut self, octets: u16) -> SunFsk {
+        self.preamble_len = if octets > u16::MAX / 8 {
+            u16::MAX / 8
+        } else {
+            octets
+        };
+        self
+    }
+
+    /// Preamble length in octets.
+    pub const fn preamble_len(&self) -> u16 {
+        self.preamble_len
+    }
+
+    /// Sync word for [`set_sync_word`], the SFD followed by zeros.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{SunFsk, SunSfd};
+    ///
+    /// const SUN: SunFsk = SunFsk::new().set_sfd(SunSfd::Sfd1);
+    /// # assert_eq!(SUN.sync_word(), [0x7A, 0x0E, 0, 0, 0, 0, 0, 0]);
+    ///
+    /// sg.set_sync_word(&SUN.sync_word())?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`set_sync_word`]: crate::SubGhz::set_sync_word
+    pub const fn sync_word(&self) -> [u8; 8] {
+        let sfd: [u8; 2] = self.sfd.bits().to_be_bytes();
+        [sfd[0], sfd[1], 0, 0, 0, 0, 0, 0]
+    }
+
+    /// Packet parameters for a frame of `frame_len` bytes, PHR and PSDU.
+    ///
+    /// For reception use the longest expected frame, [`decode`] uses the
+    /// length in the PHR and ignores trailing bytes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{
+    ///     FskBandwidth, FskBitrate, FskFdev, FskModParams, FskPulseShape, PacketType, SunFsk,
+    ///     Timeout,
+    /// };
+    ///
+    /// // Wi-SUN operating mode 1a, 50 kb/s with modulation index 0.5
+    /// const MOD_PARAMS: FskModParams = FskModParams::new()
+    ///     .set_bitrate(FskBitrate::from_bps(50_000))
+    ///     .set_pulse_shape(FskPulseShape::Bt05)
+    ///     .set_bandwidth(FskBandwidth::Bw117)
+    ///     .set_fdev(FskFdev::from_hertz(12_500));
+    /// const SUN: SunFsk = SunFsk::new().set_preamble_len(8);
+    ///
+    /// sg.set_packet_type(PacketType::Fsk)?;
+    /// sg.set_fsk_mod_params(&MOD_PARAMS)?;
+    /// sg.set_sync_word(&SUN.sync_word())?;
+    ///
+    /// let mut frame: [u8; 255] = [0; 255];
+    /// let len: usize = SUN.encode(b"wi-sun", &mut frame).unwrap();
+    /// sg.set_packet_params(&SUN.packet_params(len as u8))?;
+    /// sg.write_buffer(0, &frame[..len])?;
+    /// sg.set_tx(Timeout::DISABLED)?;
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`decode`]: SunFsk::decode
+    pub const fn packet_params(&self, frame_len: u8) -> GenericPacketParams {
+        GenericPacketParams::new()
+            .set_preamble_len(8 * self.preamble_len)
+            .set_preamble_detection(PreambleDetection::Bit16)
+            .set_sync_word_len(16)
+            .set_header_type(HeaderType::Fixed)
+            .set_payload_len(frame_len)
+            .set_crc_type(CrcType::Disabled)
+            .set_whitening_enable(false)
+    }
+
+    /// Encode a frame, returns the length of the frame.
+    ///
+    /// `frame` must be at least [`SUN_PHR_LEN`] + `payload.len()` + FCS
+    /// length bytes.
+    pub fn encode(&self, payload: &[u8], frame: &mut [u8]) -> Result<usize, SunFskError> {
+        let psdu_len: usize = payload.len() + self.fcs.fcs_len();
+        if psdu_len > SUN_PSDU_MAX {
+            return Err(SunFskError::PsduLen);
+        }
+        let phr: SunPhr = SunPhr::new(self.fcs, self.whitening, psdu_len as u16);
+        let frame_len: usize = phr.frame_len();
+        if frame.len() < frame_len {
+            return Err(SunFskError::BufferTooSmall);
+        }
+
+        frame[..SUN_PHR_LEN].copy_from_slice(&phr.to_bytes());
+        let psdu: &mut [u8] = &mut frame[SUN_PHR_LEN..frame_len];
+        let (data, fcs) = psdu.split_at_mut(payload.len());
+        data.iter_mut()
+            .zip(payload.iter())
+            .for_each(|(air, byte)| *air = byte.reverse_bits());
+        let crc: [u8; 4] = self.fcs.checksum(data).to_be_bytes();
+        fcs.copy_from_slice(&crc[4 - self.fcs.fcs_len()..]);
+        if self.whitening {
+            whiten(psdu);
+        }
+        Ok(frame_len)
+    }
+
+    /// Decode a received frame in place, returns the payload.
+    ///
+    /// The FCS type and data whitening are read from the PHR.
+    /// Bytes after the PSDU length in the PHR are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{SunFskError, SunFsk};
+    ///
+    /// let mut frame: [u8; 64] = [0; 64];
+    /// let len: usize = SunFsk::new().encode(b"meter 42", &mut frame).unwrap();
+    ///
+    /// let mut rx: [u8; 64] = frame;
+    /// assert_eq!(SunFsk::decode(&mut rx), Ok(&b"meter 42"[..]));
+    ///
+    /// let mut rx: [u8; 64] = frame;
+    /// rx[4] ^= 0x01;
+    /// assert_eq!(SunFsk::decode(&mut rx), Err(SunFskError::Fcs));
+    ///
+    /// let mut rx: [u8; 64] = frame;
+    /// assert_eq!(SunFsk::decode(&mut rx[..len - 1]), Err(SunFskError::BufferTooSmall));
+    /// ```
+    pub fn decode(frame: &mut [u8]) -> Result<&[u8], SunFskError> {
+        if frame.len() < SUN_PHR_LEN {
+            return Err(SunFskError::BufferTooSmall);
+        }
+        let phr: SunPhr = SunPhr::from_bytes([frame[0], frame[1]])?;
+        let frame_len: usize = phr.frame_len();
+        if frame.len() < frame_len {
+            return Err(SunFskError::BufferTooSmall);
+        }
+
+        let psdu: &mut [u8] = &mut frame[SUN_PHR_LEN..frame_len];
+        if phr.whitening_en() {
+            whiten(psdu);
+        }
+        let data_len: usize = psdu.len() - phr.fcs().fcs_len();
+        let (data, fcs) = psdu.split_at_mut(data_len);
+        let expected: u32 = fcs.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b));
+        if phr.fcs().checksum(data) != expected {
+            return Err(SunFskError::Fcs);
+        }
+        data.iter_mut().for_each(|byte| *byte = byte.reverse_bits());
+        Ok(data)
+    }
+}
+
+impl Default for SunFsk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,6 @@
 use core::time::Duration;
 
-use crate::ValueError;
+use crate::{FskModParams, LoRaBandwidth, LoRaModParams, SpreadingFactor, ValueError};
 
 const fn abs_diff(a: u64, b: u64) -> u64 {
     if a > b {
@@ -10,6 +10,23 @@ const fn abs_diff(a: u64, b: u64) -> u64 {
     }
 }
 
+/// Number of timeout bits per second.
+const BITS_PER_SEC: u128 = 64_000;
+
+/// Duration of a LoRa symbol as a fraction of seconds.
+const fn lora_symbol(mod_params: &LoRaModParams) -> (u128, u128) {
+    // the setters only write valid values, fall back to the longest symbol
+    let sf: SpreadingFactor = match mod_params.sf() {
+        Ok(sf) => sf,
+        Err(_) => SpreadingFactor::Sf12,
+    };
+    let bw: LoRaBandwidth = match mod_params.bw() {
+        Ok(bw) => bw,
+        Err(_) => LoRaBandwidth::Bw7,
+    };
+    (1 << sf.factor(), bw.hertz() as u128)
+}
+
 /// Timeout argument.
 ///
 /// This is used by:
@@ -24,6 +41,7 @@ const fn abs_diff(a: u64, b: u64) -> u64 {
 /// [`set_tx`]: crate::SubGhz::set_tx
 /// [`TcxoMode`]: crate::TcxoMode
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Timeout {
     bits: u32,
 }
@@ -248,6 +266,111 @@ impl Timeout {
         }
     }
 
+    /// Create a new timeout from a number of LoRa symbols.
+    ///
+    /// The symbol duration is derived from the spreading factor and bandwidth
+    /// in `mod_params`.
+    /// The timeout is rounded up to the next timeout step, and saturates at
+    /// [`Timeout::MAX`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{LoRaBandwidth, LoRaModParams, SpreadingFactor, Timeout};
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new()
+    ///     .set_sf(SpreadingFactor::Sf7)
+    ///     .set_bw(LoRaBandwidth::Bw125);
+    ///
+    /// // 1.024 ms per symbol, rounded up to a multiple of 15.625 µs
+    /// const TIMEOUT: Timeout = Timeout::from_lora_symbols(8, &MOD_PARAMS);
+    /// assert_eq!(TIMEOUT.as_duration(), Duration::from_nanos(8_203_125));
+    /// assert_eq!(TIMEOUT.as_lora_symbols(&MOD_PARAMS), 8);
+    /// ```
+    pub const fn from_lora_symbols(n: u32, mod_params: &LoRaModParams) -> Timeout {
+        let (num, den): (u128, u128) = lora_symbol(mod_params);
+        Timeout::from_fraction((n as u128) * num, den)
+    }
+
+    /// Create a new timeout from a number of (G)FSK bits.
+    ///
+    /// The bit duration is derived from the bitrate in `mod_params`.
+    /// The timeout is rounded up to the next timeout step, and saturates at
+    /// [`Timeout::MAX`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use stm32wl_hal_subghz::{FskBitrate, FskModParams, Timeout};
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new().set_bitrate(FskBitrate::from_bps(50_000));
+    ///
+    /// // 20 µs per bit, rounded up to a multiple of 15.625 µs
+    /// const TIMEOUT: Timeout = Timeout::from_fsk_bits(64, &MOD_PARAMS);
+    /// assert_eq!(TIMEOUT.as_duration(), Duration::from_nanos(1_281_250));
+    /// assert_eq!(TIMEOUT.as_fsk_bits(&MOD_PARAMS), 64);
+    /// ```
+    pub const fn from_fsk_bits(n: u32, mod_params: &FskModParams) -> Timeout {
+        Timeout::from_fraction(n as u128, mod_params.bitrate().as_bps() as u128)
+    }
+
+    /// Timeout of `num` / `den` seconds, rounded up.
+    const fn from_fraction(num: u128, den: u128) -> Timeout {
+        if den == 0 {
+            return Timeout::MAX;
+        }
+        let prod: u128 = num * BITS_PER_SEC;
+        let quot: u128 = prod / den;
+        let bits: u128 = if quot * den < prod { quot + 1 } else { quot };
+        if bits > Timeout::MAX.bits as u128 {
+            Timeout::MAX
+        } else {
+            Timeout { bits: bits as u32 }
+        }
+    }
+
+    /// Get the number of complete LoRa symbols in the timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{LoRaBandwidth, LoRaModParams, SpreadingFactor, Timeout};
+    ///
+    /// const MOD_PARAMS: LoRaModParams = LoRaModParams::new()
+    ///     .set_sf(SpreadingFactor::Sf12)
+    ///     .set_bw(LoRaBandwidth::Bw125);
+    ///
+    /// // 32.768 ms per symbol
+    /// assert_eq!(Timeout::from_raw(64_000).as_lora_symbols(&MOD_PARAMS), 30);
+    /// ```
+    pub const fn as_lora_symbols(&self, mod_params: &LoRaModParams) -> u32 {
+        let (num, den): (u128, u128) = lora_symbol(mod_params);
+        ((self.bits as u128) * den / (BITS_PER_SEC * num)) as u32
+    }
+
+    /// Get the number of complete (G)FSK bits in the timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBitrate, FskModParams, Timeout};
+    ///
+    /// const MOD_PARAMS: FskModParams = FskModParams::new().set_bitrate(FskBitrate::from_bps(9_600));
+    ///
+    /// assert_eq!(Timeout::from_raw(64_000).as_fsk_bits(&MOD_PARAMS), 9_600);
+    /// ```
+    pub const fn as_fsk_bits(&self, mod_params: &FskModParams) -> u32 {
+        let bps: u128 = mod_params.bitrate().as_bps() as u128;
+        let bits: u128 = (self.bits as u128) * bps / BITS_PER_SEC;
+        if bits > u32::MAX as u128 {
+            u32::MAX
+        } else {
+            bits as u32
+        }
+    }
+
     /// Get the number of nanoseconds in the timeout.
     ///
     /// # Example
@@ -2,6 +2,7 @@
 ///
 /// Argument of [`set_ramp_time`][`crate::TxParams::set_ramp_time`].
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum RampTime {
     /// 10µs
@@ -47,6 +48,7 @@ impl From<RampTime> for core::time::Duration {
 ///
 /// Argument of [`set_tx_params`][`crate::SubGhz::set_tx_params`].
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TxParams {
     buf: [u8; 3],
 }
@@ -108,6 +110,20 @@ impl TxParams {
         self
     }
 
+    /// Get the output power.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::TxParams;
+    ///
+    /// const TX_PARAMS: TxParams = TxParams::new().set_power(0x16);
+    /// assert_eq!(TX_PARAMS.power(), 0x16);
+    /// ```
+    pub const fn power(&self) -> u8 {
+        self.buf[1]
+    }
+
     /// Set the Power amplifier ramp time for FSK, MSK, and LoRa modulation.
     ///
     /// # Example
@@ -0,0 +1,476 @@
+use crate::{Irq, ModemCfg, StandbyClk, SubGhz, SubGhzError, Timeout};
+
+/// Transmit queue errors.
+///
+/// Returned by [`TxQueue::enqueue`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TxQueueError {
+    /// The queue is full.
+    Full,
+    /// The payload is longer than `MTU`, or longer than 255 bytes.
+    PayloadLen,
+}
+
+/// Metadata of a completed frame.
+///
+/// Inner value of [`TxStatus`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TxMeta {
+    id: u32,
+    len: u8,
+    timestamp: Option<u32>,
+}
+
+impl TxMeta {
+    /// Frame ID returned by [`TxQueue::enqueue`].
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Payload length of the frame.
+    pub const fn payload_len(&self) -> u8 {
+        self.len
+    }
+
+    /// Timestamp of the [`Irq::TxDone`] interrupt, in the units of the
+    /// caller's timer.
+    ///
+    /// This is the value passed to [`TxQueue::capture_timestamp`] while the
+    /// frame was being sent, or `None` if no timestamp was captured.
+    ///
+    /// This is the end of the frame, subtract [`ModemCfg::time_on_air`] to
+    /// get the start of the frame.
+    pub const fn timestamp(&self) -> Option<u32> {
+        self.timestamp
+    }
+}
+
+/// Completion status of a transmitted frame.
+///
+/// Returned by [`poll_tx_queue`].
+///
+/// [`poll_tx_queue`]: crate::SubGhz::poll_tx_queue
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TxStatus {
+    /// The frame was sent.
+    Sent(TxMeta),
+    /// The transmission timed out.
+    Timeout(TxMeta),
+}
+
+/// Frame in the radio buffer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct Staged {
+    id: u32,
+    offset: u8,
+    len: u8,
+}
+
+impl Staged {
+    /// Offset in the radio buffer for the next frame of length `len` that
+    /// does not overlap with this frame.
+    const fn next_offset(&self, len: u8) -> Option<u8> {
+        let end: u16 = (self.offset as u16) + (self.len as u16);
+        if end + (len as u16) <= 256 {
+            Some(end as u8)
+        } else if len <= self.offset {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+struct TxFrame<const MTU: usize> {
+    buf: [u8; MTU],
+    len: u8,
+    id: u32,
+}
+
+impl<const MTU: usize> TxFrame<MTU> {
+    const fn new() -> TxFrame<MTU> {
+        TxFrame {
+            buf: [0; MTU],
+            len: 0,
+            id: 0,
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.buf[..usize::from(self.len)]
+    }
+}
+
+/// Fixed-capacity queue of frames to transmit back-to-back.
+///
+/// Drained by [`start_tx_queue`] and [`poll_tx_queue`], `SLOTS` is the
+/// number of frames stored and `MTU` is the maximum payload length of each
+/// frame.
+///
+/// While one frame is being sent the next frame is written to a free region
+/// of the radio buffer, when the [`Irq::TxDone`] interrupt is asserted the
+/// TX buffer base address is moved to the staged frame and the next
+/// transmission is started without waiting for a buffer write.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{
+///     GenericPacketParams, FskModParams, ModemCfg, Timeout, TxQueue, TxQueueError,
+/// };
+///
+/// const MODEM_CFG: ModemCfg = ModemCfg::Fsk {
+///     mod_params: FskModParams::new(),
+///     packet_params: GenericPacketParams::new(),
+/// };
+///
+/// let mut queue: TxQueue<2, 64> = TxQueue::new(MODEM_CFG, Timeout::DISABLED);
+/// assert_eq!(queue.enqueue(b"first"), Ok(0));
+/// assert_eq!(queue.enqueue(b"second"), Ok(1));
+/// assert_eq!(queue.enqueue(b"third"), Err(TxQueueError::Full));
+/// assert_eq!(queue.enqueue(&[0; 65]), Err(TxQueueError::PayloadLen));
+/// assert_eq!(queue.len(), 2);
+/// assert!(queue.is_idle());
+/// ```
+///
+/// [`start_tx_queue`]: crate::SubGhz::start_tx_queue
+/// [`poll_tx_queue`]: crate::SubGhz::poll_tx_queue
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TxQueue<const SLOTS: usize, const MTU: usize> {
+    frames: [TxFrame<MTU>; SLOTS],
+    head: usize,
+    len: usize,
+    next_id: u32,
+    cfg: ModemCfg,
+    timeout: Timeout,
+    cur: Option<Staged>,
+    staged: Option<Staged>,
+    capture: Option<u32>,
+}
+
+#[cfg(feature = "defmt")]
+impl<const SLOTS: usize, const MTU: usize> defmt::Format for TxQueue<SLOTS, MTU> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "TxQueue {{ len: {}, next_id: {}, timeout: {} }}",
+            self.len,
+            self.next_id,
+            self.timeout
+        )
+    }
+}
+
+impl<const SLOTS: usize, const MTU: usize> TxQueue<SLOTS, MTU> {
+    /// Create a new, empty, queue.
+    ///
+    /// The payload length of the packet parameters in `cfg` is replaced with
+    /// the length of each frame, `timeout` is used for every transmission.
+    pub const fn new(cfg: ModemCfg, timeout: Timeout) -> TxQueue<SLOTS, MTU> {
+        TxQueue {
+            frames: [TxFrame::new(); SLOTS],
+            head: 0,
+            len: 0,
+            next_id: 0,
+            cfg,
+            timeout,
+            cur: None,
+            staged: None,
+            capture: None,
+        }
+    }
+
+    /// Maximum number of frames waiting in the queue.
+    pub const fn capacity(&self) -> usize {
+        SLOTS
+    }
+
+    /// Number of frames waiting in the queue.
+    ///
+    /// This does not include frames that have been written to the radio
+    /// buffer.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no frames are waiting in the queue.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    pub const fn is_full(&self) -> bool {
+        self.len == SLOTS
+    }
+
+    /// Returns `true` if no frame is being sent.
+    pub const fn is_idle(&self) -> bool {
+        self.cur.is_none()
+    }
+
+    /// Add a frame to the queue.
+    ///
+    /// Returns the frame ID, this is reported in the [`TxStatus`] when the
+    /// frame is complete.
+    pub fn enqueue(&mut self, data: &[u8]) -> Result<u32, TxQueueError> {
+        if data.len() > MTU || data.len() > usize::from(u8::MAX) {
+            return Err(TxQueueError::PayloadLen);
+        }
+        if self.is_full() {
+            return Err(TxQueueError::Full);
+        }
+
+        let id: u32 = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let idx: usize = (self.head + self.len) % SLOTS;
+        self.len += 1;
+        let frame: &mut TxFrame<MTU> = &mut self.frames[idx];
+        frame.buf[..data.len()].copy_from_slice(data);
+        frame.len = data.len() as u8;
+        frame.id = id;
+        Ok(id)
+    }
+
+    /// Remove all frames waiting in the queue.
+    ///
+    /// Frames that have already been written to the radio buffer are still
+    /// sent.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Capture a timestamp for the frame being sent.
+    ///
+    /// Call this from the radio interrupt handler when [`Irq::TxDone`] is
+    /// asserted, with the value of a free-running timer such as the DWT cycle
+    /// counter, or a timer input capture.
+    /// [`capture_tx_timestamp`] does this for the radio interrupts.
+    ///
+    /// The timestamp is reported in the [`TxMeta`] of the frame by
+    /// [`poll_tx_queue`].
+    ///
+    /// [`capture_tx_timestamp`]: crate::SubGhz::capture_tx_timestamp
+    /// [`poll_tx_queue`]: crate::SubGhz::poll_tx_queue
+    pub fn capture_timestamp(&mut self, timestamp: u32) {
+        self.capture = Some(timestamp);
+    }
+
+    fn peek(&self) -> Option<&TxFrame<MTU>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(&self.frames[self.head])
+        }
+    }
+
+    fn pop(&mut self) {
+        self.head = (self.head + 1) % SLOTS;
+        self.len -= 1;
+    }
+}
+
+/// Queued transmit.
+impl SubGhz {
+    /// Start sending the frames in a [`TxQueue`].
+    ///
+    /// The packet type and modulation parameters must be configured before
+    /// calling this function, the packet parameters are written for each
+    /// frame.
+    ///
+    /// The RX buffer base address is set to zero.
+    ///
+    /// This does nothing if the queue is already sending, or if the queue is
+    /// empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// use stm32wl_hal_subghz::{
+    ///     GenericPacketParams, FskModParams, ModemCfg, Timeout, TxQueue, TxStatus,
+    /// };
+    ///
+    /// const MODEM_CFG: ModemCfg = ModemCfg::Fsk {
+    ///     mod_params: FskModParams::new(),
+    ///     packet_params: GenericPacketParams::new(),
+    /// };
+    ///
+    /// let mut queue: TxQueue<4, 128> = TxQueue::new(MODEM_CFG, Timeout::DISABLED);
+    /// sg.set_modem_cfg(&MODEM_CFG)?.unwrap();
+    ///
+    /// queue.enqueue(&[0x01; 128]).unwrap();
+    /// queue.enqueue(&[0x02; 128]).unwrap();
+    /// sg.start_tx_queue(&mut queue)?;
+    ///
+    /// while !queue.is_idle() {
+    ///     match sg.poll_tx_queue(&mut queue)? {
+    ///         Some(TxStatus::Sent(meta)) => { /* frame meta.id() was sent */ }
+    ///         Some(TxStatus::Timeout(meta)) => { /* frame meta.id() timed out */ }
+    ///         None => (),
+    ///     }
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    pub fn start_tx_queue<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut TxQueue<SLOTS, MTU>,
+    ) -> Result<(), SubGhzError> {
+        if !queue.is_idle() || queue.is_empty() {
+            return Ok(());
+        }
+        self.set_standby(StandbyClk::Rc)?;
+        self.clear_irq_status(Irq::TxDone.mask() | Irq::Timeout.mask())?;
+        self.stage_tx_frame(queue)?;
+        self.send_staged_tx_frame(queue)?;
+        self.stage_tx_frame(queue)
+    }
+
+    /// Chain the next transmission when the current frame is complete.
+    ///
+    /// Call this when the [`Irq::TxDone`] or [`Irq::Timeout`] interrupts are
+    /// asserted, or periodically.
+    /// This also writes frames enqueued after [`start_tx_queue`] to the
+    /// radio buffer, and restarts the queue if it has gone idle.
+    ///
+    /// Returns the status of the completed frame.
+    ///
+    /// # Example
+    ///
+    /// See [`start_tx_queue`].
+    ///
+    /// [`start_tx_queue`]: Self::start_tx_queue
+    pub fn poll_tx_queue<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut TxQueue<SLOTS, MTU>,
+    ) -> Result<Option<TxStatus>, SubGhzError> {
+        let cur: Staged = match queue.cur {
+            Some(cur) => cur,
+            None => {
+                self.start_tx_queue(queue)?;
+                return Ok(None);
+            }
+        };
+
+        let (_, irq) = self.irq_status()?;
+        let meta = || TxMeta {
+            id: cur.id,
+            len: cur.len,
+            timestamp: queue.capture,
+        };
+        let status: TxStatus = if irq & Irq::TxDone.mask() != 0 {
+            TxStatus::Sent(meta())
+        } else if irq & Irq::Timeout.mask() != 0 {
+            TxStatus::Timeout(meta())
+        } else {
+            self.stage_tx_frame(queue)?;
+            return Ok(None);
+        };
+
+        self.clear_irq_status(Irq::TxDone.mask() | Irq::Timeout.mask())?;
+        queue.cur = None;
+        self.stage_tx_frame(queue)?;
+        self.send_staged_tx_frame(queue)?;
+        self.stage_tx_frame(queue)?;
+        Ok(Some(status))
+    }
+
+    /// Capture a timestamp for the frame being sent from the radio interrupt
+    /// handler.
+    ///
+    /// If [`Irq::TxDone`] is asserted this passes `timestamp` to
+    /// [`TxQueue::capture_timestamp`], the interrupt is cleared by
+    /// [`poll_tx_queue`].
+    ///
+    /// `timestamp` should be read on entry to the interrupt handler, before
+    /// any radio commands, for example with [`dwt_cycle_count`].
+    ///
+    /// Returns `true` if a timestamp was captured.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let mut sg = unsafe { stm32wl_hal_subghz::SubGhz::steal() };
+    /// # let mut queue: stm32wl_hal_subghz::TxQueue<4, 128> = stm32wl_hal_subghz::TxQueue::new(
+    /// #     stm32wl_hal_subghz::ModemCfg::Fsk {
+    /// #         mod_params: stm32wl_hal_subghz::FskModParams::new(),
+    /// #         packet_params: stm32wl_hal_subghz::GenericPacketParams::new(),
+    /// #     },
+    /// #     stm32wl_hal_subghz::Timeout::DISABLED,
+    /// # );
+    /// use stm32wl_hal_subghz::{dwt_cycle_count, TxStatus};
+    ///
+    /// // in the radio interrupt handler
+    /// sg.capture_tx_timestamp(&mut queue, dwt_cycle_count())?;
+    /// if let Some(TxStatus::Sent(meta)) = sg.poll_tx_queue(&mut queue)? {
+    ///     let end_of_frame: Option<u32> = meta.timestamp();
+    /// }
+    /// # Ok::<(), stm32wl_hal_subghz::SubGhzError>(())
+    /// ```
+    ///
+    /// [`poll_tx_queue`]: Self::poll_tx_queue
+    /// [`dwt_cycle_count`]: crate::dwt_cycle_count
+    pub fn capture_tx_timestamp<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut TxQueue<SLOTS, MTU>,
+        timestamp: u32,
+    ) -> Result<bool, SubGhzError> {
+        if queue.is_idle() {
+            return Ok(false);
+        }
+        let (_, irq) = self.irq_status()?;
+        if irq & Irq::TxDone.mask() == 0 {
+            return Ok(false);
+        }
+        queue.capture_timestamp(timestamp);
+        Ok(true)
+    }
+
+    /// Write the next frame to a free region of the radio buffer.
+    fn stage_tx_frame<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut TxQueue<SLOTS, MTU>,
+    ) -> Result<(), SubGhzError> {
+        if queue.staged.is_some() {
+            return Ok(());
+        }
+        let (offset, len, id) = match queue.peek() {
+            Some(frame) => {
+                let offset: Option<u8> = match queue.cur {
+                    Some(cur) => cur.next_offset(frame.len),
+                    None => Some(0),
+                };
+                match offset {
+                    Some(offset) => {
+                        self.write_buffer(offset, frame.data())?;
+                        (offset, frame.len, frame.id)
+                    }
+                    None => return Ok(()),
+                }
+            }
+            None => return Ok(()),
+        };
+        queue.pop();
+        queue.staged = Some(Staged { id, offset, len });
+        Ok(())
+    }
+
+    /// Start sending the staged frame.
+    fn send_staged_tx_frame<const SLOTS: usize, const MTU: usize>(
+        &mut self,
+        queue: &mut TxQueue<SLOTS, MTU>,
+    ) -> Result<(), SubGhzError> {
+        if let Some(staged) = queue.staged.take() {
+            self.set_buffer_base_address(staged.offset, 0)?;
+            self.set_modem_packet_params(&queue.cfg.set_payload_len(staged.len))?;
+            self.set_tx(queue.timeout)?;
+            queue.cur = Some(staged);
+            queue.capture = None;
+        }
+        Ok(())
+    }
+}
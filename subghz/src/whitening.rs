@@ -0,0 +1,117 @@
+/// Software model of the generic packet data whitening engine.
+///
+/// The radio whitens the payload and CRC with a PN9 sequence generated by a
+/// 9-bit LFSR with the polynomial x<sup>9</sup> + x<sup>5</sup> + 1.
+/// Whitening and de-whitening are the same operation.
+///
+/// The lower 8 bits of the seed are programmed with
+/// [`set_initial_whitening`], the most significant bit of the seed is `1`
+/// after reset.
+///
+/// # Example
+///
+/// Whiten a frame, then recover the original.
+///
+/// ```
+/// use stm32wl_hal_subghz::Whitening;
+///
+/// const DATA: [u8; 4] = [0xDE, 0xAD, 0xBE, 0xEF];
+///
+/// let mut buf: [u8; 4] = DATA;
+/// Whitening::RESET.apply(&mut buf);
+/// assert_ne!(buf, DATA);
+///
+/// Whitening::RESET.apply(&mut buf);
+/// assert_eq!(buf, DATA);
+/// ```
+///
+/// [`set_initial_whitening`]: crate::SubGhz::set_initial_whitening
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Whitening {
+    lfsr: u16,
+}
+
+impl Whitening {
+    /// Whitening with the reset seed of the radio, `0x100`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Whitening;
+    ///
+    /// assert_eq!(Whitening::RESET.seed(), 0x100);
+    /// ```
+    pub const RESET: Whitening = Whitening::new(0x100);
+
+    /// Create a new whitening sequence from a 9-bit seed.
+    ///
+    /// Bits above bit 8 are ignored.
+    ///
+    /// # Example
+    ///
+    /// Whitening with a seed of `0x1A5`, the radio equivalent of
+    /// `sg.set_initial_whitening(0xA5)`.
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Whitening;
+    ///
+    /// const WHITENING: Whitening = Whitening::new(0x1A5);
+    /// # assert_eq!(WHITENING.seed(), 0x1A5);
+    /// # assert_eq!(Whitening::new(0xFFFF).seed(), 0x1FF);
+    /// ```
+    pub const fn new(seed: u16) -> Whitening {
+        Whitening { lfsr: seed & 0x1FF }
+    }
+
+    /// Get the current state of the LFSR.
+    ///
+    /// This is the seed until [`next_byte`](Self::next_byte) or
+    /// [`apply`](Self::apply) is called.
+    pub const fn seed(&self) -> u16 {
+        self.lfsr
+    }
+
+    /// Get the next byte of the PN9 sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Whitening;
+    ///
+    /// let mut pn9: Whitening = Whitening::new(0x1FF);
+    /// assert_eq!(pn9.next_byte(), 0xFF);
+    /// assert_eq!(pn9.next_byte(), 0xE1);
+    /// assert_eq!(pn9.next_byte(), 0x1D);
+    /// assert_eq!(pn9.next_byte(), 0x9A);
+    /// ```
+    pub fn next_byte(&mut self) -> u8 {
+        let ret: u8 = self.lfsr as u8;
+        (0..8).for_each(|_| {
+            let bit: u16 = ((self.lfsr >> 5) ^ self.lfsr) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 8);
+        });
+        ret
+    }
+
+    /// Whiten or de-whiten `buf` in place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::Whitening;
+    ///
+    /// let mut buf: [u8; 4] = [0; 4];
+    /// Whitening::new(0x1FF).apply(&mut buf);
+    /// assert_eq!(buf, [0xFF, 0xE1, 0x1D, 0x9A]);
+    /// ```
+    pub fn apply(mut self, buf: &mut [u8]) {
+        buf.iter_mut().for_each(|b| *b ^= self.next_byte())
+    }
+}
+
+impl Default for Whitening {
+    fn default() -> Self {
+        Self::RESET
+    }
+}
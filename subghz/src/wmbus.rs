@@ -0,0 +1,585 @@
+use crate::{
+    CrcType, FskBandwidth, FskBitrate, FskFdev, FskModParams, FskPulseShape, GenericCrc,
+    GenericPacketParams, HeaderType, PreambleDetection, RfFreq,
+};
+
+/// Block CRC, polynomial `0x3D65` with an initial value of `0x0000`, inverted.
+const WMBUS_CRC: GenericCrc = GenericCrc::new(CrcType::Byte2Inverted)
+    .set_polynomial(0x3D65)
+    .set_initial(0x0000);
+
+/// Length of the block CRC in bytes.
+const CRC_LEN: usize = 2;
+
+/// Number of data bytes after the L-field in the first block, the C, M, and A
+/// fields.
+const FIRST_BLOCK_LEN: usize = 9;
+
+/// Number of data bytes in the following blocks of frame format A.
+const BLOCK_LEN_A: usize = 16;
+
+/// Maximum number of data bytes in the second block of frame format B.
+///
+/// The second block is at most 118 bytes including the CRC, which also covers
+/// the first block.
+const BLOCK_LEN_B: usize = 116;
+
+/// Mode C frame format A and B identifiers, after the sync word.
+const MODE_C_FORMAT_A: [u8; 2] = [0x54, 0xCD];
+const MODE_C_FORMAT_B: [u8; 2] = [0x54, 0x3D];
+
+/// 3-out-of-6 code for each nibble.
+const THREE_OF_SIX: [u8; 16] = [
+    0b010110, 0b001101, 0b001110, 0b001011, 0b011100, 0b011001, 0b011010, 0b010011, 0b101100,
+    0b100101, 0b100110, 0b100011, 0b110100, 0b110001, 0b110010, 0b101001,
+];
+
+/// Wireless M-Bus errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WmbusError {
+    /// The data is shorter than the C, M, and A fields (9 bytes), or too long
+    /// for the L-field.
+    DataLen,
+    /// The output buffer is too small, or the received chips end before the
+    /// length in the L-field.
+    BufferTooSmall,
+    /// Invalid 3-out-of-6 or Manchester code, or unknown mode C frame format.
+    Coding,
+    /// The L-field is invalid for the frame format.
+    LField,
+    /// Block CRC mismatch.
+    Crc,
+}
+
+/// Wireless M-Bus (EN 13757-4) mode.
+///
+/// Only the meter to other device direction is provided.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WmbusMode {
+    /// Stationary mode, 32.768 kchip/s Manchester coded, 868.3 MHz.
+    S,
+    /// Frequent transmit mode, 100 kchip/s 3-out-of-6 coded, 868.95 MHz.
+    T,
+    /// Compact mode, 100 kchip/s NRZ, 868.95 MHz.
+    C,
+}
+
+impl WmbusMode {
+    /// Channel frequency.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{RfFreq, WmbusMode};
+    ///
+    /// assert_eq!(WmbusMode::T.rf_freq(), RfFreq::from_frequency(868_950_000));
+    /// ```
+    pub const fn rf_freq(&self) -> RfFreq {
+        match self {
+            WmbusMode::S => RfFreq::from_frequency(868_300_000),
+            WmbusMode::T | WmbusMode::C => RfFreq::from_frequency(868_950_000),
+        }
+    }
+
+    /// Modulation parameters.
+    ///
+    /// The bitrate is the chip rate, the frame is line coded in software.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{FskBitrate, FskFdev, WmbusMode};
+    ///
+    /// const MOD_PARAMS: stm32wl_hal_subghz::FskModParams = WmbusMode::C.mod_params();
+    /// assert_eq!(MOD_PARAMS.bitrate(), FskBitrate::from_bps(100_000));
+    /// assert_eq!(MOD_PARAMS.fdev(), FskFdev::from_hertz(45_000));
+    /// ```
+    pub const fn mod_params(&self) -> FskModParams {
+        let (bps, fdev, bw): (u32, u32, FskBandwidth) = match self {
+            WmbusMode::S => (32_768, 50_000, FskBandwidth::Bw156),
+            WmbusMode::T => (100_000, 50_000, FskBandwidth::Bw234),
+            WmbusMode::C => (100_000, 45_000, FskBandwidth::Bw234),
+        };
+        FskModParams::new()
+            .set_bitrate(FskBitrate::from_bps(bps))
+            .set_pulse_shape(FskPulseShape::None)
+            .set_bandwidth(bw)
+            .set_fdev(FskFdev::from_hertz(fdev))
+    }
+
+    /// Sync word for [`set_sync_word`].
+    ///
+    /// This is the end of the preamble and the synchronization word, 16
+    /// chips.
+    ///
+    /// [`set_sync_word`]: crate::SubGhz::set_sync_word
+    pub const fn sync_word(&self) -> [u8; 8] {
+        let sync: [u8; 2] = match self {
+            WmbusMode::S => [0x76, 0x96],
+            WmbusMode::T | WmbusMode::C => [0x54, 0x3D],
+        };
+        [sync[0], sync[1], 0, 0, 0, 0, 0, 0]
+    }
+
+    /// Packet parameters for `len` bytes of encoded chips.
+    ///
+    /// For reception use the longest expected frame, [`Wmbus::decode`] uses
+    /// the L-field and ignores trailing chips.
+    pub const fn packet_params(&self, len: u8) -> GenericPacketParams {
+        // minimum preamble chips for the meter to other direction, S2 for
+        // mode S, rounded up to a multiple of 8
+        let preamble: u16 = match self {
+            WmbusMode::S => 32,
+            WmbusMode::T => 40,
+            WmbusMode::C => 32,
+        };
+        GenericPacketParams::new()
+            .set_preamble_len(preamble)
+            .set_preamble_detection(PreambleDetection::Bit16)
+            .set_sync_word_len(16)
+            .set_header_type(HeaderType::Fixed)
+            .set_payload_len(len)
+            .set_crc_type(CrcType::Disabled)
+            .set_whitening_enable(false)
+    }
+}
+
+/// Wireless M-Bus frame format.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WmbusFormat {
+    /// Frame format A, a CRC after the first 10 bytes and after every 16
+    /// bytes.
+    A,
+    /// Frame format B, a CRC after the first block of 10 bytes and the
+    /// second block of up to 116 bytes, and a CRC at the end of the optional
+    /// third block.
+    ///
+    /// Only for mode C.
+    B,
+}
+
+impl WmbusFormat {
+    /// Frame length in bytes including the L-field and CRCs for `data_len`
+    /// bytes after the L-field, or `None` if the data is too short or too
+    /// long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::WmbusFormat;
+    ///
+    /// assert_eq!(WmbusFormat::A.frame_len(9), Some(12));
+    /// assert_eq!(WmbusFormat::A.frame_len(26), Some(33));
+    /// assert_eq!(WmbusFormat::B.frame_len(26), Some(29));
+    /// assert_eq!(WmbusFormat::B.frame_len(125), Some(128));
+    /// assert_eq!(WmbusFormat::B.frame_len(126), Some(131));
+    /// assert_eq!(WmbusFormat::A.frame_len(8), None);
+    /// ```
+    pub const fn frame_len(&self, data_len: usize) -> Option<usize> {
+        if data_len < FIRST_BLOCK_LEN {
+            return None;
+        }
+        let l_field: usize = match self {
+            WmbusFormat::A => data_len,
+            WmbusFormat::B => data_len + CRC_LEN * self.num_crc(data_len),
+        };
+        if l_field > u8::MAX as usize {
+            None
+        } else {
+            Some(1 + data_len + CRC_LEN * self.num_crc(data_len))
+        }
+    }
+
+    const fn num_crc(&self, data_len: usize) -> usize {
+        let rest: usize = data_len - FIRST_BLOCK_LEN;
+        match self {
+            WmbusFormat::A => {
+                let mut blocks: usize = rest / BLOCK_LEN_A;
+                if blocks * BLOCK_LEN_A < rest {
+                    blocks += 1;
+                }
+                1 + blocks
+            }
+            WmbusFormat::B => {
+                if rest > BLOCK_LEN_B {
+                    2
+                } else {
+                    1
+                }
+            }
+        }
+    }
+
+    /// Number of data bytes for an L-field.
+    const fn data_len(&self, l_field: u8) -> Result<usize, WmbusError> {
+        let l_field: usize = l_field as usize;
+        let data_len: usize = match self {
+            WmbusFormat::A => l_field,
+            WmbusFormat::B => {
+                if l_field <= FIRST_BLOCK_LEN + BLOCK_LEN_B + CRC_LEN {
+                    l_field.saturating_sub(CRC_LEN)
+                } else {
+                    l_field - 2 * CRC_LEN
+                }
+            }
+        };
+        match self.frame_len(data_len) {
+            Some(frame_len) if frame_len == 1 + l_field || matches!(self, WmbusFormat::A) => {
+                Ok(data_len)
+            }
+            _ => Err(WmbusError::LField),
+        }
+    }
+
+    /// Lengths of the CRC blocks, including the L-field in the first block.
+    fn blocks(&self, data_len: usize) -> impl Iterator<Item = usize> {
+        let (first, next): (usize, usize) = match self {
+            WmbusFormat::A => (1 + FIRST_BLOCK_LEN, BLOCK_LEN_A),
+            WmbusFormat::B => (1 + FIRST_BLOCK_LEN + BLOCK_LEN_B, usize::MAX),
+        };
+        let mut remaining: usize = 1 + data_len;
+        let mut len: usize = first;
+        core::iter::from_fn(move || {
+            if remaining == 0 {
+                None
+            } else {
+                let block: usize = core::cmp::min(len, remaining);
+                remaining -= block;
+                len = next;
+                Some(block)
+            }
+        })
+    }
+}
+
+/// Wireless M-Bus (EN 13757-4) framing and line coding.
+///
+/// The frame is the L-field followed by the data (C-field, M-field, A-field,
+/// CI-field, and application data) split into blocks, each followed by a
+/// CRC-16.
+/// The frame is line coded for the mode, and sent as a fixed length payload
+/// with the hardware CRC and whitening disabled:
+///
+/// * [`WmbusMode::mod_params`] for [`set_fsk_mod_params`].
+/// * [`WmbusMode::packet_params`] for [`set_packet_params`].
+/// * [`WmbusMode::sync_word`] for [`set_sync_word`].
+/// * [`encode`] and [`decode`] for the frame.
+///
+/// The radio buffer limits the encoded frame to 255 bytes, 170 bytes before
+/// 3-out-of-6 coding, and 127 bytes before Manchester coding.
+///
+/// # Example
+///
+/// ```
+/// use stm32wl_hal_subghz::{Wmbus, WmbusMode};
+///
+/// const WMBUS: Wmbus = Wmbus::new(WmbusMode::T);
+///
+/// // C-field, manufacturer, address, CI-field, application data
+/// const DATA: [u8; 12] = [
+///     0x44, 0x2D, 0x2C, 0x78, 0x56, 0x34, 0x12, 0x01, 0x07, 0x7A, 0x01, 0x02,
+/// ];
+///
+/// let mut chips: [u8; 64] = [0; 64];
+/// let len: usize = WMBUS.encode(&DATA, &mut chips).unwrap();
+/// // L-field, 2 blocks, 2 CRCs, 3-out-of-6 coded
+/// assert_eq!(len, (1 + 12 + 4) * 3 / 2 + 1);
+///
+/// let mut data: [u8; 64] = [0; 64];
+/// assert_eq!(WMBUS.decode(&chips[..len], &mut data), Ok(&DATA[..]));
+/// ```
+///
+/// [`encode`]: Wmbus::encode
+/// [`decode`]: Wmbus::decode
+/// [`set_fsk_mod_params`]: crate::SubGhz::set_fsk_mod_params
+/// [`set_packet_params`]: crate::SubGhz::set_packet_params
+/// [`set_sync_word`]: crate::SubGhz::set_sync_word
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Wmbus {
+    mode: WmbusMode,
+    format: WmbusFormat,
+}
+
+impl Wmbus {
+    /// Create a new `Wmbus` struct with frame format A.
+    pub const fn new(mode: WmbusMode) -> Wmbus {
+        Wmbus {
+            mode,
+            format: WmbusFormat::A,
+        }
+    }
+
+    /// Set the frame format for transmission.
+    ///
+    /// Modes S and T always use frame format A, in mode C the receiver
+    /// detects the format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{Wmbus, WmbusFormat, WmbusMode};
+    ///
+    /// const C: Wmbus = Wmbus::new(WmbusMode::C).set_format(WmbusFormat::B);
+    /// assert_eq!(C.format(), WmbusFormat::B);
+    ///
+    /// const T: Wmbus = Wmbus::new(WmbusMode::T).set_format(WmbusFormat::B);
+    /// assert_eq!(T.format(), WmbusFormat::A);
+    /// ```
+    #[must_use = "set_format returns a modified Wmbus"]
+    pub const fn set_format(mut self, format: WmbusFormat) -> Wmbus {
+        if let WmbusMode::C = self.mode {
+            self.format = format;
+        }
+        self
+    }
+
+    /// Mode.
+    pub const fn mode(&self) -> WmbusMode {
+        self.mode
+    }
+
+    /// Frame format for transmission.
+    pub const fn format(&self) -> WmbusFormat {
+        self.format
+    }
+
+    /// Length of the encoded chips for `data_len` bytes of data, or `None` if
+    /// the data is too short or too long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{Wmbus, WmbusMode};
+    ///
+    /// assert_eq!(Wmbus::new(WmbusMode::S).encoded_len(9), Some(24));
+    /// assert_eq!(Wmbus::new(WmbusMode::T).encoded_len(9), Some(18));
+    /// assert_eq!(Wmbus::new(WmbusMode::C).encoded_len(9), Some(14));
+    /// ```
+    pub const fn encoded_len(&self, data_len: usize) -> Option<usize> {
+        match self.format.frame_len(data_len) {
+            Some(frame_len) => Some(match self.mode {
+                WmbusMode::S => 2 * frame_len,
+                // 12 chips per byte, the last byte is padded
+                WmbusMode::T => 3 * (frame_len / 2) + 2 * (frame_len % 2),
+                WmbusMode::C => MODE_C_FORMAT_A.len() + frame_len,
+            }),
+            None => None,
+        }
+    }
+
+    /// Build and line code a frame, returns the length of the encoded chips.
+    ///
+    /// `data` starts with the C-field, the L-field and CRCs are added.
+    pub fn encode(&self, data: &[u8], chips: &mut [u8]) -> Result<usize, WmbusError> {
+        let enc_len: usize = self.encoded_len(data.len()).ok_or(WmbusError::DataLen)?;
+        if chips.len() < enc_len {
+            return Err(WmbusError::BufferTooSmall);
+        }
+        let chips: &mut [u8] = &mut chips[..enc_len];
+
+        let l_field: u8 = match self.format {
+            WmbusFormat::A => data.len() as u8,
+            WmbusFormat::B => (enc_len - MODE_C_FORMAT_B.len() - 1) as u8,
+        };
+        let mut frame = core::iter::once(l_field).chain(data.iter().copied());
+        let mut crc_block: [u8; 1 + FIRST_BLOCK_LEN + BLOCK_LEN_B] =
+            [0; 1 + FIRST_BLOCK_LEN + BLOCK_LEN_B];
+        let mut coder: LineCoder = LineCoder::new(self.mode, chips);
+        if let WmbusMode::C = self.mode {
+            match self.format {
+                WmbusFormat::A => MODE_C_FORMAT_A,
+                WmbusFormat::B => MODE_C_FORMAT_B,
+            }
+            .iter()
+            .for_each(|&byte| coder.push(byte));
+        }
+        for block_len in self.format.blocks(data.len()) {
+            let block: &mut [u8] = &mut crc_block[..block_len];
+            block.iter_mut().for_each(|byte| {
+                // frame has exactly the sum of the block lengths
+                *byte = frame.next().unwrap_or(0);
+                coder.push(*byte);
+            });
+            WMBUS_CRC
+                .checksum(block)
+                .to_be_bytes()
+                .iter()
+                .for_each(|&byte| coder.push(byte));
+        }
+        coder.finish();
+        Ok(enc_len)
+    }
+
+    /// Decode received chips, returns the data starting with the C-field.
+    ///
+    /// In mode C the frame format is detected from the chips, the format set
+    /// with [`set_format`](Self::set_format) is ignored.
+    /// Chips after the length in the L-field are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stm32wl_hal_subghz::{Wmbus, WmbusError, WmbusFormat, WmbusMode};
+    ///
+    /// const TX: Wmbus = Wmbus::new(WmbusMode::C).set_format(WmbusFormat::B);
+    /// const RX: Wmbus = Wmbus::new(WmbusMode::C);
+    ///
+    /// let data: [u8; 20] = [0x44; 20];
+    /// let mut chips: [u8; 255] = [0; 255];
+    /// TX.encode(&data, &mut chips).unwrap();
+    ///
+    /// let mut out: [u8; 255] = [0; 255];
+    /// assert_eq!(RX.decode(&chips, &mut out), Ok(&data[..]));
+    ///
+    /// chips[5] ^= 0x10;
+    /// assert_eq!(RX.decode(&chips, &mut out), Err(WmbusError::Crc));
+    /// ```
+    pub fn decode<'a>(&self, chips: &[u8], data: &'a mut [u8]) -> Result<&'a [u8], WmbusError> {
+        let (format, chips): (WmbusFormat, &[u8]) = match self.mode {
+            WmbusMode::C => {
+                if chips.len() < MODE_C_FORMAT_A.len() {
+                    return Err(WmbusError::BufferTooSmall);
+                }
+                let (header, chips) = chips.split_at(MODE_C_FORMAT_A.len());
+                if header == MODE_C_FORMAT_A {
+                    (WmbusFormat::A, chips)
+                } else if header == MODE_C_FORMAT_B {
+                    (WmbusFormat::B, chips)
+                } else {
+                    return Err(WmbusError::Coding);
+                }
+            }
+            WmbusMode::S | WmbusMode::T => (WmbusFormat::A, chips),
+        };
+
+        let l_field: u8 = decode_byte(self.mode, chips, 0)?;
+        let data_len: usize = format.data_len(l_field)?;
+        if data.len() < data_len {
+            return Err(WmbusError::BufferTooSmall);
+        }
+
+        let mut crc_block: [u8; 1 + FIRST_BLOCK_LEN + BLOCK_LEN_B] =
+            [0; 1 + FIRST_BLOCK_LEN + BLOCK_LEN_B];
+        let mut idx: usize = 0;
+        let mut out: usize = 0;
+        for block_len in format.blocks(data_len) {
+            let block: &mut [u8] = &mut crc_block[..block_len];
+            for byte in block.iter_mut() {
+                *byte = decode_byte(self.mode, chips, idx)?;
+                idx += 1;
+            }
+            let crc: u16 = u16::from_be_bytes([
+                decode_byte(self.mode, chips, idx)?,
+                decode_byte(self.mode, chips, idx + 1)?,
+            ]);
+            idx += CRC_LEN;
+            if WMBUS_CRC.checksum(block) != crc {
+                return Err(WmbusError::Crc);
+            }
+            // skip the L-field
+            let block: &[u8] = if out == 0 { &block[1..] } else { block };
+            data[out..out + block.len()].copy_from_slice(block);
+            out += block.len();
+        }
+        Ok(&data[..out])
+    }
+}
+
+/// Decode the byte at `idx` of the line coded frame.
+fn decode_byte(mode: WmbusMode, chips: &[u8], idx: usize) -> Result<u8, WmbusError> {
+    match mode {
+        WmbusMode::S => {
+            let pair: [u8; 2] = [
+                *chips.get(2 * idx).ok_or(WmbusError::BufferTooSmall)?,
+                *chips.get(2 * idx + 1).ok_or(WmbusError::BufferTooSmall)?,
+            ];
+            let bits: u16 = u16::from_be_bytes(pair);
+            (0..8).try_fold(0, |acc: u8, n| match (bits >> (14 - 2 * n)) & 0b11 {
+                0b01 => Ok(acc << 1),
+                0b10 => Ok((acc << 1) | 1),
+                _ => Err(WmbusError::Coding),
+            })
+        }
+        WmbusMode::T => {
+            let bit: usize = 12 * idx;
+            let byte: usize = bit / 8;
+            let window: u32 = (0..3).fold(0, |acc, n| {
+                (acc << 8) | u32::from(chips.get(byte + n).copied().unwrap_or(0))
+            });
+            if chips.len() * 8 < bit + 12 {
+                return Err(WmbusError::BufferTooSmall);
+            }
+            let code: u32 = (window >> (12 - (bit % 8))) & 0xFFF;
+            let nibble = |code: u32| {
+                THREE_OF_SIX
+                    .iter()
+                    .position(|&c| u32::from(c) == code)
+                    .map(|n| n as u8)
+                    .ok_or(WmbusError::Coding)
+            };
+            Ok((nibble(code >> 6)? << 4) | nibble(code & 0x3F)?)
+        }
+        WmbusMode::C => chips.get(idx).copied().ok_or(WmbusError::BufferTooSmall),
+    }
+}
+
+/// Line codes bytes into a chip buffer, MSB first.
+struct LineCoder<'a> {
+    mode: WmbusMode,
+    chips: &'a mut [u8],
+    acc: u32,
+    acc_bits: u32,
+    idx: usize,
+}
+
+impl<'a> LineCoder<'a> {
+    fn new(mode: WmbusMode, chips: &'a mut [u8]) -> LineCoder<'a> {
+        LineCoder {
+            mode,
+            chips,
+            acc: 0,
+            acc_bits: 0,
+            idx: 0,
+        }
+    }
+
+    fn push_bits(&mut self, bits: u32, len: u32) {
+        self.acc = (self.acc << len) | bits;
+        self.acc_bits += len;
+        while self.acc_bits >= 8 {
+            self.acc_bits -= 8;
+            self.chips[self.idx] = (self.acc >> self.acc_bits) as u8;
+            self.idx += 1;
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        match self.mode {
+            WmbusMode::S => {
+                let chips: u32 = (0..8).fold(0, |acc, n| {
+                    let pair: u32 = if byte & (0x80 >> n) != 0 { 0b10 } else { 0b01 };
+                    (acc << 2) | pair
+                });
+                self.push_bits(chips, 16)
+            }
+            WmbusMode::T => {
+                let hi: u32 = u32::from(THREE_OF_SIX[usize::from(byte >> 4)]);
+                let lo: u32 = u32::from(THREE_OF_SIX[usize::from(byte & 0xF)]);
+                self.push_bits((hi << 6) | lo, 12)
+            }
+            WmbusMode::C => self.push_bits(u32::from(byte), 8),
+        }
+    }
+
+    /// Pad the last byte with a `0101` postamble.
+    fn finish(&mut self) {
+        if self.acc_bits != 0 {
+            let pad: u32 = 8 - self.acc_bits;
+            self.push_bits(0x55 & ((1 << pad) - 1), pad);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use common::XorShift;
+use rand_core::RngCore;
+use stm32wl_hal_subghz::{
+    Arq, ArqError, ArqEvent, CodingRate, FskBitrate, FskModParams, GenericPacketParams, HeaderType,
+    LoRaBandwidth, LoRaModParams, LoRaPacketParams, ModemCfg, SpreadingFactor,
+};
+
+const MTU: usize = 64;
+const TICK_HZ: u32 = 1000;
+
+const LORA: ModemCfg = ModemCfg::LoRa {
+    mod_params: LoRaModParams::new()
+        .set_sf(SpreadingFactor::Sf7)
+        .set_bw(LoRaBandwidth::Bw125)
+        .set_cr(CodingRate::Cr45),
+    packet_params: LoRaPacketParams::new()
+        .set_preamble_len(8)
+        .set_header_type(HeaderType::Variable)
+        .set_crc_en(true),
+};
+
+const FSK: ModemCfg = ModemCfg::Fsk {
+    mod_params: FskModParams::new().set_bitrate(FskBitrate::from_bps(50_000)),
+    packet_params: GenericPacketParams::new()
+        .set_preamble_len(32)
+        .set_sync_word_len(32)
+        .set_header_type(HeaderType::Variable),
+};
+
+/// Simulated half-duplex channel that drops frames pseudorandomly.
+struct Channel {
+    rng: XorShift,
+    loss_percent: u32,
+}
+
+impl Channel {
+    fn new(seed: u32, loss_percent: u32) -> Channel {
+        Channel {
+            rng: XorShift(seed),
+            loss_percent,
+        }
+    }
+
+    fn lost(&mut self) -> bool {
+        self.rng.next_u32() % 100 < self.loss_percent
+    }
+}
+
+/// Transmit the next frame from `tx` to `rx`, if there is one.
+fn step(
+    tx: &mut Arq<MTU>,
+    rx: &mut Arq<MTU>,
+    ch: &mut Channel,
+    now: u32,
+    msg: &mut [u8],
+) -> Option<ArqEvent> {
+    let mut frame: [u8; MTU] = [0; MTU];
+    match tx.poll_tx(now, &mut frame) {
+        Some(len) if !ch.lost() => rx.receive(&frame[..len], msg).unwrap(),
+        _ => None,
+    }
+}
+
+fn transfer(cfg: ModemCfg, seed: u32, loss_percent: u32) {
+    let mut alice: Arq<MTU> = Arq::new(cfg, TICK_HZ).set_retries(20);
+    let mut bob: Arq<MTU> = Arq::new(cfg, TICK_HZ).set_retries(20);
+    let mut ch: Channel = Channel::new(seed, loss_percent);
+    let mut msg: [u8; MTU] = [0; MTU];
+
+    let mut received: Vec<Vec<u8>> = Vec::new();
+    let mut acked: Vec<u8> = Vec::new();
+    let mut now: u32 = 0;
+
+    for n in 0..50u8 {
+        let payload: Vec<u8> = vec![n; usize::from(n % 40) + 1];
+        let seq: u8 = alice.send(&payload).unwrap();
+        assert_eq!(alice.send(&payload), Err(ArqError::Busy));
+
+        while alice.is_busy() {
+            let event: Option<ArqEvent> = step(&mut alice, &mut bob, &mut ch, now, &mut msg);
+            if let Some(ArqEvent::Received(len)) = event {
+                received.push(msg[..len].to_vec());
+            }
+            let event: Option<ArqEvent> = step(&mut bob, &mut alice, &mut ch, now, &mut msg);
+            match event {
+                Some(ArqEvent::Acked(acked_seq)) => {
+                    assert_eq!(acked_seq, seq);
+                    acked.push(acked_seq);
+                }
+                None => (),
+                Some(event) => panic!("unexpected event {:?}", event),
+            }
+            assert_eq!(alice.poll(now), None, "frame {} failed", n);
+            now += 1;
+        }
+    }
+
+    assert_eq!(acked, (0..50).collect::<Vec<u8>>());
+    assert_eq!(received.len(), 50);
+    for (n, payload) in received.iter().enumerate() {
+        assert_eq!(payload, &vec![n as u8; n % 40 + 1]);
+    }
+    if loss_percent > 0 {
+        assert!(alice.retransmits() > 0);
+        assert!(bob.duplicates() > 0);
+    }
+}
+
+#[test]
+fn lossless_lora() {
+    transfer(LORA, 1, 0);
+}
+
+#[test]
+fn lossy_lora() {
+    transfer(LORA, 0x1234_5678, 30);
+}
+
+#[test]
+fn lossy_fsk() {
+    transfer(FSK, 0xDEAD_BEEF, 30);
+}
+
+#[test]
+fn lost_ack_is_duplicate() {
+    let mut alice: Arq<MTU> = Arq::new(FSK, TICK_HZ);
+    let mut bob: Arq<MTU> = Arq::new(FSK, TICK_HZ);
+    let mut frame: [u8; MTU] = [0; MTU];
+    let mut msg: [u8; MTU] = [0; MTU];
+
+    let seq: u8 = alice.send(b"data").unwrap();
+    let len: usize = alice.poll_tx(0, &mut frame).unwrap();
+    assert_eq!(
+        bob.receive(&frame[..len], &mut msg),
+        Ok(Some(ArqEvent::Received(4)))
+    );
+    // ACK lost
+    assert!(bob.poll_tx(1, &mut [0; MTU]).is_some());
+
+    // no retransmission before the timeout
+    let timeout: u32 = alice.ack_timeout();
+    assert_eq!(alice.poll_tx(timeout - 1, &mut frame), None);
+    let len: usize = alice.poll_tx(timeout, &mut frame).unwrap();
+    assert_eq!(alice.retransmits(), 1);
+
+    // retransmission is acknowledged, but not delivered again
+    assert_eq!(bob.receive(&frame[..len], &mut msg), Ok(None));
+    assert_eq!(bob.duplicates(), 1);
+    let len: usize = bob.poll_tx(timeout + 1, &mut frame).unwrap();
+    assert_eq!(
+        alice.receive(&frame[..len], &mut msg),
+        Ok(Some(ArqEvent::Acked(seq)))
+    );
+}
+
+#[test]
+fn retries_exhausted() {
+    let mut alice: Arq<MTU> = Arq::new(LORA, TICK_HZ).set_retries(2);
+    let mut frame: [u8; MTU] = [0; MTU];
+    let timeout: u32 = alice.ack_timeout();
+
+    let seq: u8 = alice.send(&[0; MTU - 2]).unwrap();
+    let mut sent: u32 = 0;
+    let mut now: u32 = 0;
+    let failed_at: u32 = loop {
+        if alice.poll_tx(now, &mut frame).is_some() {
+            sent += 1;
+        }
+        if let Some(event) = alice.poll(now) {
+            assert_eq!(event, ArqEvent::Failed(seq));
+            break now;
+        }
+        now += 1;
+    };
+
+    assert_eq!(sent, 3);
+    assert_eq!(failed_at, 3 * timeout);
+    assert!(!alice.is_busy());
+    assert!(alice.send(&[0; MTU - 2]).is_ok());
+}
+
+#[test]
+fn errors() {
+    let mut arq: Arq<MTU> = Arq::new(FSK, TICK_HZ);
+    let mut msg: [u8; 4] = [0; 4];
+
+    assert_eq!(arq.send(&[0; MTU - 1]), Err(ArqError::PayloadLen));
+    assert_eq!(arq.receive(&[0x00], &mut msg), Err(ArqError::Frame));
+    assert_eq!(arq.receive(&[0x7F, 0x00], &mut msg), Err(ArqError::Frame));
+    assert_eq!(
+        arq.receive(&[0x00, 0x00, 1, 2, 3, 4, 5], &mut msg),
+        Err(ArqError::BufferTooSmall)
+    );
+    // unexpected ACK is ignored
+    assert_eq!(arq.receive(&[0x01, 0x00], &mut msg), Ok(None));
+}
@@ -0,0 +1,78 @@
+use stm32wl_hal_subghz::{CrcType, GenericCrc};
+
+const CHECK: &[u8] = b"123456789";
+
+#[test]
+fn check_values() {
+    assert_eq!(GenericCrc::new(CrcType::Byte2).checksum(CHECK), 0xE5CC);
+    assert_eq!(GenericCrc::CCITT.checksum(CHECK), 0x1A33);
+    assert_eq!(GenericCrc::IBM.checksum(CHECK), 0xAEE7);
+    assert_eq!(
+        GenericCrc::new(CrcType::Byte2Inverted)
+            .set_initial(0xFFFF)
+            .checksum(CHECK),
+        0xD64E
+    );
+    assert_eq!(
+        GenericCrc::new(CrcType::Byte1)
+            .set_polynomial(0x07)
+            .set_initial(0x00)
+            .checksum(CHECK),
+        0xF4
+    );
+    assert_eq!(
+        GenericCrc::new(CrcType::Byte1Inverted)
+            .set_polynomial(0x2F)
+            .set_initial(0xFF)
+            .checksum(CHECK),
+        0xDF
+    );
+}
+
+#[test]
+fn one_byte_ignores_msb() {
+    let crc: GenericCrc = GenericCrc::new(CrcType::Byte1)
+        .set_polynomial(0x07)
+        .set_initial(0x00);
+    assert_eq!(
+        crc.checksum(CHECK),
+        crc.set_polynomial(0xAB07)
+            .set_initial(0xCD00)
+            .checksum(CHECK)
+    );
+}
+
+#[test]
+fn disabled() {
+    let crc: GenericCrc = GenericCrc::new(CrcType::Disabled);
+    assert_eq!(crc.checksum(CHECK), 0);
+    assert!(crc.verify(CHECK));
+    assert!(crc.verify(&[]));
+}
+
+#[test]
+fn empty() {
+    assert_eq!(GenericCrc::IBM.checksum(&[]), 0xFFFF);
+    assert_eq!(GenericCrc::CCITT.checksum(&[]), !0x1D0F);
+}
+
+#[test]
+fn round_trip() {
+    let crcs: [GenericCrc; 4] = [
+        GenericCrc::IBM,
+        GenericCrc::CCITT,
+        GenericCrc::new(CrcType::Byte1).set_polynomial(0x07),
+        GenericCrc::new(CrcType::Byte1Inverted).set_polynomial(0x2F),
+    ];
+    for crc in crcs.iter() {
+        let mut frame: [u8; 11] = [0; 11];
+        frame[..9].copy_from_slice(CHECK);
+        let len: usize = 9 + crc.crc_len();
+        let sum: [u8; 2] = crc.checksum(CHECK).to_be_bytes();
+        frame[9..len].copy_from_slice(&sum[2 - crc.crc_len()..]);
+        assert!(crc.verify(&frame[..len]));
+
+        frame[0] ^= 1;
+        assert!(!crc.verify(&frame[..len]));
+    }
+}
@@ -0,0 +1,78 @@
+use stm32wl_hal_subghz::{Fhss, RfFreq};
+
+const BASE: u32 = 902_500_000;
+const SPACING: u32 = 500_000;
+
+#[test]
+fn permutation() {
+    let fhss: Fhss<50> = Fhss::new(BASE, SPACING, 0xDEAD_BEEF, 1);
+    let mut seen: [bool; 50] = [false; 50];
+    for ch in fhss.channels().iter() {
+        let idx: usize = (0..50)
+            .position(|i| RfFreq::from_frequency(BASE + i * SPACING) == *ch)
+            .unwrap();
+        assert!(!seen[idx], "channel {} repeated", idx);
+        seen[idx] = true;
+    }
+}
+
+#[test]
+fn same_seed_same_sequence() {
+    let tx: Fhss<64> = Fhss::new(BASE, SPACING, 42, 100);
+    let rx: Fhss<64> = Fhss::new(BASE, SPACING, 42, 100);
+    for ticks in (0..10_000).step_by(37) {
+        assert_eq!(tx.freq_at(ticks), rx.freq_at(ticks));
+    }
+}
+
+#[test]
+fn different_seed_different_sequence() {
+    let a: Fhss<50> = Fhss::new(BASE, SPACING, 1, 1);
+    let b: Fhss<50> = Fhss::new(BASE, SPACING, 2, 1);
+    assert_ne!(a.channels(), b.channels());
+}
+
+#[test]
+fn dwell_boundaries() {
+    let fhss: Fhss<50> = Fhss::new(BASE, SPACING, 7, 100);
+    assert_eq!(fhss.dwell(), 100);
+    assert_eq!(fhss.slot(0), 0);
+    assert_eq!(fhss.slot(99), 0);
+    assert_eq!(fhss.slot(100), 1);
+    assert_eq!(fhss.ticks_to_next_hop(0), 100);
+    assert_eq!(fhss.ticks_to_next_hop(99), 1);
+    assert_eq!(fhss.ticks_to_next_hop(100), 100);
+    assert_eq!(fhss.freq_at(99), fhss.freq(0));
+    assert_eq!(fhss.freq_at(100), fhss.freq(1));
+    // the sequence repeats after every channel is visited
+    assert_eq!(fhss.freq(50), fhss.freq(0));
+    assert_eq!(fhss.freq_at(50 * 100 + 1), fhss.freq(0));
+
+    // a dwell of zero is replaced with one
+    let fhss: Fhss<50> = Fhss::new(BASE, SPACING, 7, 0);
+    assert_eq!(fhss.dwell(), 1);
+    assert_eq!(fhss.slot(3), 3);
+    assert_eq!(fhss.ticks_to_next_hop(3), 1);
+}
+
+#[test]
+fn hop_clock_wrap() {
+    // 100 does not divide 2^32, the last slot before the wrap is short
+    let fhss: Fhss<50> = Fhss::new(BASE, SPACING, 7, 100);
+    assert_eq!(u32::MAX % 100, 95);
+    assert_eq!(fhss.ticks_to_next_hop(u32::MAX - 95), 96);
+    assert_eq!(fhss.ticks_to_next_hop(u32::MAX - 1), 2);
+    assert_eq!(fhss.ticks_to_next_hop(u32::MAX), 1);
+    assert_eq!(fhss.freq_at(u32::MAX.wrapping_add(1)), fhss.freq(0));
+
+    // 1024 divides 2^32, the wrap is on a slot boundary
+    let fhss: Fhss<50> = Fhss::new(BASE, SPACING, 7, 1024);
+    assert_eq!(fhss.ticks_to_next_hop(u32::MAX - 1023), 1024);
+    assert_eq!(fhss.ticks_to_next_hop(u32::MAX), 1);
+}
+
+#[test]
+#[should_panic]
+fn channel_overflow() {
+    let _: Fhss<2> = Fhss::new(u32::MAX, 1, 0, 1);
+}
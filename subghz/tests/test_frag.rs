@@ -0,0 +1,201 @@
+use stm32wl_hal_subghz::{FragError, Fragmenter, Reassembler, FRAG_HEADER_LEN, FRAG_MAX};
+
+const MTU: u8 = 255;
+
+fn data<const N: usize>() -> [u8; N] {
+    let mut ret: [u8; N] = [0; N];
+    ret.iter_mut()
+        .enumerate()
+        .for_each(|(idx, byte)| *byte = idx as u8);
+    ret
+}
+
+#[test]
+fn round_trip_reverse_order() {
+    let data: [u8; 2000] = data();
+    let frag = Fragmenter::new(&data, 42, MTU).unwrap();
+    assert_eq!(frag.count(), 8);
+
+    let mut rx_buf: [u8; 2048] = [0; 2048];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; MTU as usize] = [0; MTU as usize];
+
+    for idx in (1..frag.count()).rev() {
+        let len: usize = frag.fragment(idx, &mut pkt).unwrap().unwrap();
+        assert_eq!(reasm.push(&pkt[..len], 0), Ok(None));
+    }
+    let len: usize = frag.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 0), Ok(Some(&data[..])));
+    assert_eq!(reasm.msg_id(), None);
+}
+
+#[test]
+fn exact_multiple() {
+    let data: [u8; 502] = data();
+    let frag = Fragmenter::new(&data, 0, MTU).unwrap();
+    assert_eq!(frag.count(), 2);
+
+    let mut rx_buf: [u8; 502] = [0; 502];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; MTU as usize] = [0; MTU as usize];
+    let mut frag_iter = frag;
+    let mut msg_len: Option<usize> = None;
+    while let Some(len) = frag_iter.next_into(&mut pkt).unwrap() {
+        assert_eq!(len, MTU as usize);
+        msg_len = reasm.push(&pkt[..len], 0).unwrap().map(|msg| msg.len());
+    }
+    assert_eq!(msg_len, Some(data.len()));
+}
+
+#[test]
+fn empty() {
+    let frag = Fragmenter::new(&[], 1, MTU).unwrap();
+    assert_eq!(frag.count(), 1);
+
+    let mut pkt: [u8; FRAG_HEADER_LEN] = [0; FRAG_HEADER_LEN];
+    assert_eq!(frag.fragment(0, &mut pkt), Ok(Some(FRAG_HEADER_LEN)));
+
+    let mut rx_buf: [u8; 0] = [];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    assert_eq!(reasm.push(&pkt, 0), Ok(Some(&[][..])));
+}
+
+#[test]
+fn too_long() {
+    let data: [u8; FRAG_MAX * 11 + 1] = [0; FRAG_MAX * 11 + 1];
+    assert_eq!(Fragmenter::new(&data, 0, 15), Err(FragError::TooLong));
+    assert_eq!(
+        Fragmenter::new(&data[..FRAG_MAX * 11], 0, 15).map(|f| f.count()),
+        Ok(FRAG_MAX)
+    );
+}
+
+#[test]
+fn missing_and_duplicate() {
+    let data: [u8; 100] = data();
+    let frag = Fragmenter::new(&data, 5, 14).unwrap();
+    assert_eq!(frag.count(), 10);
+
+    let mut rx_buf: [u8; 100] = [0; 100];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; 14] = [0; 14];
+
+    for &idx in [0, 2, 2, 9].iter() {
+        let len: usize = frag.fragment(idx, &mut pkt).unwrap().unwrap();
+        assert_eq!(reasm.push(&pkt[..len], 0), Ok(None));
+    }
+    let bitmap: [u8; FRAG_MAX / 8] = reasm.received_bitmap();
+    assert_eq!(bitmap[0], 0b0000_0101);
+    assert_eq!(bitmap[1], 0b0000_0010);
+    assert!(bitmap[2..].iter().all(|&b| b == 0));
+
+    // retransmit the missing fragments
+    let missing: Vec<usize> = (0..frag.count())
+        .filter(|&idx| !reasm.is_received(idx))
+        .collect();
+    assert_eq!(missing, [1, 3, 4, 5, 6, 7, 8]);
+    let (last, rest) = missing.split_last().unwrap();
+    for &idx in rest.iter() {
+        let len: usize = frag.fragment(idx, &mut pkt).unwrap().unwrap();
+        assert_eq!(reasm.push(&pkt[..len], 0), Ok(None));
+    }
+    let len: usize = frag.fragment(*last, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 0), Ok(Some(&data[..])));
+}
+
+#[test]
+fn timeout() {
+    let data: [u8; 100] = data();
+    let frag = Fragmenter::new(&data, 5, 54).unwrap();
+    assert_eq!(frag.count(), 2);
+
+    let mut rx_buf: [u8; 100] = [0; 100];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; 54] = [0; 54];
+
+    let len: usize = frag.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], u32::MAX - 10), Ok(None));
+    assert!(!reasm.is_expired(88));
+    assert!(reasm.is_expired(89));
+
+    // the first fragment was discarded
+    let len: usize = frag.fragment(1, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 89), Ok(None));
+    assert!(!reasm.is_received(0));
+    assert!(reasm.is_received(1));
+}
+
+#[test]
+fn new_message_discards() {
+    let data: [u8; 100] = data();
+    let old = Fragmenter::new(&data, 1, 54).unwrap();
+    let new = Fragmenter::new(&data, 2, 54).unwrap();
+
+    let mut rx_buf: [u8; 100] = [0; 100];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; 54] = [0; 54];
+
+    let len: usize = old.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 0), Ok(None));
+    let len: usize = new.fragment(1, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 0), Ok(None));
+    assert_eq!(reasm.msg_id(), Some(2));
+    assert!(!reasm.is_received(0));
+}
+
+#[test]
+fn completed_not_delivered_twice() {
+    let data: [u8; 100] = data();
+    let single = Fragmenter::new(&data[..10], 7, 54).unwrap();
+    let multi = Fragmenter::new(&data, 8, 54).unwrap();
+    assert_eq!(single.count(), 1);
+
+    let mut rx_buf: [u8; 100] = [0; 100];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+    let mut pkt: [u8; 54] = [0; 54];
+
+    let len: usize = single.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 0), Ok(Some(&data[..10])));
+    // retransmission of a single fragment message
+    assert_eq!(reasm.push(&pkt[..len], 1), Ok(None));
+    assert_eq!(reasm.msg_id(), None);
+
+    let len: usize = multi.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 2), Ok(None));
+    let len: usize = multi.fragment(1, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 2), Ok(Some(&data[..])));
+    // late retransmissions of the completed message
+    for idx in 0..multi.count() {
+        let len: usize = multi.fragment(idx, &mut pkt).unwrap().unwrap();
+        assert_eq!(reasm.push(&pkt[..len], 3), Ok(None));
+    }
+    assert_eq!(reasm.msg_id(), None);
+
+    // a new message ID is accepted, after which the old ID starts a new message
+    let len: usize = single.fragment(0, &mut pkt).unwrap().unwrap();
+    assert_eq!(reasm.push(&pkt[..len], 4), Ok(Some(&data[..10])));
+}
+
+#[test]
+fn errors() {
+    let mut rx_buf: [u8; 10] = [0; 10];
+    let mut reasm = Reassembler::new(&mut rx_buf, 100);
+
+    assert_eq!(reasm.push(&[0, 0, 0], 0), Err(FragError::Header));
+    // index after last
+    assert_eq!(
+        reasm.push(&[0, 2, 1, 4, 0, 0, 0, 0], 0),
+        Err(FragError::Header)
+    );
+    // short fragment before the last
+    assert_eq!(reasm.push(&[0, 0, 1, 4, 0, 0], 0), Err(FragError::Header));
+    // does not fit the buffer
+    assert_eq!(
+        reasm.push(&[0, 3, 3, 4, 0, 0, 0, 0], 0),
+        Err(FragError::BufferTooSmall)
+    );
+
+    let frag = Fragmenter::new(&[0; 20], 0, 14).unwrap();
+    let mut pkt: [u8; 13] = [0; 13];
+    assert_eq!(frag.fragment(0, &mut pkt), Err(FragError::BufferTooSmall));
+}
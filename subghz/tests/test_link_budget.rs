@@ -0,0 +1,40 @@
+use stm32wl_hal_subghz::{
+    CodingRate, FskBandwidth, FskBitrate, LinkBudget, LoRaBandwidth, SpreadingFactor,
+};
+
+fn db_tenths(x: u32) -> f64 {
+    100.0 * f64::from(x).log10()
+}
+
+#[test]
+fn fsk_snr_limit_accuracy() {
+    const BW: FskBandwidth = FskBandwidth::Bw467;
+    for bps in (600..=300_000).step_by(997) {
+        let bitrate: FskBitrate = FskBitrate::from_bps(bps);
+        let lb: LinkBudget = LinkBudget::fsk(bitrate, BW);
+        let expected: f64 = 110.0 + db_tenths(bitrate.as_bps()) - db_tenths(BW.hertz());
+        let diff: f64 = (f64::from(lb.snr_limit_tenths()) - expected).abs();
+        assert!(
+            diff <= 1.0,
+            "bps={} expected={} actual={}",
+            bps,
+            expected,
+            lb.snr_limit_tenths()
+        );
+    }
+}
+
+#[test]
+fn lora_bandwidth_doubling() {
+    const BWS: [LoRaBandwidth; 3] = [
+        LoRaBandwidth::Bw125,
+        LoRaBandwidth::Bw250,
+        LoRaBandwidth::Bw500,
+    ];
+    for pair in BWS.windows(2) {
+        let narrow = LinkBudget::lora(SpreadingFactor::Sf7, pair[0], CodingRate::Cr45);
+        let wide = LinkBudget::lora(SpreadingFactor::Sf7, pair[1], CodingRate::Cr45);
+        assert_eq!(wide.sensitivity_tenths() - narrow.sensitivity_tenths(), 30);
+        assert!((wide.bitrate() as i64 - narrow.bitrate() as i64 * 2).abs() <= 1);
+    }
+}
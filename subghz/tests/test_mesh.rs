@@ -0,0 +1,235 @@
+mod common;
+
+use common::XorShift;
+use stm32wl_hal_subghz::{
+    LoRaPacketParams, Mesh, MeshError, MeshEvent, MESH_BROADCAST, MESH_HEADER_LEN,
+};
+
+const MTU: usize = 64;
+const CACHE: usize = 16;
+const TICK_HZ: u32 = 1000;
+
+type Node = Mesh<MTU, CACHE>;
+
+/// Simulated network, `links[a]` are the nodes in range of node `a`.
+///
+/// Node `n` has address `n + 1`, the channel is always free.
+/// Returns the events of each node after every node is idle.
+fn flood(
+    nodes: &mut [Node],
+    links: &[&[usize]],
+    start: u32,
+    rng: &mut XorShift,
+) -> Vec<Vec<MeshEvent>> {
+    let mut events: Vec<Vec<MeshEvent>> = vec![Vec::new(); nodes.len()];
+    let mut frame: [u8; MTU] = [0; MTU];
+    let mut msg: [u8; MTU] = [0; MTU];
+
+    for now in start..start + 10 * TICK_HZ {
+        for tx in 0..nodes.len() {
+            if !nodes[tx].is_tx_ready(now) {
+                continue;
+            }
+            let len: usize = nodes[tx].take_tx(&mut frame).unwrap();
+            for &rx in links[tx] {
+                if let Some(event) = nodes[rx]
+                    .receive(&frame[..len], now, rng, &mut msg)
+                    .unwrap()
+                {
+                    events[rx].push(event);
+                }
+            }
+        }
+        if nodes.iter().all(|node| !node.is_busy()) {
+            return events;
+        }
+    }
+    panic!("frames still queued");
+}
+
+fn nodes(n: u16, hop_limit: u8) -> Vec<Node> {
+    (1..=n)
+        .map(|addr| Node::new(addr, LoRaPacketParams::new(), TICK_HZ).set_hop_limit(hop_limit))
+        .collect()
+}
+
+#[test]
+fn line_unicast() {
+    // 1 - 2 - 3 - 4
+    let links: [&[usize]; 4] = [&[1], &[0, 2], &[1, 3], &[2]];
+    let mut rng: XorShift = XorShift(0x1234_5678);
+    let mut nodes: Vec<Node> = nodes(4, 3);
+
+    nodes[0].send(4, b"meter 7", 0).unwrap();
+    let events: Vec<Vec<MeshEvent>> = flood(&mut nodes, &links, 0, &mut rng);
+
+    assert_eq!(
+        events[3],
+        [MeshEvent::Received {
+            src: 1,
+            dst: 4,
+            len: 7
+        }]
+    );
+    assert!(events[..3].iter().all(|e| e.is_empty()));
+    assert_eq!(nodes[1].relayed(), 1);
+    assert_eq!(nodes[2].relayed(), 1);
+    // the destination does not relay
+    assert_eq!(nodes[3].relayed(), 0);
+}
+
+#[test]
+fn hop_limit() {
+    // 1 - 2 - 3 - 4, reaches node 3 after one rebroadcast
+    let links: [&[usize]; 4] = [&[1], &[0, 2], &[1, 3], &[2]];
+    let mut rng: XorShift = XorShift(42);
+    let mut nodes: Vec<Node> = nodes(4, 1);
+
+    nodes[0].send(MESH_BROADCAST, b"ping", 0).unwrap();
+    let events: Vec<Vec<MeshEvent>> = flood(&mut nodes, &links, 0, &mut rng);
+
+    let received = MeshEvent::Received {
+        src: 1,
+        dst: MESH_BROADCAST,
+        len: 4,
+    };
+    assert_eq!(events[1], [received]);
+    assert_eq!(events[2], [received]);
+    assert!(events[3].is_empty());
+    assert_eq!(nodes[2].relayed(), 0);
+}
+
+#[test]
+fn duplicate_suppression() {
+    // fully connected, every node hears every rebroadcast
+    let links: [&[usize]; 5] = [
+        &[1, 2, 3, 4],
+        &[0, 2, 3, 4],
+        &[0, 1, 3, 4],
+        &[0, 1, 2, 4],
+        &[0, 1, 2, 3],
+    ];
+    let mut rng: XorShift = XorShift(0xDEAD_BEEF);
+    let mut nodes: Vec<Node> = nodes(5, 7);
+
+    for n in 0..10u32 {
+        let src: usize = (n % 5) as usize;
+        let start: u32 = n * 10 * TICK_HZ;
+        nodes[src]
+            .send(MESH_BROADCAST, &n.to_le_bytes(), start)
+            .unwrap();
+        let events: Vec<Vec<MeshEvent>> = flood(&mut nodes, &links, start, &mut rng);
+        for (idx, events) in events.iter().enumerate() {
+            if idx == src {
+                assert!(events.is_empty());
+            } else {
+                assert_eq!(
+                    events,
+                    &[MeshEvent::Received {
+                        src: src as u16 + 1,
+                        dst: MESH_BROADCAST,
+                        len: 4
+                    }]
+                );
+            }
+        }
+    }
+
+    // each frame is rebroadcast once by every other node, and every copy
+    // after the first is a duplicate
+    let relayed: u32 = nodes.iter().map(|node| node.relayed()).sum();
+    let duplicates: u32 = nodes.iter().map(|node| node.duplicates()).sum();
+    assert_eq!(relayed, 10 * 4);
+    assert_eq!(duplicates, 10 * (5 * 4 - 4));
+}
+
+#[test]
+fn cache_timeout() {
+    let mut rng: XorShift = XorShift(7);
+    let mut alice: Node = Node::new(1, LoRaPacketParams::new(), TICK_HZ);
+    let mut bob: Node = Node::new(2, LoRaPacketParams::new(), TICK_HZ).set_cache_timeout(100);
+    let mut frame: [u8; MTU] = [0; MTU];
+    let mut msg: [u8; MTU] = [0; MTU];
+
+    alice.send(2, b"x", 0).unwrap();
+    let len: usize = alice.take_tx(&mut frame).unwrap();
+    let event = Some(MeshEvent::Received {
+        src: 1,
+        dst: 2,
+        len: 1,
+    });
+
+    assert_eq!(bob.receive(&frame[..len], 0, &mut rng, &mut msg), Ok(event));
+    assert_eq!(bob.receive(&frame[..len], 99, &mut rng, &mut msg), Ok(None));
+    assert_eq!(bob.duplicates(), 1);
+    assert_eq!(
+        bob.receive(&frame[..len], 100, &mut rng, &mut msg),
+        Ok(event)
+    );
+}
+
+#[test]
+fn channel_busy() {
+    let mut rng: XorShift = XorShift(99);
+    let mut node: Node = Node::new(1, LoRaPacketParams::new(), TICK_HZ).set_cad_retries(2);
+
+    let seq: u8 = node.send(MESH_BROADCAST, b"x", 0).unwrap();
+    let mut now: u32 = 0;
+    let mut attempts: u32 = 0;
+    let event: MeshEvent = loop {
+        if node.is_tx_ready(now) {
+            attempts += 1;
+            if let Some(event) = node.channel_busy(now, &mut rng) {
+                break event;
+            }
+            assert!(!node.is_tx_ready(now));
+        }
+        now += 1;
+    };
+
+    assert_eq!(event, MeshEvent::ChannelBusy(seq));
+    assert_eq!(attempts, 3);
+    assert!(!node.is_busy());
+}
+
+#[test]
+fn relay_dropped_when_busy() {
+    let mut rng: XorShift = XorShift(3);
+    let mut alice: Node = Node::new(1, LoRaPacketParams::new(), TICK_HZ);
+    let mut bob: Node = Node::new(2, LoRaPacketParams::new(), TICK_HZ);
+    let mut frame: [u8; MTU] = [0; MTU];
+    let mut msg: [u8; MTU] = [0; MTU];
+
+    alice.send(3, b"x", 0).unwrap();
+    let len: usize = alice.take_tx(&mut frame).unwrap();
+    bob.send(3, b"y", 0).unwrap();
+
+    assert_eq!(bob.receive(&frame[..len], 0, &mut rng, &mut msg), Ok(None));
+    assert_eq!(bob.dropped(), 1);
+    assert_eq!(bob.take_tx(&mut frame), Some(MESH_HEADER_LEN + 1));
+    assert_eq!(&frame[MESH_HEADER_LEN..MESH_HEADER_LEN + 1], b"y");
+}
+
+#[test]
+fn errors() {
+    let mut rng: XorShift = XorShift(1);
+    let mut node: Node = Node::new(1, LoRaPacketParams::new(), TICK_HZ);
+    let mut msg: [u8; 2] = [0; 2];
+
+    assert_eq!(
+        node.send(2, &[0; MTU - MESH_HEADER_LEN + 1], 0),
+        Err(MeshError::PayloadLen)
+    );
+    assert_eq!(
+        node.receive(&[0; MESH_HEADER_LEN - 1], 0, &mut rng, &mut msg),
+        Err(MeshError::Frame)
+    );
+    assert_eq!(
+        node.receive(&[0xFF, 0xFF, 1, 0, 0, 0], 0, &mut rng, &mut msg),
+        Err(MeshError::Frame)
+    );
+    assert_eq!(
+        node.receive(&[2, 0, 1, 0, 0, 0, 1, 2, 3], 0, &mut rng, &mut msg),
+        Err(MeshError::BufferTooSmall)
+    );
+}
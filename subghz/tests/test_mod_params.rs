@@ -1,4 +1,6 @@
-use stm32wl_hal_subghz::{FskBandwidth, FskBitrate, FskFdev, LoRaBandwidth};
+use stm32wl_hal_subghz::{
+    FskBandwidth, FskBitrate, FskFdev, FskModParams, FskModParamsError, LoRaBandwidth,
+};
 
 #[test]
 fn fsk_bw_ord() {
@@ -69,3 +71,40 @@ fn fsk_fdev_from_hertz_limits() {
     assert_eq!(ONE.as_hertz(), 0);
     assert_eq!(MAX.as_hertz(), 6_967_294);
 }
+
+#[test]
+fn fsk_bw_smallest_for() {
+    let mut prev: Option<FskBandwidth> = None;
+    for hz in (0..=467_000).step_by(100) {
+        let bw: FskBandwidth = FskBandwidth::smallest_for(hz).unwrap();
+        assert!(bw.hertz() >= hz);
+        if let Some(prev) = prev {
+            assert!(bw >= prev);
+        }
+        prev = Some(bw);
+    }
+    assert_eq!(FskBandwidth::smallest_for(467_001), None);
+}
+
+#[test]
+fn fsk_mod_params_validate_limits() {
+    const PARAMS: FskModParams = FskModParams::new().set_bandwidth(FskBandwidth::Bw467);
+
+    assert!(matches!(
+        PARAMS.set_bitrate(FskBitrate::from_bps(500)).validate(),
+        Err(FskModParamsError::Bitrate(e)) if e.under()
+    ));
+    assert!(matches!(
+        PARAMS.set_bitrate(FskBitrate::from_bps(400_000)).validate(),
+        Err(FskModParamsError::Bitrate(e)) if e.over()
+    ));
+    assert!(matches!(
+        PARAMS.set_fdev(FskFdev::from_hertz(500)).validate(),
+        Err(FskModParamsError::Fdev(e)) if e.under()
+    ));
+    assert!(matches!(
+        PARAMS.set_fdev(FskFdev::from_hertz(250_000)).validate(),
+        Err(FskModParamsError::Fdev(e)) if e.over()
+    ));
+    assert_eq!(PARAMS.validate(), Ok(()));
+}
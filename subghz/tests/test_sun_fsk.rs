@@ -0,0 +1,112 @@
+use stm32wl_hal_subghz::{
+    CrcType, HeaderType, SunFcs, SunFsk, SunFskError, SunPhr, SunSfd, SUN_PHR_LEN, SUN_PSDU_MAX,
+};
+
+const CHECK: &[u8] = b"123456789";
+
+#[test]
+fn crc16_fcs() {
+    let sun: SunFsk = SunFsk::new().set_fcs(SunFcs::Crc16).set_whitening_en(false);
+    let mut frame: [u8; 16] = [0; 16];
+    let len: usize = sun.encode(CHECK, &mut frame).unwrap();
+    assert_eq!(len, SUN_PHR_LEN + 9 + 2);
+    assert_eq!(frame[..2], [0x10, 11]);
+    // payload bits are sent LSB first
+    assert_eq!(frame[2], b'1'.reverse_bits());
+    // CRC-16/KERMIT of the check string is 0x2189, sent MSB first
+    assert_eq!(frame[11..13], 0x2189_u16.reverse_bits().to_be_bytes());
+}
+
+#[test]
+fn crc32_fcs() {
+    let sun: SunFsk = SunFsk::new().set_whitening_en(false);
+    let mut frame: [u8; 16] = [0; 16];
+    let len: usize = sun.encode(CHECK, &mut frame).unwrap();
+    assert_eq!(len, SUN_PHR_LEN + 9 + 4);
+    assert_eq!(frame[..2], [0x00, 13]);
+    // CRC-32 of the check string is 0xCBF43926, sent MSB first
+    assert_eq!(frame[11..15], 0xCBF4_3926_u32.reverse_bits().to_be_bytes());
+}
+
+#[test]
+fn pn9_whitening() {
+    // PN9 sequence of IEEE 802.15.4g with the all ones seed, in transmission
+    // order, MSB first
+    const PN9: [u8; 16] = [
+        0xFF, 0x87, 0xB8, 0x59, 0xB7, 0xA1, 0xCC, 0x24, 0x57, 0x5E, 0x4B, 0x9C, 0x0E, 0xE9, 0xEA,
+        0x50,
+    ];
+
+    // an all zero PSDU, the CRC-16 of zeros is zero, is sent as the PN9
+    // sequence
+    let sun: SunFsk = SunFsk::new().set_fcs(SunFcs::Crc16);
+    let mut frame: [u8; 18] = [0; 18];
+    let len: usize = sun.encode(&[0; 14], &mut frame).unwrap();
+    assert_eq!(len, 18);
+    // PHR is not whitened
+    assert_eq!(frame[..2], [0x18, 16]);
+    assert_eq!(frame[2..], PN9);
+    assert_eq!(SunFsk::decode(&mut frame), Ok(&[0; 14][..]));
+}
+
+#[test]
+fn round_trip() {
+    let payload: Vec<u8> = (0..=250).map(|n| n as u8).collect();
+    for &fcs in [SunFcs::Crc16, SunFcs::Crc32].iter() {
+        for &whitening in [false, true].iter() {
+            let sun: SunFsk = SunFsk::new().set_fcs(fcs).set_whitening_en(whitening);
+            let data: &[u8] = &payload[..240];
+            let mut frame: [u8; 255] = [0; 255];
+            assert_eq!(
+                sun.encode(data, &mut frame),
+                Ok(SUN_PHR_LEN + data.len() + fcs.fcs_len())
+            );
+            let phr: SunPhr = SunPhr::from_bytes([frame[0], frame[1]]).unwrap();
+            assert_eq!(phr.fcs(), fcs);
+            assert_eq!(phr.whitening_en(), whitening);
+            // trailing bytes from a fixed length reception are ignored
+            assert_eq!(SunFsk::decode(&mut frame), Ok(data));
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    let sun: SunFsk = SunFsk::new();
+    let mut frame: [u8; 8] = [0; 8];
+    assert_eq!(
+        sun.encode(&[0; 4], &mut frame[..7]),
+        Err(SunFskError::BufferTooSmall)
+    );
+    assert_eq!(
+        sun.encode(&[0; SUN_PSDU_MAX - 3], &mut [0; 2100]),
+        Err(SunFskError::PsduLen)
+    );
+    assert!(sun.encode(&[0; SUN_PSDU_MAX - 4], &mut [0; 2100]).is_ok());
+    assert_eq!(
+        SunFsk::decode(&mut [0x00]),
+        Err(SunFskError::BufferTooSmall)
+    );
+    assert_eq!(
+        SunFsk::decode(&mut [0x80, 0x04, 0, 0, 0, 0]),
+        Err(SunFskError::Phr)
+    );
+}
+
+#[test]
+fn radio_cfg() {
+    let sun: SunFsk = SunFsk::new().set_sfd(SunSfd::Sfd0).set_preamble_len(8);
+    assert_eq!(sun.sync_word()[..2], [0x90, 0x4E]);
+
+    let params = sun.packet_params(100);
+    assert_eq!(params.preamble_len(), 64);
+    assert_eq!(params.sync_word_len(), 16);
+    assert_eq!(
+        params,
+        params
+            .set_header_type(HeaderType::Fixed)
+            .set_crc_type(CrcType::Disabled)
+            .set_whitening_enable(false)
+            .set_payload_len(100)
+    );
+}
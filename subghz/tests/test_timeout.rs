@@ -1,5 +1,7 @@
 use core::time::Duration;
-use stm32wl_hal_subghz::{Timeout, ValueError};
+use stm32wl_hal_subghz::{
+    LoRaBandwidth, LoRaModParams, LoRaSymbTimeout, SpreadingFactor, Timeout, ValueError,
+};
 
 #[test]
 fn saturate() {
@@ -44,3 +46,23 @@ fn upper_limit() {
         Err(ValueError::too_high(too_high.as_nanos(), high.as_nanos()))
     );
 }
+
+#[test]
+fn lora_symbols_round_trip() {
+    const MOD_PARAMS: LoRaModParams = LoRaModParams::new()
+        .set_sf(SpreadingFactor::Sf9)
+        .set_bw(LoRaBandwidth::Bw125);
+    for n in 0..1000 {
+        let to: Timeout = Timeout::from_lora_symbols(n, &MOD_PARAMS);
+        assert_eq!(to.as_lora_symbols(&MOD_PARAMS), n);
+    }
+}
+
+#[test]
+fn lora_symb_timeout_rounds_up() {
+    for n in 0..=u8::MAX {
+        let to: LoRaSymbTimeout = LoRaSymbTimeout::from_symbols(n);
+        assert!(to.symbols() >= u16::from(n.min(248)), "{}", n);
+        assert!(to <= LoRaSymbTimeout::MAX);
+    }
+}
@@ -0,0 +1,38 @@
+use stm32wl_hal_subghz::Whitening;
+
+#[test]
+fn pn9_all_ones() {
+    const EXPECTED: [u8; 8] = [0xFF, 0xE1, 0x1D, 0x9A, 0xED, 0x85, 0x33, 0x24];
+    let mut pn9: Whitening = Whitening::new(0x1FF);
+    EXPECTED
+        .iter()
+        .for_each(|&byte| assert_eq!(pn9.next_byte(), byte));
+}
+
+#[test]
+fn pn9_reset() {
+    const EXPECTED: [u8; 8] = [0x00, 0x11, 0x13, 0x57, 0x1B, 0x47, 0x2A, 0x36];
+    let mut buf: [u8; 8] = [0; 8];
+    Whitening::default().apply(&mut buf);
+    assert_eq!(buf, EXPECTED);
+}
+
+#[test]
+fn period() {
+    let mut pn9: Whitening = Whitening::new(0x1FF);
+    // 511 bits, stepped 8 bits at a time
+    (0..511).for_each(|_| {
+        pn9.next_byte();
+    });
+    assert_eq!(pn9.seed(), 0x1FF);
+}
+
+#[test]
+fn involution() {
+    const DATA: [u8; 5] = [0x01, 0x23, 0x45, 0x67, 0x89];
+    let mut buf: [u8; 5] = DATA;
+    Whitening::new(0x0A5).apply(&mut buf);
+    assert_ne!(buf, DATA);
+    Whitening::new(0x0A5).apply(&mut buf);
+    assert_eq!(buf, DATA);
+}
@@ -0,0 +1,233 @@
+use stm32wl_hal_subghz::{
+    CrcType, GenericCrc, GenericPacketParams, HeaderType, PreambleDetection, Wmbus, WmbusError,
+    WmbusFormat, WmbusMode,
+};
+
+const CRC: GenericCrc = GenericCrc::new(CrcType::Byte2Inverted)
+    .set_polynomial(0x3D65)
+    .set_initial(0x0000);
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|n| (n as u8).wrapping_mul(37)).collect()
+}
+
+#[test]
+fn crc_check_value() {
+    // CRC-16/EN-13757
+    assert_eq!(CRC.checksum(b"123456789"), 0xC2B7);
+}
+
+#[test]
+fn format_a_blocks() {
+    let wmbus: Wmbus = Wmbus::new(WmbusMode::C);
+    let data: Vec<u8> = data(30);
+    let mut chips: [u8; 64] = [0; 64];
+    let len: usize = wmbus.encode(&data, &mut chips).unwrap();
+    assert_eq!(len, 2 + 1 + 30 + 3 * 2);
+    assert_eq!(chips[..2], [0x54, 0xCD]);
+
+    let frame: &[u8] = &chips[2..len];
+    assert_eq!(frame[0], 30);
+    // first block: L-field and 9 bytes
+    assert_eq!(frame[1..10], data[..9]);
+    assert_eq!(frame[10..12], CRC.checksum(&frame[..10]).to_be_bytes());
+    // full block of 16 bytes
+    assert_eq!(frame[12..28], data[9..25]);
+    assert_eq!(frame[28..30], CRC.checksum(&frame[12..28]).to_be_bytes());
+    // partial last block
+    assert_eq!(frame[30..35], data[25..]);
+    assert_eq!(frame[35..37], CRC.checksum(&frame[30..35]).to_be_bytes());
+}
+
+#[test]
+fn format_b_blocks() {
+    let wmbus: Wmbus = Wmbus::new(WmbusMode::C).set_format(WmbusFormat::B);
+    let data: Vec<u8> = data(130);
+    let mut chips: [u8; 255] = [0; 255];
+    let len: usize = wmbus.encode(&data, &mut chips).unwrap();
+    assert_eq!(len, 2 + 1 + 130 + 2 * 2);
+    assert_eq!(chips[..2], [0x54, 0x3D]);
+
+    let frame: &[u8] = &chips[2..len];
+    // L-field includes the CRCs
+    assert_eq!(usize::from(frame[0]), 130 + 4);
+    // first and second block share a CRC
+    assert_eq!(frame[1..126], data[..125]);
+    assert_eq!(frame[126..128], CRC.checksum(&frame[..126]).to_be_bytes());
+    assert_eq!(frame[128..133], data[125..]);
+    assert_eq!(
+        frame[133..135],
+        CRC.checksum(&frame[128..133]).to_be_bytes()
+    );
+
+    // longest frame without a third block
+    let len: usize = wmbus.encode(&data[..125], &mut chips).unwrap();
+    assert_eq!(len, 2 + 128);
+    assert_eq!(chips[2], 127);
+}
+
+#[test]
+fn known_answer() {
+    // example telegram from the OMS specification, format A with block CRCs
+    const FRAME: [u8; 55] = [
+        0x2E, 0x44, 0x93, 0x15, 0x78, 0x56, 0x34, 0x12, 0x33, 0x03, 0x33, 0x63, 0x7A, 0x2A, 0x00,
+        0x20, 0x25, 0x59, 0x23, 0xC9, 0x5A, 0xAA, 0x26, 0xD1, 0xB2, 0xE7, 0x49, 0x3B, 0xC2, 0xAD,
+        0x01, 0x3E, 0xC4, 0xA6, 0xF6, 0xD3, 0x52, 0x9B, 0x52, 0x0E, 0xDF, 0xF0, 0xEA, 0x6D, 0xEF,
+        0xC9, 0x55, 0xB2, 0x9D, 0x6D, 0x69, 0xEB, 0xF3, 0xEC, 0x8A,
+    ];
+    let data: Vec<u8> = [
+        &FRAME[1..10],
+        &FRAME[12..28],
+        &FRAME[30..46],
+        &FRAME[48..53],
+    ]
+    .concat();
+    assert_eq!(data.len(), usize::from(FRAME[0]));
+
+    let wmbus: Wmbus = Wmbus::new(WmbusMode::C);
+    let mut chips: [u8; 64] = [0; 64];
+    let len: usize = wmbus.encode(&data, &mut chips).unwrap();
+    assert_eq!(chips[..2], [0x54, 0xCD]);
+    assert_eq!(chips[2..len], FRAME);
+
+    let mut out: [u8; 64] = [0; 64];
+    assert_eq!(wmbus.decode(&chips[..len], &mut out), Ok(&data[..]));
+}
+
+#[test]
+fn three_of_six() {
+    let wmbus: Wmbus = Wmbus::new(WmbusMode::T);
+    let mut data: [u8; 9] = [0; 9];
+    data[..8].copy_from_slice(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]);
+    let mut chips: [u8; 32] = [0; 32];
+    let len: usize = wmbus.encode(&data, &mut chips).unwrap();
+    assert_eq!(len, 18);
+
+    // L-field 0x09, then 0x01 0x23
+    let codes: u64 = u64::from_be_bytes([
+        0, 0, chips[0], chips[1], chips[2], chips[3], chips[4], chips[5],
+    ]);
+    assert_eq!(codes >> 42, 0b010110);
+    assert_eq!((codes >> 36) & 0x3F, 0b100101);
+    assert_eq!((codes >> 30) & 0x3F, 0b010110);
+    assert_eq!((codes >> 24) & 0x3F, 0b001101);
+    assert_eq!((codes >> 18) & 0x3F, 0b001110);
+    assert_eq!((codes >> 12) & 0x3F, 0b001011);
+    // every code has 3 ones
+    let ones: u32 = chips[..len].iter().map(|c| c.count_ones()).sum();
+    assert_eq!(ones, 12 * 2 * 3);
+
+    // odd number of codes, the last byte is padded with 0101
+    let len: usize = wmbus.encode(&[0; 10], &mut chips).unwrap();
+    assert_eq!(len, 23);
+    assert_eq!(chips[len - 1] & 0x0F, 0b0101);
+}
+
+#[test]
+fn manchester() {
+    let wmbus: Wmbus = Wmbus::new(WmbusMode::S);
+    let mut chips: [u8; 32] = [0; 32];
+    let len: usize = wmbus.encode(&[0xF0; 9], &mut chips).unwrap();
+    assert_eq!(len, 2 * 12);
+    // L-field 0x09
+    assert_eq!(chips[..2], [0x55, 0x96]);
+    assert_eq!(chips[2..4], [0xAA, 0x55]);
+}
+
+#[test]
+fn round_trip() {
+    let modes: [Wmbus; 4] = [
+        Wmbus::new(WmbusMode::S),
+        Wmbus::new(WmbusMode::T),
+        Wmbus::new(WmbusMode::C),
+        Wmbus::new(WmbusMode::C).set_format(WmbusFormat::B),
+    ];
+    for wmbus in modes.iter() {
+        for len in 9..=200 {
+            let data: Vec<u8> = data(len);
+            let mut chips: [u8; 512] = [0; 512];
+            let enc_len: usize = match wmbus.encode(&data, &mut chips) {
+                Ok(enc_len) => enc_len,
+                Err(e) => {
+                    assert_eq!(e, WmbusError::BufferTooSmall);
+                    assert!(wmbus.encoded_len(len).unwrap() > 512);
+                    continue;
+                }
+            };
+            assert_eq!(Some(enc_len), wmbus.encoded_len(len));
+            let mut out: [u8; 255] = [0; 255];
+            assert_eq!(
+                wmbus.decode(&chips[..enc_len], &mut out),
+                Ok(&data[..]),
+                "{:?} {}",
+                wmbus,
+                len
+            );
+            // trailing chips are ignored
+            assert_eq!(wmbus.decode(&chips, &mut out), Ok(&data[..]));
+        }
+    }
+}
+
+#[test]
+fn packet_params() {
+    assert_eq!(
+        WmbusMode::T.packet_params(60),
+        GenericPacketParams::new()
+            .set_preamble_len(40)
+            .set_preamble_detection(PreambleDetection::Bit16)
+            .set_sync_word_len(16)
+            .set_header_type(HeaderType::Fixed)
+            .set_payload_len(60)
+            .set_crc_type(CrcType::Disabled)
+            .set_whitening_enable(false)
+    );
+    assert_eq!(WmbusMode::S.sync_word()[..2], [0x76, 0x96]);
+}
+
+#[test]
+fn errors() {
+    let c: Wmbus = Wmbus::new(WmbusMode::C);
+    let t: Wmbus = Wmbus::new(WmbusMode::T);
+    let mut chips: [u8; 64] = [0; 64];
+    let mut out: [u8; 64] = [0; 64];
+
+    assert_eq!(c.encode(&[0; 8], &mut chips), Err(WmbusError::DataLen));
+    assert_eq!(c.encode(&[0; 256], &mut chips), Err(WmbusError::DataLen));
+    assert_eq!(
+        c.encode(&[0; 20], &mut chips[..20]),
+        Err(WmbusError::BufferTooSmall)
+    );
+
+    let len: usize = c.encode(&[0; 20], &mut chips).unwrap();
+    assert_eq!(
+        c.decode(&chips[..len - 1], &mut out),
+        Err(WmbusError::BufferTooSmall)
+    );
+    assert_eq!(
+        c.decode(&chips[..len], &mut out[..19]),
+        Err(WmbusError::BufferTooSmall)
+    );
+    chips[len - 3] ^= 1;
+    assert_eq!(c.decode(&chips[..len], &mut out), Err(WmbusError::Crc));
+    chips[1] = 0x00;
+    assert_eq!(c.decode(&chips[..len], &mut out), Err(WmbusError::Coding));
+    // L-field shorter than the C, M, and A fields
+    assert_eq!(
+        c.decode(&[0x54, 0xCD, 8], &mut out),
+        Err(WmbusError::LField)
+    );
+    // L-field 128 and 129 are invalid in format B
+    assert_eq!(
+        c.decode(&[0x54, 0x3D, 128], &mut out),
+        Err(WmbusError::LField)
+    );
+    assert_eq!(
+        c.decode(&[0x54, 0x3D, 129], &mut out),
+        Err(WmbusError::LField)
+    );
+
+    let len: usize = t.encode(&[0; 9], &mut chips).unwrap();
+    chips[0] = 0xFF;
+    assert_eq!(t.decode(&chips[..len], &mut out), Err(WmbusError::Coding));
+}